@@ -0,0 +1,225 @@
+// How the rules of a compiled lexer turn their DFA match into a token.
+// `CompiledLexer` uses this module and generated lexers include it as is, so
+// it only depends on `std`; not every lexer uses every item.
+
+use std::borrow::Cow;
+
+/// What a rule makes of the input its DFA pattern matched
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Symbol and regex scanners: the match is the token
+    Token,
+    /// An EOL scanner: the match runs through the line terminator
+    Line(Line),
+    /// A block scanner: the match runs through the end delimiter, or to the
+    /// end of the input when the block is not `terminated`
+    Block { block: Block, terminated: bool },
+}
+
+/// The text and facts of a token made by an `Action`
+#[allow(dead_code)]
+pub struct Lexeme {
+    pub value: String,
+    /// The block content with its escapes transformed, for typed values
+    pub literal: Option<String>,
+    pub had_escapes: bool,
+    pub unterminated: bool,
+}
+
+#[allow(dead_code)]
+impl Action {
+    /// The number of bytes of `input` the token takes when the DFA matched
+    /// `len` of them; `None` for a block missing its end delimiter, which is
+    /// an error unless the block allows it
+    pub fn consumed(&self, input: &str, len: usize) -> Option<usize> {
+        match self {
+            Action::Token | Action::Line(_) => Some(len),
+            Action::Block { block, terminated: true } => Some(block.end(input, len)),
+            Action::Block { block, terminated: false } => block.allow_unterminated.then_some(len),
+        }
+    }
+
+    /// The token for a DFA match of `len` bytes of `input`, or the delimiters
+    /// of a block missing its end delimiter
+    pub fn lexeme<'a>(&'a self, input: &str, len: usize) -> Result<Lexeme, (&'a str, &'a str)> {
+        match self {
+            Action::Token => Ok(Lexeme::new(input[..len].to_string())),
+            Action::Line(line) => Ok(Lexeme::new(line.value(&input[..len]).to_string())),
+            Action::Block { block, terminated } => block.lexeme(input, len, *terminated),
+        }
+    }
+}
+
+impl Lexeme {
+    fn new(value: String) -> Self {
+        Self {
+            value,
+            literal: None,
+            had_escapes: false,
+            unterminated: false,
+        }
+    }
+}
+
+/// An EOL scanner's token value
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub delimiter_len: usize,
+    pub include_delimiter: bool,
+    pub include_terminator: bool,
+    /// The characters line terminators start with under the scanner's `LineEndings`
+    pub terminators: &'static str,
+}
+
+impl Line {
+    /// The value of the line `matched`, which ends with its terminator, if any
+    fn value<'a>(&self, matched: &'a str) -> &'a str {
+        let start = if self.include_delimiter { 0 } else { self.delimiter_len };
+        let end = match self.include_terminator {
+            true => matched.len(),
+            false => matched[self.delimiter_len..]
+                .find(|c| self.terminators.contains(c))
+                .map_or(matched.len(), |at| self.delimiter_len + at),
+        };
+        &matched[start..end]
+    }
+}
+
+/// A block scanner with one delimiter pair, no nesting and two-character escapes
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub start: Cow<'static, str>,
+    pub end: Cow<'static, str>,
+    pub include_delimiters: bool,
+    /// The escapes the scanner skips, one per escape character; none in raw mode
+    pub escapes: Cow<'static, [Escape]>,
+    /// The escape map, sorted by sequence
+    pub escape_map: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+    pub transform_escapes: bool,
+    pub typed_values: bool,
+    pub allow_unterminated: bool,
+}
+
+/// An escape of a compiled block: an escape character and the character after it
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Escape {
+    /// A simple escape; by default the escaped character is looked up in the escape map
+    Simple(char, Replace),
+    /// The default backslash escape, kept as written
+    Backslash,
+    /// The one-character end delimiter written twice, by default standing for itself
+    Doubled(char, Replace),
+}
+
+/// The transform of an escape rule
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Replace {
+    Default,
+    Verbatim,
+    Remove,
+    HexCodePoint,
+}
+
+#[allow(dead_code)]
+impl Escape {
+    /// The character the escape starts with
+    pub fn escape_char(&self) -> char {
+        match *self {
+            Escape::Simple(c, _) | Escape::Doubled(c, _) => c,
+            Escape::Backslash => '\\',
+        }
+    }
+}
+
+impl Block {
+    /// The end of a terminated block the DFA matched as `input[..len]`. With a
+    /// doubled delimiter escape the match also takes the character after the
+    /// block, unless it reached the end of the input, to tell the end
+    /// delimiter from an escape.
+    fn end(&self, input: &str, len: usize) -> usize {
+        let doubled = self.escapes.iter().any(|escape| matches!(escape, Escape::Doubled(..)));
+        match input[..len].chars().next_back() {
+            Some(last) if doubled && !input[..len].ends_with(&*self.end) => len - last.len_utf8(),
+            _ => len,
+        }
+    }
+
+    fn lexeme(&self, input: &str, len: usize, terminated: bool) -> Result<Lexeme, (&str, &str)> {
+        if !terminated && !self.allow_unterminated {
+            return Err((&self.start, &self.end));
+        }
+        let content_end = if terminated { self.end(input, len) - self.end.len() } else { len };
+        let content = &input[self.start.len()..content_end];
+
+        let (unescaped, had_escapes) = self.unescape(content);
+        let body = if self.transform_escapes { unescaped.as_str() } else { content };
+        let value = match (self.include_delimiters, terminated) {
+            (true, true) => format!("{}{}{}", self.start, body, self.end),
+            (true, false) => format!("{}{}", self.start, body),
+            (false, _) => body.to_string(),
+        };
+        Ok(Lexeme {
+            value,
+            literal: self.typed_values.then_some(unescaped),
+            had_escapes,
+            unterminated: !terminated,
+        })
+    }
+
+    /// Returns `content` with its escapes transformed, and whether it had any
+    fn unescape(&self, content: &str) -> (String, bool) {
+        let mut result = String::with_capacity(content.len());
+        let mut had_escapes = false;
+        let mut chars = content.char_indices().peekable();
+
+        while let Some((at, c)) = chars.next() {
+            let escape = self.escapes.iter().find(|escape| escape.escape_char() == c);
+            let escaped = chars.peek().map(|&(_, escaped)| escaped);
+            let (Some(escape), Some(escaped)) = (escape, escaped) else {
+                result.push(c);
+                continue;
+            };
+            if matches!(escape, Escape::Doubled(..)) && escaped != c {
+                result.push(c);
+                continue;
+            }
+            chars.next();
+            had_escapes = true;
+
+            let sequence = &content[at..at + c.len_utf8() + escaped.len_utf8()];
+            let (default, replace) = match *escape {
+                Escape::Simple(_, replace) => {
+                    let escaped = &sequence[c.len_utf8()..];
+                    let mapped = self.escape_map.iter().find(|(from, _)| from == escaped);
+                    (mapped.map_or(escaped, |(_, to)| &**to), replace)
+                }
+                Escape::Backslash => (sequence, Replace::Verbatim),
+                Escape::Doubled(_, replace) => (&sequence[c.len_utf8()..], replace),
+            };
+            match replace {
+                Replace::Default => result.push_str(default),
+                Replace::Verbatim => result.push_str(sequence),
+                Replace::Remove => {}
+                Replace::HexCodePoint => match hex_code_point(sequence) {
+                    Some(ch) => result.push(ch),
+                    None => result.push_str(sequence),
+                },
+            }
+        }
+        (result, had_escapes)
+    }
+}
+
+/// Decodes the hex digits after the first character of `sequence`, as
+/// `EscapeTransform::HexCodePoint` does
+fn hex_code_point(sequence: &str) -> Option<char> {
+    let rest = sequence.chars().next().map_or("", |c| &sequence[c.len_utf8()..]);
+    let digits = &rest[rest.find(|c: char| c.is_ascii_hexdigit())?..];
+    let digits = &digits[..digits.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(digits.len())];
+    Some(u32::from_str_radix(digits, 16).ok().and_then(char::from_u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+}
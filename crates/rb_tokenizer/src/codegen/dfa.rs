@@ -0,0 +1,137 @@
+use regex_automata::dfa::{dense, Automaton, StartKind};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+/// Index of the dead state in `DfaTables`; a search stops once it gets there
+pub(crate) const DEAD: usize = 0;
+
+/// A DFA over all of a tokenizer's scanners, flattened into plain tables.
+///
+/// Pattern `i` is scanner `i`. The DFA is anchored and uses leftmost-first
+/// semantics, so the earliest pattern that matches at all wins and its match
+/// length is the one its own `Regex::find` would report — exactly how the
+/// interpreted tokenizer picks the first scanner that accepts the input.
+///
+/// Matches are delayed by one byte, as in `regex-automata`: entering a match
+/// state on the byte at `i` means a match ending at `i`. `eoi_matches` records
+/// the match reached by running out of input in each state.
+#[derive(Clone)]
+pub(crate) struct DfaTables {
+    pub(crate) classes: [u8; 256],
+    pub(crate) class_count: usize,
+    pub(crate) start: usize,
+    /// `state * class_count + class` to the next state
+    pub(crate) transitions: Vec<u32>,
+    /// The pattern matched on entering each state, plus one; zero for none
+    pub(crate) matches: Vec<u32>,
+    /// The pattern matched at the end of input in each state, plus one; zero for none
+    pub(crate) eoi_matches: Vec<u32>,
+}
+
+impl DfaTables {
+    pub(crate) fn build(patterns: &[String]) -> Result<Self, String> {
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .match_kind(MatchKind::LeftmostFirst)
+                    .start_kind(StartKind::Anchored)
+                    .minimize(true),
+            )
+            .build_many(patterns)
+            .map_err(|e| e.to_string())?;
+
+        let byte_classes = dfa.byte_classes();
+        let mut classes = [0u8; 256];
+        let mut representatives = Vec::new();
+        for byte in 0..=255u8 {
+            let class = byte_classes.get(byte);
+            classes[byte as usize] = class;
+            if class as usize == representatives.len() {
+                representatives.push(byte);
+            }
+        }
+        let class_count = representatives.len();
+
+        let start_id = dfa
+            .start_state(&start::Config::new().anchored(Anchored::Yes))
+            .map_err(|e| e.to_string())?;
+
+        // Number the states breadth-first, with the dead state fixed at zero
+        let mut index: HashMap<StateID, usize> = HashMap::new();
+        let mut order: Vec<StateID> = Vec::new();
+        let dead_id = StateID::ZERO;
+        if !dfa.is_dead_state(dead_id) {
+            return Err("the DFA has no dead state".to_string());
+        }
+        let mut queue = VecDeque::new();
+        for id in [dead_id, start_id] {
+            if let Entry::Vacant(entry) = index.entry(id) {
+                entry.insert(order.len());
+                order.push(id);
+                queue.push_back(id);
+            }
+        }
+
+        // States are dequeued in numbering order, so rows are appended in order too
+        let mut transitions = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            for &byte in &representatives {
+                let next = dfa.next_state(id, byte);
+                if dfa.is_quit_state(next) {
+                    return Err("the DFA would need to give up on some inputs".to_string());
+                }
+                let next_index = *index.entry(next).or_insert_with(|| {
+                    order.push(next);
+                    queue.push_back(next);
+                    order.len() - 1
+                });
+                transitions.push(next_index as u32);
+            }
+        }
+
+        let match_of = |id: StateID| -> u32 {
+            if dfa.is_match_state(id) {
+                dfa.match_pattern(id, 0).as_u32() + 1
+            } else {
+                0
+            }
+        };
+        let matches = order.iter().map(|&id| match_of(id)).collect();
+        let eoi_matches = order.iter().map(|&id| match_of(dfa.next_eoi_state(id))).collect();
+
+        Ok(Self {
+            classes,
+            class_count,
+            start: index[&start_id],
+            transitions,
+            matches,
+            eoi_matches,
+        })
+    }
+
+    pub(crate) fn state_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Runs the DFA over `input`; returns the winning pattern and its match length
+    pub(crate) fn find(&self, input: &[u8]) -> Option<(usize, usize)> {
+        let mut state = self.start;
+        let mut found = None;
+        for (at, &byte) in input.iter().enumerate() {
+            state = self.transitions[state * self.class_count + self.classes[byte as usize] as usize] as usize;
+            if state == DEAD {
+                return found;
+            }
+            if self.matches[state] != 0 {
+                found = Some((self.matches[state] as usize - 1, at));
+            }
+        }
+        if self.eoi_matches[state] != 0 {
+            found = Some((self.eoi_matches[state] as usize - 1, input.len()));
+        }
+        found
+    }
+}
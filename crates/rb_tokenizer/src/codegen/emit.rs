@@ -0,0 +1,218 @@
+use super::actions::{Action, Block, Escape};
+use super::lexer::CompiledLexer;
+use crate::tokens::TokenKind;
+use std::fmt::Write;
+
+/// Writes the Rust source of a standalone lexer for `lexer`.
+///
+/// The DFA becomes constant tables and a small search loop, and the rules'
+/// actions are the source of `actions.rs`, so matching needs nothing from
+/// `rb_tokenizer`; the source only uses its `Scanner` trait, token types and
+/// `Tokenizer` to produce the same tokens as the original.
+pub(crate) fn emit<K: TokenKind>(
+    lexer: &CompiledLexer<K>,
+    name: impl Fn(K) -> String,
+    lexer_name: &str,
+    constructor_name: &str,
+) -> String {
+    let tables = &lexer.tables;
+    let state_type = if tables.state_count() <= u16::MAX as usize + 1 { "u16" } else { "u32" };
+    let mut out = String::new();
+
+    out.push_str("// @generated by rb_tokenizer::codegen from a tokenizer definition; do not edit.\n");
+    out.push_str("// Include it in a module of its own, e.g.\n");
+    out.push_str("// `mod lexer { include!(concat!(env!(\"OUT_DIR\"), \"/lexer.rs\")); }`\n\n");
+
+    writeln!(out, "const CLASS_COUNT: usize = {};", tables.class_count).unwrap();
+    writeln!(out, "const START: usize = {};\n", tables.start).unwrap();
+    write_table(&mut out, "CLASSES", "u8", &tables.classes);
+    write_table(&mut out, "TRANSITIONS", state_type, &tables.transitions);
+    write_table(&mut out, "MATCHES", state_type, &tables.matches);
+    write_table(&mut out, "EOI_MATCHES", state_type, &tables.eoi_matches);
+
+    out.push_str(
+        "/// Runs the DFA over `input`; returns the winning rule and its match length
+fn find(input: &[u8]) -> Option<(usize, usize)> {
+    let mut state = START;
+    let mut found = None;
+    for (at, &byte) in input.iter().enumerate() {
+        state = TRANSITIONS[state * CLASS_COUNT + CLASSES[byte as usize] as usize] as usize;
+        if state == 0 {
+            return found;
+        }
+        if MATCHES[state] != 0 {
+            found = Some((MATCHES[state] as usize - 1, at));
+        }
+    }
+    if EOI_MATCHES[state] != 0 {
+        found = Some((EOI_MATCHES[state] as usize - 1, input.len()));
+    }
+    found
+}
+
+",
+    );
+
+    out.push_str(include_str!("actions.rs"));
+    out.push('\n');
+
+    // Both rules of a block scanner share its block
+    let mut blocks: Vec<&Block> = Vec::new();
+    for rule in &lexer.rules {
+        if let Action::Block { block, terminated: true } = &rule.action {
+            writeln!(out, "const BLOCK_{}: Block = {};\n", blocks.len(), block_source(block)).unwrap();
+            blocks.push(block);
+        }
+    }
+
+    writeln!(out, "/// Token type, sub-type and action of each rule, in scanner order").unwrap();
+    writeln!(out, "static RULES: [(&str, Option<&str>, Action); {}] = [", lexer.rules.len()).unwrap();
+    let mut block = 0;
+    for rule in &lexer.rules {
+        let action = match &rule.action {
+            Action::Token => "Action::Token".to_string(),
+            Action::Line(line) => format!(
+                "Action::Line(Line {{ delimiter_len: {}, include_delimiter: {}, include_terminator: {}, terminators: {:?} }})",
+                line.delimiter_len, line.include_delimiter, line.include_terminator, line.terminators
+            ),
+            Action::Block { terminated, .. } => {
+                // The unterminated rule follows the terminated one
+                let source = format!("Action::Block {{ block: BLOCK_{}, terminated: {} }}", block, terminated);
+                block += usize::from(!terminated);
+                source
+            }
+        };
+        writeln!(out, "    ({:?}, {}, {}),", name(rule.token_type), option(rule.token_sub_type, &name), action).unwrap();
+    }
+    out.push_str("];\n\n");
+
+    out.push_str(
+        "/// Matches a token at the start of `input`; returns its type, sub-type and the
+/// byte length it takes, or `None` if no rule matches or `input` starts a block
+/// missing its end delimiter
+pub fn match_token(input: &str) -> Option<(&'static str, Option<&'static str>, usize)> {
+    let (rule, len) = find(input.as_bytes())?;
+    let (token_type, token_sub_type, action) = &RULES[rule];
+    Some((token_type, *token_sub_type, action.consumed(input, len)?))
+}
+
+",
+    );
+
+    writeln!(out, "/// Lexer compiled from {} scanners to a {}-state DFA", lexer.rules.len(), tables.state_count()).unwrap();
+    out.push_str("#[derive(Debug, Default, Clone, Copy)]\n");
+    writeln!(out, "pub struct {};\n", lexer_name).unwrap();
+
+    writeln!(out, "impl ::rb_tokenizer::scanners::Scanner for {} {{", lexer_name).unwrap();
+    out.push_str(
+        "    fn scan(&self, input: &str) -> Result<Option<::rb_tokenizer::tokens::Token>, ::rb_tokenizer::tokens::TokenizationError> {
+        let Some((rule, len)) = find(input.as_bytes()) else {
+            return Ok(None);
+        };
+        let (token_type, token_sub_type, action) = &RULES[rule];
+        let lexeme = action.lexeme(input, len).map_err(|(start, end)| {
+            ::rb_tokenizer::tokens::TokenizationError::UnmatchedBlockDelimiter(start.to_string(), end.to_string())
+        })?;
+
+        let mut token = ::rb_tokenizer::tokens::Token::new(*token_type, *token_sub_type, lexeme.value, 0, 0);
+        token.literal = lexeme.literal.map(::rb_tokenizer::tokens::TokenValue::Str);
+        if lexeme.had_escapes {
+            token.flags.insert(::rb_tokenizer::tokens::TokenFlags::HAD_ESCAPES);
+        }
+        if lexeme.unterminated {
+            token.flags.insert(::rb_tokenizer::tokens::TokenFlags::UNTERMINATED);
+        }
+        Ok(Some(token))
+    }
+
+    fn consumed_len(&self, input: &str, token: &::rb_tokenizer::tokens::Token) -> usize {
+        // The value may leave out delimiters or line terminators
+        find(input.as_bytes())
+            .and_then(|(rule, len)| RULES[rule].2.consumed(input, len))
+            .unwrap_or(token.value.len())
+    }
+}
+
+",
+    );
+
+    let config = &lexer.config;
+    writeln!(out, "/// A tokenizer with the original configuration, scanning with `{}`", lexer_name).unwrap();
+    writeln!(out, "pub fn {}() -> ::rb_tokenizer::Tokenizer {{", constructor_name).unwrap();
+    out.push_str("    let config = ::rb_tokenizer::TokenizerConfig {\n");
+    writeln!(out, "        tokenize_whitespace: {},", config.tokenize_whitespace).unwrap();
+    writeln!(out, "        continue_on_error: {},", config.continue_on_error).unwrap();
+    writeln!(out, "        error_tolerance_limit: {},", config.error_tolerance_limit).unwrap();
+    writeln!(out, "        track_token_positions: {},", config.track_token_positions).unwrap();
+    writeln!(out, "        column_mode: ::rb_tokenizer::ColumnMode::{:?},", config.column_mode).unwrap();
+    writeln!(out, "        line_endings: ::rb_tokenizer::LineEndings::{:?},", config.line_endings).unwrap();
+    writeln!(out, "        report_line_ending_style: {},", config.report_line_ending_style).unwrap();
+    out.push_str("    };\n");
+    out.push_str("    let mut tokenizer = ::rb_tokenizer::Tokenizer::with_config(config);\n");
+    writeln!(out, "    tokenizer.add_scanner(Box::new({}));", lexer_name).unwrap();
+    out.push_str("    tokenizer\n}\n");
+
+    out
+}
+
+/// A `Block` expression with the same fields as `block`
+fn block_source(block: &Block) -> String {
+    let escapes: Vec<String> = block
+        .escapes
+        .iter()
+        .map(|escape| match escape {
+            Escape::Simple(c, replace) => format!("Escape::Simple({:?}, Replace::{:?})", c, replace),
+            Escape::Backslash => "Escape::Backslash".to_string(),
+            Escape::Doubled(c, replace) => format!("Escape::Doubled({:?}, Replace::{:?})", c, replace),
+        })
+        .collect();
+    let escape_map: Vec<String> = block
+        .escape_map
+        .iter()
+        .map(|(from, to)| format!("(Cow::Borrowed({:?}), Cow::Borrowed({:?}))", from, to))
+        .collect();
+    format!(
+        "Block {{
+    start: Cow::Borrowed({:?}),
+    end: Cow::Borrowed({:?}),
+    include_delimiters: {},
+    escapes: Cow::Borrowed(&[{}]),
+    escape_map: Cow::Borrowed(&[{}]),
+    transform_escapes: {},
+    typed_values: {},
+    allow_unterminated: {},
+}}",
+        block.start,
+        block.end,
+        block.include_delimiters,
+        escapes.join(", "),
+        escape_map.join(", "),
+        block.transform_escapes,
+        block.typed_values,
+        block.allow_unterminated
+    )
+}
+
+fn option<K: TokenKind>(kind: Option<K>, name: &impl Fn(K) -> String) -> String {
+    match kind {
+        Some(kind) => format!("Some({:?})", name(kind)),
+        None => "None".to_string(),
+    }
+}
+
+fn write_table<T: std::fmt::Display>(out: &mut String, table: &str, element: &str, values: &[T]) {
+    writeln!(out, "#[rustfmt::skip]\nstatic {}: [{}; {}] = [", table, element, values.len()).unwrap();
+    let mut line = String::from("   ");
+    for value in values {
+        let item = format!(" {},", value);
+        if line.len() + item.len() > 100 {
+            writeln!(out, "{}", line).unwrap();
+            line = String::from("   ");
+        }
+        line.push_str(&item);
+    }
+    if !values.is_empty() {
+        writeln!(out, "{}", line).unwrap();
+    }
+    out.push_str("];\n\n");
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fmt};
+
+/// Why a tokenizer could not be compiled to a DFA lexer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    /// The scanner at this position of `Tokenizer::scanners` is a closure,
+    /// callback, context-aware or custom scanner, a nesting or multi-pair
+    /// block scanner or one with escapes it cannot compile, an EOL scanner
+    /// with a line continuation or terminators, or uses an accept strategy,
+    /// literal conversion, capture groups or symbol matching options, whose
+    /// behaviour cannot be compiled
+    UnsupportedScanner { index: usize, reason: String },
+    /// The regex of the scanner at this position cannot be compiled to an
+    /// equivalent DFA
+    UnsupportedPattern { index: usize, pattern: String, reason: String },
+    /// Building the combined DFA failed
+    Build(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodegenError::UnsupportedScanner { index, reason } => {
+                write!(f, "Scanner {} cannot be compiled: {}", index, reason)
+            }
+            CodegenError::UnsupportedPattern { index, pattern, reason } => {
+                write!(f, "Scanner {} pattern '{}' cannot be compiled: {}", index, pattern, reason)
+            }
+            CodegenError::Build(message) => write!(f, "Failed to build the lexer DFA: {}", message),
+        }
+    }
+}
+
+impl Error for CodegenError {}
+
+/// The first difference between the interpreted and generated tokenizers on a sample
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Index of the sample in the list passed to `LexerGenerator::verify`
+    pub sample: usize,
+    /// The interpreted tokenizer's token or error at the first difference
+    pub expected: String,
+    /// The generated lexer's token or error at the first difference
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Sample {} differs: interpreted tokenizer gave {}, generated lexer gave {}",
+            self.sample, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for Mismatch {}
@@ -0,0 +1,76 @@
+use super::actions::Action;
+use super::dfa::DfaTables;
+use crate::scanners::Scanner;
+use crate::tokenizers::{Tokenizer, TokenizerConfig};
+use crate::tokens::{Token, TokenFlags, TokenKind, TokenTypeRegistry, TokenValue, TokenizationError};
+
+/// The token a DFA pattern produces
+#[derive(Clone)]
+pub(crate) struct Rule<K: TokenKind> {
+    pub(crate) token_type: K,
+    pub(crate) token_sub_type: Option<K>,
+    pub(crate) action: Action,
+}
+
+/// A tokenizer definition compiled to a single DFA.
+///
+/// It is a `Scanner` that runs the DFA tables directly, so the compiled form
+/// can be used and tested without generating source first; the generated
+/// lexer runs the same tables.
+pub struct CompiledLexer<K: TokenKind = &'static str> {
+    pub(crate) tables: DfaTables,
+    pub(crate) rules: Vec<Rule<K>>,
+    pub(crate) config: TokenizerConfig,
+    /// Names of interned kinds, carried over to `into_tokenizer`
+    pub(crate) registry: TokenTypeRegistry,
+}
+
+impl<K: TokenKind> CompiledLexer<K> {
+    /// Number of DFA states, including the dead state
+    pub fn state_count(&self) -> usize {
+        self.tables.state_count()
+    }
+
+    /// Number of byte equivalence classes the DFA distinguishes
+    pub fn class_count(&self) -> usize {
+        self.tables.class_count
+    }
+
+    /// A tokenizer with the original configuration whose only scanner is this lexer
+    pub fn into_tokenizer(self) -> Tokenizer<K> {
+        let mut tokenizer = Tokenizer::with_typed_config(self.config.clone());
+        tokenizer.registry = self.registry.clone();
+        tokenizer.add_scanner(Box::new(self));
+        tokenizer
+    }
+}
+
+impl<K: TokenKind> Scanner<K> for CompiledLexer<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
+        let Some((rule, len)) = self.tables.find(input.as_bytes()) else {
+            return Ok(None);
+        };
+        let rule = &self.rules[rule];
+        let lexeme = rule.action.lexeme(input, len).map_err(|(start, end)| {
+            TokenizationError::UnmatchedBlockDelimiter(start.to_string(), end.to_string())
+        })?;
+
+        let mut token = Token::new(rule.token_type, rule.token_sub_type, lexeme.value, 0, 0);
+        token.literal = lexeme.literal.map(TokenValue::Str);
+        if lexeme.had_escapes {
+            token.flags.insert(TokenFlags::HAD_ESCAPES);
+        }
+        if lexeme.unterminated {
+            token.flags.insert(TokenFlags::UNTERMINATED);
+        }
+        Ok(Some(token))
+    }
+
+    fn consumed_len(&self, input: &str, token: &Token<K>) -> usize {
+        // The value may leave out delimiters or line terminators
+        self.tables
+            .find(input.as_bytes())
+            .and_then(|(rule, len)| self.rules[rule].action.consumed(input, len))
+            .unwrap_or(token.value.len())
+    }
+}
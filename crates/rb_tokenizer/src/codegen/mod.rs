@@ -0,0 +1,352 @@
+//! Compiles a tokenizer definition to a single DFA and generates Rust source
+//! for a standalone lexer with the same token output.
+//!
+//! Every symbol, regex and EOL scanner becomes one pattern of an anchored,
+//! leftmost-first DFA, and every block scanner two: its terminated blocks,
+//! and blocks running to the end of the input, which are errors unless the
+//! scanner allows them. One DFA run per token replaces trying every scanner in
+//! turn. The generated lexer matches tokens with its own tables and search
+//! loop and uses no scanner from this crate; it only plugs into a `Tokenizer`
+//! through the `Scanner` trait.
+//!
+//! Only regular scanners compile: EOL scanners without a line continuation or
+//! terminators, and block scanners with one delimiter pair, no nesting, and
+//! simple, default backslash or doubled end delimiter escapes without custom
+//! transforms. Closure, callback, context-aware and custom scanners, named
+//! escapes and entities, and regex accept strategies cannot be compiled
+//! either.
+//!
+//! Generation is meant to run from a build script:
+//!
+//! ```ignore
+//! let tokenizer = my_tokenizer();
+//! let source = LexerGenerator::new(&tokenizer)
+//!     .lexer_name("JsonLexer")
+//!     .constructor_name("json_tokenizer")
+//!     .generate()?;
+//! std::fs::write(out_dir.join("json_lexer.rs"), source)?;
+//! ```
+
+mod actions;
+mod dfa;
+mod emit;
+pub mod error;
+mod lexer;
+
+pub use error::{CodegenError, Mismatch};
+pub use lexer::CompiledLexer;
+
+use crate::scanners::block_scanner::{EscapeRule, EscapeTransform};
+use crate::scanners::{BlockScanner, ScannerType};
+use crate::tokenizers::{is_anchored, LineEndings, Tokenizer};
+use crate::tokens::{Token, TokenKind, TokenTypeId, TokenizationError};
+use actions::{Action, Block, Escape, Line, Replace};
+use dfa::DfaTables;
+use lexer::Rule;
+use std::any::Any;
+use std::borrow::Cow;
+
+/// Builds a `CompiledLexer` or lexer source from a tokenizer's scanners
+pub struct LexerGenerator<'t, K: TokenKind = &'static str> {
+    tokenizer: &'t Tokenizer<K>,
+    lexer_name: String,
+    constructor_name: String,
+}
+
+impl<'t, K: TokenKind> LexerGenerator<'t, K> {
+    pub fn new(tokenizer: &'t Tokenizer<K>) -> Self {
+        Self {
+            tokenizer,
+            lexer_name: "GeneratedLexer".to_string(),
+            constructor_name: "tokenizer".to_string(),
+        }
+    }
+
+    /// Sets the name of the generated scanner struct (default `GeneratedLexer`)
+    pub fn lexer_name(mut self, name: &str) -> Self {
+        self.lexer_name = name.to_string();
+        self
+    }
+
+    /// Sets the name of the generated function returning a ready tokenizer (default `tokenizer`)
+    pub fn constructor_name(mut self, name: &str) -> Self {
+        self.constructor_name = name.to_string();
+        self
+    }
+
+    /// Compiles the tokenizer's scanners to a DFA
+    pub fn compile(&self) -> Result<CompiledLexer<K>, CodegenError> {
+        let mut patterns = Vec::new();
+        let mut rules = Vec::new();
+
+        for (index, scanner) in self.tokenizer.enabled_scanners() {
+            let unsupported = |reason: &str| CodegenError::UnsupportedScanner {
+                index,
+                reason: reason.to_string(),
+            };
+            match scanner {
+                ScannerType::Symbol(scanner) => {
+                    if !scanner.options.is_exact() {
+                        return Err(unsupported("symbol scanners with matching options are not supported"));
+                    }
+                    patterns.push(regex::escape(&scanner.symbol));
+                    rules.push(Rule {
+                        token_type: scanner.token_type,
+                        token_sub_type: scanner.token_sub_type,
+                        action: Action::Token,
+                    });
+                }
+                ScannerType::Regex(scanner) => {
+                    let pattern = scanner.pattern.as_str();
+                    let unsupported_pattern = |reason: &str| CodegenError::UnsupportedPattern {
+                        index,
+                        pattern: pattern.to_string(),
+                        reason: reason.to_string(),
+                    };
+                    if scanner.accept_strategy.is_some() {
+                        return Err(unsupported("regex scanners with an accept strategy are not supported"));
+                    }
+                    if scanner.literal.is_some() {
+                        return Err(unsupported("regex scanners with a literal conversion are not supported"));
+                    }
+                    if scanner.uses_captures() {
+                        return Err(unsupported("regex scanners using capture groups are not supported"));
+                    }
+                    // Unanchored patterns can match later in the input, which a DFA run from
+                    // the current position cannot reproduce
+                    if !is_anchored(pattern) {
+                        return Err(unsupported_pattern("the pattern must be anchored with `^`"));
+                    }
+                    if scanner.pattern.is_match("") {
+                        return Err(unsupported_pattern("the pattern matches the empty string"));
+                    }
+                    // Check each pattern alone so failures name the scanner
+                    DfaTables::build(&[pattern.to_string()]).map_err(|e| unsupported_pattern(&e))?;
+                    patterns.push(pattern.to_string());
+                    rules.push(Rule {
+                        token_type: scanner.token_type,
+                        token_sub_type: scanner.token_sub_type,
+                        action: Action::Token,
+                    });
+                }
+                ScannerType::Block(scanner) => {
+                    let block = compile_block(scanner).map_err(unsupported)?;
+                    // A block that opens is either terminated or runs to the end of the input
+                    patterns.push(block_pattern(&block));
+                    patterns.push(format!(r"{}(?s:.)*\z", regex::escape(&block.start)));
+                    for terminated in [true, false] {
+                        rules.push(Rule {
+                            token_type: scanner.token_type(),
+                            token_sub_type: scanner.token_sub_type(),
+                            action: Action::Block { block: block.clone(), terminated },
+                        });
+                    }
+                }
+                ScannerType::Eol(scanner) => {
+                    if scanner.continuation.is_some() {
+                        return Err(unsupported("EOL scanners with a line continuation are not supported"));
+                    }
+                    if !scanner.terminators.is_empty() {
+                        return Err(unsupported("EOL scanners with terminators are not supported"));
+                    }
+                    if scanner.delimiter.is_empty() {
+                        return Err(unsupported("EOL scanners with an empty delimiter are not supported"));
+                    }
+                    let line_endings = scanner.line_endings.unwrap_or(self.tokenizer.config().line_endings);
+                    let (content, terminator) = match line_endings {
+                        LineEndings::Lf => (r"[^\n]", r"\n"),
+                        LineEndings::Any => (r"[^\r\n]", r"\r\n|\r|\n"),
+                        LineEndings::Unicode => (r"[^\r\n\x{2028}\x{2029}]", r"\r\n|[\r\n\x{2028}\x{2029}]"),
+                    };
+                    patterns.push(format!("{}{}*(?:{})?", regex::escape(&scanner.delimiter), content, terminator));
+                    rules.push(Rule {
+                        token_type: scanner.token_type,
+                        token_sub_type: scanner.token_sub_type,
+                        action: Action::Line(Line {
+                            delimiter_len: scanner.delimiter.len(),
+                            include_delimiter: scanner.include_delimiter,
+                            include_terminator: scanner.include_terminator,
+                            terminators: match line_endings {
+                                LineEndings::Lf => "\n",
+                                LineEndings::Any => "\r\n",
+                                LineEndings::Unicode => "\r\n\u{2028}\u{2029}",
+                            },
+                        }),
+                    });
+                }
+                ScannerType::Closure(_) => return Err(unsupported("closure scanners are not supported")),
+                ScannerType::Callback(_) => return Err(unsupported("callback scanners are not supported")),
+                ScannerType::Context(_) => return Err(unsupported("context-aware scanners are not supported")),
+                ScannerType::Scanner(_) => return Err(unsupported("custom scanners are not supported")),
+            }
+        }
+
+        Ok(CompiledLexer {
+            tables: DfaTables::build(&patterns).map_err(CodegenError::Build)?,
+            rules,
+            config: self.tokenizer.config().clone(),
+            registry: self.tokenizer.registry.clone(),
+        })
+    }
+
+    /// Compiles the tokenizer and returns the Rust source of a standalone lexer.
+    ///
+    /// The source defines a `match_token` function running the DFA, the lexer
+    /// struct, which implements `Scanner` with it, and a constructor function
+    /// returning a `Tokenizer` (with `&'static str` kinds named after the
+    /// original kinds) that uses the lexer as its only scanner.
+    pub fn generate(&self) -> Result<String, CodegenError> {
+        let lexer = self.compile()?;
+        Ok(emit::emit(&lexer, |kind| kind_name(self.tokenizer, kind), &self.lexer_name, &self.constructor_name))
+    }
+
+    /// Tokenizes every sample with both the original tokenizer and `generated`
+    /// and reports the first sample on which their tokens (with their literals
+    /// and flags) or errors differ.
+    ///
+    /// `generated` is typically the tokenizer returned by a generated
+    /// lexer's constructor, or `compile()?.into_tokenizer()`. Kinds are
+    /// compared by name, so the two tokenizers may use different kind types.
+    pub fn verify<G: TokenKind>(&self, generated: &Tokenizer<G>, samples: &[&str]) -> Result<(), Mismatch> {
+        for (sample, input) in samples.iter().enumerate() {
+            let expected = describe(self.tokenizer.tokenize(input), |kind| kind_name(self.tokenizer, kind));
+            let actual = describe(generated.tokenize(input), |kind| kind_name(generated, kind));
+            if let Some(at) = (0..expected.len().max(actual.len())).find(|&at| expected.get(at) != actual.get(at)) {
+                let entry = |entries: &[String]| entries.get(at).cloned().unwrap_or_else(|| "nothing".to_string());
+                return Err(Mismatch {
+                    sample,
+                    expected: entry(&expected),
+                    actual: entry(&actual),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The compiled form of a block scanner, if its blocks are regular: one
+/// delimiter pair, no nesting, and only simple escapes, the default backslash
+/// escape and a doubled one-character end delimiter, none of them starting
+/// the end delimiter
+fn compile_block<K: TokenKind>(scanner: &BlockScanner<K>) -> Result<Block, &'static str> {
+    let [(start, end)] = scanner.delimiter_pairs() else {
+        return Err("block scanners with several delimiter pairs are not supported");
+    };
+    if scanner.allows_nesting() {
+        return Err("nesting block scanners are not supported");
+    }
+    if start.is_empty() || end.is_empty() {
+        return Err("block scanners with an empty delimiter are not supported");
+    }
+
+    let mut escapes: Vec<Escape> = Vec::new();
+    let rules = if scanner.is_raw() { &[][..] } else { scanner.escape_rules() };
+    for (index, rule) in rules.iter().enumerate() {
+        let replace = match scanner.escape_transform(index) {
+            None => Replace::Default,
+            Some(EscapeTransform::Verbatim) => Replace::Verbatim,
+            Some(EscapeTransform::Remove) => Replace::Remove,
+            Some(EscapeTransform::HexCodePoint) => Replace::HexCodePoint,
+            Some(EscapeTransform::Custom(_)) => return Err("custom escape transforms are not supported"),
+        };
+        let escape = match rule {
+            EscapeRule::Simple { escape_char } => Escape::Simple(*escape_char, replace),
+            EscapeRule::Doubled { sequence } if sequence == end && end.chars().count() == 1 => {
+                Escape::Doubled(end.chars().next().unwrap(), replace)
+            }
+            EscapeRule::Doubled { .. } => {
+                return Err("doubled escapes other than a one-character end delimiter are not supported")
+            }
+            EscapeRule::Named { .. } => return Err("named escapes and entities are not supported"),
+            EscapeRule::Pattern { .. } => return Err("pattern escapes are not supported"),
+            EscapeRule::Balanced { .. } => return Err("balanced escapes are not supported"),
+        };
+        // Only the first rule for a character ever matches
+        if !escapes.iter().any(|known| known.escape_char() == escape.escape_char()) {
+            escapes.push(escape);
+        }
+    }
+    if !scanner.is_raw() && scanner.uses_default_backslash_escape() && !escapes.iter().any(|known| known.escape_char() == '\\') {
+        escapes.push(Escape::Backslash);
+    }
+    let starts_end = |escape: &Escape| !matches!(escape, Escape::Doubled(..)) && end.starts_with(escape.escape_char());
+    if escapes.iter().any(starts_end) {
+        return Err("end delimiters starting with an escape character are not supported");
+    }
+
+    let mut escape_map: Vec<_> = scanner
+        .escape_map()
+        .iter()
+        .map(|(from, to)| (Cow::Owned(from.clone()), Cow::Owned(to.clone())))
+        .collect();
+    escape_map.sort();
+    Ok(Block {
+        start: Cow::Owned(start.clone()),
+        end: Cow::Owned(end.clone()),
+        include_delimiters: scanner.includes_delimiters(),
+        escapes: Cow::Owned(escapes),
+        escape_map: Cow::Owned(escape_map),
+        transform_escapes: scanner.transforms_escapes(),
+        typed_values: scanner.has_typed_values(),
+        allow_unterminated: scanner.allows_unterminated(),
+    })
+}
+
+/// The pattern of a terminated block. Escape characters only occur in
+/// escapes, so the shortest match ends at the end delimiter the scanner stops
+/// at. A doubled end delimiter ends the block only when it is not followed by
+/// another, so the pattern also takes the next character (see `Block::end`).
+fn block_pattern(block: &Block) -> String {
+    let mut alternatives = Vec::new();
+    let mut excluded = String::new();
+    for escape in block.escapes.iter() {
+        let c = regex::escape(&escape.escape_char().to_string());
+        match escape {
+            Escape::Doubled(..) => alternatives.push(format!("{}{}", c, c)),
+            _ => alternatives.push(format!("{}(?s:.)", c)),
+        }
+        excluded.push_str(&c);
+    }
+    alternatives.push(if excluded.is_empty() { "(?s:.)".to_string() } else { format!("[^{}]", excluded) });
+
+    let (start, end) = (regex::escape(&block.start), regex::escape(&block.end));
+    match block.escapes.iter().any(|escape| matches!(escape, Escape::Doubled(..))) {
+        true => format!(r"{}(?:{})*{}(?:[^{}]|\z)", start, alternatives.join("|"), end, end),
+        false => format!("{}(?:{})*?{}", start, alternatives.join("|"), end),
+    }
+}
+
+/// Names a kind, resolving interned ids through the tokenizer's registry
+fn kind_name<K: TokenKind>(tokenizer: &Tokenizer<K>, kind: K) -> String {
+    match (&kind as &dyn Any).downcast_ref::<TokenTypeId>() {
+        Some(&id) => tokenizer.registry.resolve(id).into_owned(),
+        None => kind.name().into_owned(),
+    }
+}
+
+/// Renders a tokenization result as one entry per token or error, with kinds replaced by their names
+fn describe<K: TokenKind>(
+    result: Result<Vec<Token<K>>, Vec<TokenizationError>>,
+    name: impl Fn(K) -> String,
+) -> Vec<String> {
+    match result {
+        Ok(tokens) => tokens
+            .iter()
+            .map(|token| {
+                let kind = match token.token_sub_type {
+                    Some(sub_type) => format!("{}:{}", name(token.token_type), name(sub_type)),
+                    None => name(token.token_type),
+                };
+                let mut entry = format!("{} {:?} at {}:{}", kind, token.value, token.line, token.column);
+                if let Some(literal) = &token.literal {
+                    entry.push_str(&format!(" = {:?}", literal));
+                }
+                if !token.flags.is_empty() {
+                    entry.push_str(&format!(" [{}]", token.flags.iter().collect::<Vec<_>>().join(", ")));
+                }
+                entry
+            })
+            .collect(),
+        Err(errors) => errors.iter().map(|error| format!("error: {}", error)).collect(),
+    }
+}
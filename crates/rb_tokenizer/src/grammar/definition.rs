@@ -0,0 +1,341 @@
+use crate::tokenizers::{ColumnMode, LineEndings, TokenizerConfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Top-level layout of a grammar file. Sections are deserialized one at a time
+/// so that errors can be attributed to the entry they occur in.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, bound(deserialize = "V: Deserialize<'de>"))]
+pub(super) struct RawGrammar<V> {
+    #[serde(default)]
+    pub(super) config: Option<V>,
+    #[serde(default)]
+    pub(super) scanners: Vec<V>,
+}
+
+/// The `config` section of a grammar file. Unset options keep their
+/// `TokenizerConfig::default()` values.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigDefinition {
+    pub tokenize_whitespace: Option<bool>,
+    pub continue_on_error: Option<bool>,
+    pub error_tolerance_limit: Option<usize>,
+    pub track_token_positions: Option<bool>,
+    pub column_mode: Option<ColumnModeDefinition>,
+    /// Tab width for the `display_width` column mode
+    pub tab_width: Option<usize>,
+    pub line_endings: Option<LineEndingsDefinition>,
+    pub report_line_ending_style: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnModeDefinition {
+    Bytes,
+    Chars,
+    Utf16,
+    Graphemes,
+    DisplayWidth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndingsDefinition {
+    Lf,
+    Any,
+    Unicode,
+}
+
+impl ConfigDefinition {
+    /// Builds the tokenizer configuration, or names the field that is invalid
+    pub(super) fn to_config(&self) -> Result<TokenizerConfig, (&'static str, String)> {
+        let mut config = TokenizerConfig::default();
+        if let Some(value) = self.tokenize_whitespace {
+            config.tokenize_whitespace = value;
+        }
+        if let Some(value) = self.continue_on_error {
+            config.continue_on_error = value;
+        }
+        if let Some(value) = self.error_tolerance_limit {
+            if value == 0 {
+                return Err(("error_tolerance_limit", "error_tolerance_limit must be at least 1".to_string()));
+            }
+            config.error_tolerance_limit = value;
+        }
+        if let Some(value) = self.track_token_positions {
+            config.track_token_positions = value;
+        }
+        if let Some(value) = self.report_line_ending_style {
+            config.report_line_ending_style = value;
+        }
+        if let Some(line_endings) = self.line_endings {
+            config.line_endings = match line_endings {
+                LineEndingsDefinition::Lf => LineEndings::Lf,
+                LineEndingsDefinition::Any => LineEndings::Any,
+                LineEndingsDefinition::Unicode => LineEndings::Unicode,
+            };
+        }
+
+        match (self.column_mode, self.tab_width) {
+            (_, Some(0)) => return Err(("tab_width", "tab_width must be at least 1".to_string())),
+            (Some(ColumnModeDefinition::DisplayWidth), tab_width) => {
+                config.column_mode = ColumnMode::DisplayWidth { tab_width: tab_width.unwrap_or(4) };
+            }
+            (_, Some(_)) => {
+                return Err(("tab_width", "tab_width requires column_mode = \"display_width\"".to_string()));
+            }
+            (Some(mode), None) => {
+                config.column_mode = match mode {
+                    ColumnModeDefinition::Bytes => ColumnMode::Bytes,
+                    ColumnModeDefinition::Chars => ColumnMode::Chars,
+                    ColumnModeDefinition::Utf16 => ColumnMode::Utf16,
+                    ColumnModeDefinition::Graphemes => ColumnMode::Graphemes,
+                    ColumnModeDefinition::DisplayWidth => unreachable!(),
+                };
+            }
+            (None, None) => {}
+        }
+
+        Ok(config)
+    }
+}
+
+/// One entry of the `scanners` list, selected by its `type` field.
+///
+/// `priority` orders scanners: lower values are tried first, and entries with
+/// equal priority keep their order in the file. An optional unique `name`
+/// lets the loaded tokenizer remove, replace or disable the scanner.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ScannerDefinition {
+    Symbol {
+        symbol: String,
+        token_type: String,
+        #[serde(default)]
+        token_sub_type: Option<String>,
+        #[serde(default)]
+        priority: i64,
+        #[serde(default)]
+        name: Option<String>,
+        /// Match letters in either case, only ASCII ones or any
+        #[serde(default)]
+        case_insensitive: Option<CaseMatchingDefinition>,
+        /// Don't match a symbol ending in a word character when one follows it
+        #[serde(default)]
+        word_boundary: bool,
+        /// Compare the symbol and the input in Unicode Normalization Form C
+        #[serde(default)]
+        normalize: bool,
+    },
+    Regex {
+        pattern: String,
+        token_type: String,
+        #[serde(default)]
+        token_sub_type: Option<String>,
+        #[serde(default)]
+        priority: i64,
+        #[serde(default)]
+        name: Option<String>,
+        /// Only try the pattern when the input starts with one of these characters
+        #[serde(default)]
+        accept_start_chars: Option<String>,
+        /// Only try the pattern when the input starts with this prefix
+        #[serde(default)]
+        accept_prefix: Option<String>,
+        /// The capture group, by number or name, whose text is the token value
+        #[serde(default)]
+        value_group: Option<CaptureGroupDefinition>,
+        /// Named groups that give the token their name as its sub-type when they match
+        #[serde(default)]
+        sub_type_groups: Vec<String>,
+        /// Keep the text of the other capture groups as token metadata
+        #[serde(default)]
+        capture_metadata: bool,
+    },
+    Block {
+        start: String,
+        end: String,
+        token_type: String,
+        #[serde(default)]
+        token_sub_type: Option<String>,
+        #[serde(default)]
+        priority: i64,
+        #[serde(default)]
+        name: Option<String>,
+        /// Further start and end delimiters, e.g. `[["[", "]"], ["{", "}"]]`
+        #[serde(default)]
+        pairs: Vec<(String, String)>,
+        #[serde(default)]
+        nesting: bool,
+        #[serde(default)]
+        raw: bool,
+        #[serde(default = "default_true")]
+        include_delimiters: bool,
+        #[serde(default)]
+        escapes: Vec<EscapeDefinition>,
+        /// Whether a backslash escapes the next character when no escape rule matches
+        #[serde(default = "default_true")]
+        default_backslash_escape: bool,
+        /// Replacement text for escape sequences, e.g. `n = "\n"` or `amp = "&"`
+        #[serde(default)]
+        escape_map: BTreeMap<String, String>,
+        #[serde(default)]
+        transform_escapes: bool,
+        /// Resolve unmapped named escapes as HTML5 character references
+        #[serde(default)]
+        html_entities: bool,
+        /// Report named escapes that resolve to nothing as errors
+        #[serde(default)]
+        reject_unknown_entities: bool,
+        /// Give tokens their content, with escapes transformed, as a string literal
+        #[serde(default)]
+        typed_value: bool,
+        /// Scan a block missing its end delimiter to the end of the input
+        #[serde(default)]
+        allow_unterminated: bool,
+    },
+    Eol {
+        delimiter: String,
+        token_type: String,
+        #[serde(default)]
+        token_sub_type: Option<String>,
+        #[serde(default)]
+        priority: i64,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default = "default_true")]
+        include_delimiter: bool,
+        /// Continues the token onto the next line when a line ends with this, e.g. a backslash
+        #[serde(default)]
+        continuation: Option<String>,
+        /// Whether the line terminator is part of the token value
+        #[serde(default = "default_true")]
+        include_terminator: bool,
+        /// Sequences ending the token before the end of the line, e.g. `%>`
+        #[serde(default)]
+        terminators: Vec<String>,
+    },
+}
+
+impl ScannerDefinition {
+    pub fn priority(&self) -> i64 {
+        match self {
+            ScannerDefinition::Symbol { priority, .. }
+            | ScannerDefinition::Regex { priority, .. }
+            | ScannerDefinition::Block { priority, .. }
+            | ScannerDefinition::Eol { priority, .. } => *priority,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            ScannerDefinition::Symbol { name, .. }
+            | ScannerDefinition::Regex { name, .. }
+            | ScannerDefinition::Block { name, .. }
+            | ScannerDefinition::Eol { name, .. } => name.as_deref(),
+        }
+    }
+
+    pub fn token_type(&self) -> &str {
+        match self {
+            ScannerDefinition::Symbol { token_type, .. }
+            | ScannerDefinition::Regex { token_type, .. }
+            | ScannerDefinition::Block { token_type, .. }
+            | ScannerDefinition::Eol { token_type, .. } => token_type,
+        }
+    }
+
+    pub fn token_sub_type(&self) -> Option<&str> {
+        match self {
+            ScannerDefinition::Symbol { token_sub_type, .. }
+            | ScannerDefinition::Regex { token_sub_type, .. }
+            | ScannerDefinition::Block { token_sub_type, .. }
+            | ScannerDefinition::Eol { token_sub_type, .. } => token_sub_type.as_deref(),
+        }
+    }
+}
+
+/// An `EscapeRule` of a block scanner, selected by its `kind` field
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EscapeDefinition {
+    Simple {
+        escape_char: char,
+        #[serde(default)]
+        transform: Option<EscapeTransformDefinition>,
+    },
+    Named {
+        start_char: char,
+        end_char: char,
+        #[serde(default = "default_named_max_length")]
+        max_length: usize,
+        #[serde(default)]
+        transform: Option<EscapeTransformDefinition>,
+    },
+    Pattern {
+        pattern: String,
+        #[serde(default)]
+        transform: Option<EscapeTransformDefinition>,
+    },
+    /// A sequence written twice, the end delimiter unless given
+    Doubled {
+        #[serde(default)]
+        sequence: Option<String>,
+        #[serde(default)]
+        transform: Option<EscapeTransformDefinition>,
+    },
+    Balanced {
+        start_seq: String,
+        end_seq: String,
+        #[serde(default)]
+        allow_nesting: bool,
+        #[serde(default)]
+        transform: Option<EscapeTransformDefinition>,
+    },
+}
+
+impl EscapeDefinition {
+    pub fn transform(&self) -> Option<EscapeTransformDefinition> {
+        match self {
+            EscapeDefinition::Simple { transform, .. }
+            | EscapeDefinition::Named { transform, .. }
+            | EscapeDefinition::Pattern { transform, .. }
+            | EscapeDefinition::Doubled { transform, .. }
+            | EscapeDefinition::Balanced { transform, .. } => *transform,
+        }
+    }
+}
+
+/// The built-in `EscapeTransform`s; custom transforms can only be set in code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscapeTransformDefinition {
+    Verbatim,
+    Remove,
+    HexCodePoint,
+}
+
+/// The case-insensitive `CaseMatching`s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMatchingDefinition {
+    Ascii,
+    Unicode,
+}
+
+/// A regex capture group, written as its number or its name
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum CaptureGroupDefinition {
+    Index(usize),
+    Name(String),
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_named_max_length() -> usize {
+    10
+}
@@ -0,0 +1,29 @@
+use std::{error::Error, fmt};
+
+/// A problem found while loading a grammar file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarError {
+    /// 1-based line of the grammar file the problem was found on, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl GrammarError {
+    pub fn new(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Grammar error at line {}: {}", line, self.message),
+            None => write!(f, "Grammar error: {}", self.message),
+        }
+    }
+}
+
+impl Error for GrammarError {}
@@ -0,0 +1,469 @@
+use super::definition::{
+    CaptureGroupDefinition, CaseMatchingDefinition, ConfigDefinition, EscapeDefinition, EscapeTransformDefinition, RawGrammar, ScannerDefinition,
+};
+use super::error::GrammarError;
+use super::locate;
+use crate::scanners::block_scanner::{EscapeRule, EscapeTransform};
+use crate::scanners::regex_scanner::CaptureGroup;
+use crate::scanners::scanner::AcceptStrategy;
+use crate::scanners::{BlockScanner, CaseMatching, EolScanner, RegexScanner, ScannerType, SymbolOptions, SymbolScanner};
+use crate::tokenizers::{is_anchored, Tokenizer, TokenizerConfig};
+use crate::tokens::TokenTypeId;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+/// File formats a grammar can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrammarFormat {
+    Toml,
+    Json,
+}
+
+impl GrammarFormat {
+    /// Picks the format from a `.toml` or `.json` file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(GrammarFormat::Toml),
+            "json" => Some(GrammarFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A section of the grammar file, deserialized on its own so that errors can
+/// be attributed to it. `span` is where the section is in the file, if known.
+struct Entry<T> {
+    value: Result<T, String>,
+    span: Option<Range<usize>>,
+}
+
+struct Parsed {
+    config: Option<Entry<ConfigDefinition>>,
+    scanners: Vec<Entry<ScannerDefinition>>,
+}
+
+/// The grammar text, used to turn spans and field names into line numbers
+struct Source<'a> {
+    text: &'a str,
+    format: GrammarFormat,
+}
+
+impl Source<'_> {
+    fn parse(&self) -> Result<Parsed, GrammarError> {
+        match self.format {
+            GrammarFormat::Toml => self.parse_toml(),
+            GrammarFormat::Json => self.parse_json(),
+        }
+    }
+
+    fn parse_toml(&self) -> Result<Parsed, GrammarError> {
+        let raw: RawGrammar<toml::Spanned<toml::Value>> = toml::from_str(self.text).map_err(|e| {
+            GrammarError::new(e.span().map(|span| locate::line_at(self.text, span.start)), e.message())
+        })?;
+
+        fn entry<T: DeserializeOwned>(value: toml::Spanned<toml::Value>) -> Entry<T> {
+            let span = value.span();
+            Entry {
+                value: value.into_inner().try_into().map_err(|e: toml::de::Error| e.message().to_string()),
+                span: Some(span),
+            }
+        }
+
+        Ok(Parsed {
+            config: raw.config.map(entry),
+            scanners: raw.scanners.into_iter().map(entry).collect(),
+        })
+    }
+
+    fn parse_json(&self) -> Result<Parsed, GrammarError> {
+        let raw: RawGrammar<serde_json::Value> = serde_json::from_str(self.text).map_err(|e| {
+            // Drop serde_json's " at line L column C" suffix; the line is reported separately
+            let message = e.to_string();
+            let message = match message.rfind(" at line ") {
+                Some(at) if e.line() > 0 => message[..at].to_string(),
+                _ => message,
+            };
+            GrammarError::new((e.line() > 0).then_some(e.line()), message)
+        })?;
+
+        fn entry<T: DeserializeOwned>(value: serde_json::Value, span: Option<Range<usize>>) -> Entry<T> {
+            Entry {
+                value: serde_json::from_value(value).map_err(|e| e.to_string()),
+                span,
+            }
+        }
+
+        let config_span = locate::json_member(self.text, "config");
+        let scanner_spans = locate::json_member(self.text, "scanners")
+            .map(|array| locate::json_array_elements(self.text, &array))
+            .unwrap_or_default();
+
+        Ok(Parsed {
+            config: raw.config.map(|config| entry(config, config_span)),
+            scanners: raw
+                .scanners
+                .into_iter()
+                .enumerate()
+                .map(|(i, scanner)| entry(scanner, scanner_spans.get(i).cloned()))
+                .collect(),
+        })
+    }
+
+    /// Line of `field` within the section at `span`
+    fn line(&self, span: &Option<Range<usize>>, field: Option<&str>) -> Option<usize> {
+        let span = span.as_ref()?;
+        Some(match field {
+            Some(field) => locate::field_line(self.text, span, field, self.format),
+            None => locate::line_at(self.text, span.start),
+        })
+    }
+
+    /// Error for a section that failed to deserialize, pointing at the field
+    /// the message names when there is one
+    fn deserialize_error(&self, span: &Option<Range<usize>>, message: String) -> GrammarError {
+        let field = ["unknown field `", "missing field `", "duplicate field `"]
+            .iter()
+            .find_map(|prefix| message.strip_prefix(prefix))
+            .and_then(|rest| rest.split('`').next());
+        let field = match field {
+            Some(_) if message.starts_with("missing") => None,
+            Some(field) => Some(field),
+            None if message.starts_with("unknown variant") => Some("type"),
+            None => None,
+        };
+        GrammarError::new(self.line(span, field), message)
+    }
+}
+
+/// Builds an interned tokenizer from grammar text.
+pub(super) fn load(text: &str, format: GrammarFormat) -> Result<Tokenizer<TokenTypeId>, Vec<GrammarError>> {
+    let source = Source { text, format };
+    let parsed = source.parse().map_err(|e| vec![e])?;
+    let mut errors = Vec::new();
+
+    let config = match parsed.config {
+        None => TokenizerConfig::default(),
+        Some(Entry { value: Ok(definition), span }) => definition.to_config().unwrap_or_else(|(field, message)| {
+            errors.push(GrammarError::new(source.line(&span, Some(field)), message));
+            TokenizerConfig::default()
+        }),
+        Some(Entry { value: Err(message), span }) => {
+            errors.push(source.deserialize_error(&span, message));
+            TokenizerConfig::default()
+        }
+    };
+
+    let mut tokenizer = Tokenizer::interned_with_config(config);
+    let mut scanners = Vec::new();
+    let mut symbols: Vec<(SymbolScanner, Option<usize>)> = Vec::new();
+    let mut names: HashMap<String, Option<usize>> = HashMap::new();
+
+    for entry in parsed.scanners {
+        let definition = match entry.value {
+            Ok(definition) => definition,
+            Err(message) => {
+                errors.push(source.deserialize_error(&entry.span, message));
+                continue;
+            }
+        };
+
+        if let Some((symbol, options)) = symbol_options(&definition) {
+            let line = source.line(&entry.span, Some("symbol"));
+            let scanner = SymbolScanner::with_options(symbol, "Symbol", None, options);
+            // Symbols are duplicates when one matches all of the other, e.g. `if` ignoring case and `IF`
+            let covers = |a: &SymbolScanner, b: &SymbolScanner| a.match_len(&b.symbol) == Some(b.symbol.len());
+            if let Some((earlier, first)) = symbols.iter().find(|(earlier, _)| covers(earlier, &scanner) || covers(&scanner, earlier)) {
+                let matched = if earlier.symbol == *symbol { String::new() } else { format!("matches '{}', ", earlier.symbol) };
+                let message = match first {
+                    Some(first) => format!("Duplicate symbol '{}' ({}first defined at line {})", symbol, matched, first),
+                    None if matched.is_empty() => format!("Duplicate symbol '{}'", symbol),
+                    None => format!("Duplicate symbol '{}' (matches '{}')", symbol, earlier.symbol),
+                };
+                errors.push(GrammarError::new(line, message));
+                continue;
+            }
+            symbols.push((scanner, line));
+        }
+
+        if let Some(name) = definition.name() {
+            let line = source.line(&entry.span, Some("name"));
+            if let Some(first) = names.insert(name.to_string(), line) {
+                let message = match first {
+                    Some(first) => format!("Duplicate scanner name '{}' (first defined at line {})", name, first),
+                    None => format!("Duplicate scanner name '{}'", name),
+                };
+                errors.push(GrammarError::new(line, message));
+                continue;
+            }
+        }
+
+        match build_scanner(&mut tokenizer, &definition) {
+            Ok(scanner) => scanners.push((definition.name().map(str::to_string), definition.priority(), scanner)),
+            Err((field, message)) => errors.push(GrammarError::new(source.line(&entry.span, Some(field)), message)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    for (name, priority, scanner) in scanners {
+        tokenizer
+            .register_scanner(name.as_deref(), priority, scanner)
+            .expect("scanner names were checked for duplicates");
+    }
+    Ok(tokenizer)
+}
+
+type BuildError = (&'static str, String);
+
+fn require_non_empty(field: &'static str, value: &str) -> Result<(), BuildError> {
+    if value.is_empty() {
+        return Err((field, format!("{} must not be empty", field)));
+    }
+    Ok(())
+}
+
+fn compile_pattern(field: &'static str, pattern: &str) -> Result<Regex, BuildError> {
+    Regex::new(pattern).map_err(|e| (field, format!("Invalid regex pattern '{}': {}", pattern, e)))
+}
+
+/// The symbol of a symbol definition and the options it is matched with
+fn symbol_options(definition: &ScannerDefinition) -> Option<(&str, SymbolOptions)> {
+    let ScannerDefinition::Symbol {
+        symbol,
+        case_insensitive,
+        word_boundary,
+        normalize,
+        ..
+    } = definition
+    else {
+        return None;
+    };
+    let case = match case_insensitive {
+        None => CaseMatching::Exact,
+        Some(CaseMatchingDefinition::Ascii) => CaseMatching::AsciiInsensitive,
+        Some(CaseMatchingDefinition::Unicode) => CaseMatching::UnicodeInsensitive,
+    };
+    Some((symbol, SymbolOptions { case, word_boundary: *word_boundary, normalize: *normalize }))
+}
+
+/// Validates a definition and builds its scanner, interning its type names
+fn build_scanner(
+    tokenizer: &mut Tokenizer<TokenTypeId>,
+    definition: &ScannerDefinition,
+) -> Result<ScannerType<TokenTypeId>, BuildError> {
+    require_non_empty("token_type", definition.token_type())?;
+    if let Some(sub_type) = definition.token_sub_type() {
+        require_non_empty("token_sub_type", sub_type)?;
+    }
+    let token_type = tokenizer.intern(definition.token_type());
+    let token_sub_type = definition.token_sub_type().map(|name| tokenizer.intern(name));
+
+    Ok(match definition {
+        ScannerDefinition::Symbol { .. } => {
+            let (symbol, options) = symbol_options(definition).expect("the definition is a symbol");
+            require_non_empty("symbol", symbol)?;
+            ScannerType::Symbol(SymbolScanner::with_options(symbol, token_type, token_sub_type, options))
+        }
+        ScannerDefinition::Regex {
+            pattern,
+            accept_start_chars,
+            accept_prefix,
+            value_group,
+            sub_type_groups,
+            capture_metadata,
+            ..
+        } => {
+            let regex = compile_pattern("pattern", pattern)?;
+            if !is_anchored(pattern) {
+                return Err(("pattern", format!("Regex pattern '{}' must be anchored with '^'", pattern)));
+            }
+            if regex.is_match("") {
+                return Err(("pattern", format!("Regex pattern '{}' can match an empty string", pattern)));
+            }
+
+            let accept_strategy = match (accept_start_chars.clone(), accept_prefix.clone()) {
+                (Some(_), Some(_)) => {
+                    return Err((
+                        "accept_prefix",
+                        "Only one of accept_start_chars and accept_prefix may be set".to_string(),
+                    ));
+                }
+                (Some(chars), None) => Some(AcceptStrategy::Fn(Box::new(move |input: &str| {
+                    input.chars().next().is_some_and(|c| chars.contains(c))
+                }))),
+                (None, Some(prefix)) => Some(AcceptStrategy::Fn(Box::new(move |input: &str| input.starts_with(&prefix)))),
+                (None, None) => None,
+            };
+
+            let mut scanner = RegexScanner {
+                pattern: regex,
+                token_type,
+                token_sub_type,
+                accept_strategy,
+                literal: None,
+                value_group: value_group.as_ref().map(|group| match group {
+                    CaptureGroupDefinition::Index(index) => CaptureGroup::Index(*index),
+                    CaptureGroupDefinition::Name(name) => CaptureGroup::Name(name.clone()),
+                }),
+                sub_type_groups: Vec::new(),
+                capture_metadata: *capture_metadata,
+            };
+            // Checked one field at a time so the error points at the right one
+            scanner.check_groups().map_err(|message| ("value_group", message))?;
+            for name in sub_type_groups {
+                scanner.sub_type_groups.push((name.clone(), tokenizer.intern(name)));
+            }
+            scanner.check_groups().map_err(|message| ("sub_type_groups", message))?;
+            ScannerType::Regex(scanner)
+        }
+        ScannerDefinition::Block {
+            start,
+            end,
+            pairs,
+            nesting,
+            raw,
+            include_delimiters,
+            escapes,
+            default_backslash_escape,
+            escape_map,
+            transform_escapes,
+            html_entities,
+            reject_unknown_entities,
+            typed_value,
+            allow_unterminated,
+            ..
+        } => {
+            require_non_empty("start", start)?;
+            require_non_empty("end", end)?;
+
+            let mut scanner = BlockScanner::new(start, end, token_type, token_sub_type, *nesting, *raw, *include_delimiters);
+            for (start, end) in pairs {
+                require_non_empty("pairs", start)?;
+                require_non_empty("pairs", end)?;
+                scanner.add_delimiter_pair(start, end);
+            }
+            for escape in escapes {
+                let rule = build_escape_rule(escape, end)?;
+                match escape.transform() {
+                    Some(transform) => scanner.add_escape_rule_with_transform(rule, build_escape_transform(transform)),
+                    None => scanner.add_escape_rule(rule),
+                }
+            }
+            for (sequence, replacement) in escape_map {
+                require_non_empty("escape_map", sequence)?;
+                scanner.add_escape_mapping(sequence, replacement.as_str());
+            }
+            scanner.set_default_backslash_escape(*default_backslash_escape);
+            scanner.set_transform_escapes(*transform_escapes);
+            scanner.set_html_entities(*html_entities);
+            scanner.set_reject_unknown_entities(*reject_unknown_entities);
+            scanner.set_typed_values(*typed_value);
+            scanner.set_allow_unterminated(*allow_unterminated);
+            ScannerType::Block(scanner)
+        }
+        ScannerDefinition::Eol {
+            delimiter,
+            include_delimiter,
+            continuation,
+            include_terminator,
+            terminators,
+            ..
+        } => {
+            require_non_empty("delimiter", delimiter)?;
+            let mut scanner = EolScanner::new(delimiter, token_type, token_sub_type, *include_delimiter);
+            if let Some(continuation) = continuation {
+                require_non_empty("continuation", continuation)?;
+            }
+            scanner.set_line_continuation(continuation.as_deref());
+            scanner.set_include_terminator(*include_terminator);
+            for terminator in terminators {
+                require_non_empty("terminators", terminator)?;
+                scanner.add_terminator(terminator);
+            }
+            ScannerType::Eol(scanner)
+        }
+    })
+}
+
+fn build_escape_rule(escape: &EscapeDefinition, end: &str) -> Result<EscapeRule, BuildError> {
+    Ok(match escape {
+        EscapeDefinition::Simple { escape_char, .. } => EscapeRule::Simple { escape_char: *escape_char },
+        EscapeDefinition::Named {
+            start_char,
+            end_char,
+            max_length,
+            ..
+        } => {
+            if *max_length == 0 {
+                return Err(("max_length", "max_length of a named escape must be at least 1".to_string()));
+            }
+            EscapeRule::Named {
+                start_char: *start_char,
+                end_char: *end_char,
+                max_length: *max_length,
+            }
+        }
+        EscapeDefinition::Pattern { pattern, .. } => {
+            EscapeRule::pattern(pattern).map_err(|e| ("escapes", format!("Invalid regex pattern '{}': {}", pattern, e)))?
+        }
+        EscapeDefinition::Doubled { sequence, .. } => {
+            let sequence = sequence.as_deref().unwrap_or(end);
+            require_non_empty("sequence", sequence)?;
+            EscapeRule::Doubled {
+                sequence: sequence.to_string(),
+            }
+        }
+        EscapeDefinition::Balanced {
+            start_seq,
+            end_seq,
+            allow_nesting,
+            ..
+        } => {
+            require_non_empty("start_seq", start_seq)?;
+            require_non_empty("end_seq", end_seq)?;
+            EscapeRule::Balanced {
+                start_seq: start_seq.clone(),
+                end_seq: end_seq.clone(),
+                allow_nesting: *allow_nesting,
+            }
+        }
+    })
+}
+
+fn build_escape_transform(transform: EscapeTransformDefinition) -> EscapeTransform {
+    match transform {
+        EscapeTransformDefinition::Verbatim => EscapeTransform::Verbatim,
+        EscapeTransformDefinition::Remove => EscapeTransform::Remove,
+        EscapeTransformDefinition::HexCodePoint => EscapeTransform::HexCodePoint,
+    }
+}
+
+/// Loading tokenizers from grammar files
+impl Tokenizer<TokenTypeId> {
+    /// Builds a tokenizer from a grammar written in `format`.
+    ///
+    /// All validation errors are reported, each with the line of the grammar
+    /// file it was found on.
+    pub fn from_grammar(text: &str, format: GrammarFormat) -> Result<Self, Vec<GrammarError>> {
+        load(text, format)
+    }
+
+    /// Builds a tokenizer from a `.toml` or `.json` grammar file
+    pub fn from_grammar_file(path: impl AsRef<Path>) -> Result<Self, Vec<GrammarError>> {
+        let path = path.as_ref();
+        let format = GrammarFormat::from_path(path).ok_or_else(|| {
+            vec![GrammarError::new(
+                None,
+                format!("Unknown grammar format for '{}': expected a .toml or .json file", path.display()),
+            )]
+        })?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| vec![GrammarError::new(None, format!("Cannot read '{}': {}", path.display(), e))])?;
+        load(&text, format)
+    }
+}
@@ -0,0 +1,132 @@
+//! Maps parts of a grammar file back onto line numbers.
+//!
+//! TOML values carry their spans; for JSON, which has none, the spans of the
+//! top-level sections are recovered by walking the (already validated) text.
+
+use super::loader::GrammarFormat;
+use regex::Regex;
+use std::ops::Range;
+
+/// 1-based line containing byte `offset`
+pub(super) fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// Line on which `field` is set inside the entry spanning `span`,
+/// or the entry's first line if the field does not appear in it
+pub(super) fn field_line(text: &str, span: &Range<usize>, field: &str, format: GrammarFormat) -> usize {
+    let key = regex::escape(field);
+    let pattern = match format {
+        GrammarFormat::Toml => format!(r#"(?m)(^|[{{,])\s*("{key}"|{key})\s*="#),
+        GrammarFormat::Json => format!(r#""{key}"\s*:"#),
+    };
+    Regex::new(&pattern)
+        .ok()
+        .and_then(|regex| regex.find(&text[span.clone()]))
+        .map_or_else(|| line_at(text, span.start), |found| line_at(text, span.start + found.start()))
+}
+
+/// Span of the value stored under `key` in the top-level JSON object
+pub(super) fn json_member(text: &str, key: &str) -> Option<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+    if bytes.get(pos) != Some(&b'{') {
+        return None;
+    }
+    pos += 1;
+
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        if bytes.get(pos) != Some(&b'"') {
+            return None;
+        }
+        let key_end = skip_value(bytes, pos);
+        let name = &text[pos + 1..key_end - 1];
+
+        pos = skip_whitespace(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+        let value_start = skip_whitespace(bytes, pos + 1);
+        let value_end = skip_value(bytes, value_start);
+        if name == key {
+            return Some(value_start..value_end);
+        }
+
+        pos = skip_whitespace(bytes, value_end);
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+        pos += 1;
+    }
+}
+
+/// Spans of the elements of the JSON array spanning `array`
+pub(super) fn json_array_elements(text: &str, array: &Range<usize>) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut elements = Vec::new();
+    if bytes.get(array.start) != Some(&b'[') {
+        return elements;
+    }
+
+    let mut pos = array.start + 1;
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        if pos >= array.end || bytes[pos] == b']' {
+            return elements;
+        }
+        let end = skip_value(bytes, pos);
+        elements.push(pos..end);
+
+        pos = skip_whitespace(bytes, end);
+        if bytes.get(pos) == Some(&b',') {
+            pos += 1;
+        }
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the offset just past the JSON value starting at `pos`
+fn skip_value(bytes: &[u8], mut pos: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string = false;
+
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        pos += 1;
+        if in_string {
+            match byte {
+                b'\\' => pos += 1,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return pos;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos;
+                }
+            }
+            b',' | b'}' | b']' if depth == 0 => return pos - 1,
+            byte if byte.is_ascii_whitespace() && depth == 0 => return pos - 1,
+            _ => {}
+        }
+    }
+    pos.min(bytes.len())
+}
@@ -0,0 +1,17 @@
+//! Building tokenizers from declarative TOML or JSON grammar files.
+//!
+//! A grammar has an optional `config` section mirroring `TokenizerConfig` and
+//! a `scanners` list whose entries are selected by their `type`
+//! (`symbol`, `regex`, `block` or `eol`). Token type names are interned, so the
+//! result is a `Tokenizer<TokenTypeId>`.
+
+pub mod definition;
+pub mod error;
+mod loader;
+mod locate;
+
+pub use definition::{
+    CaptureGroupDefinition, CaseMatchingDefinition, ConfigDefinition, EscapeDefinition, EscapeTransformDefinition, ScannerDefinition,
+};
+pub use error::GrammarError;
+pub use loader::GrammarFormat;
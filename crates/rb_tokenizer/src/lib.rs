@@ -1,10 +1,20 @@
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "grammar")]
+pub mod grammar;
 pub mod scanners;
 pub mod tokens;
 pub mod tokenizers;
 pub mod utils;
 
 // Re-export main types at crate root for easier access
-pub use tokenizers::{Tokenizer, TokenizerConfig};
+pub use tokenizers::{
+    ColumnMode, LineEndingStyle, LineEndings, ParallelConfig, ScannerError, SplitBoundary, Tokenizer,
+    TokenizerBuilder, TokenizerConfig,
+};
+
+#[cfg(feature = "macros")]
+pub use rb_tokenizer_macros::tokenizer;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -29,20 +39,8 @@ mod tests {
 
         // Test token comparison
         let expected = vec![
-            tokens::Token {
-                token_type: "Number",
-                token_sub_type: None,
-                value: "123".to_string(),
-                line: 1,
-                column: 1,
-            },
-            tokens::Token {
-                token_type: "Operator",
-                token_sub_type: Some("Plus"),
-                value: "+".to_string(),
-                line: 1,
-                column: 5,
-            },
+            tokens::Token::new("Number", None, "123".to_string(), 1, 1),
+            tokens::Token::new("Operator", Some("Plus"), "+".to_string(), 1, 5),
         ];
 
         println!("\nComparison Example:");
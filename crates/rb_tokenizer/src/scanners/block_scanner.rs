@@ -1,9 +1,13 @@
+use super::html_entities;
 use super::scanner::Scanner;
-use crate::tokens::{Token, TokenizationError};
-use regex::Regex;
+use crate::tokens::{DelimiterLocation, Token, TokenFlags, TokenKind, TokenValue, TokenizationError};
+use regex_automata::{meta, Anchored, Input};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Types of escape rules supported by the scanner
+#[derive(Clone)]
 pub enum EscapeRule {
     /// Simple single-character escape (e.g., \n, \t)
     Simple {
@@ -23,8 +27,16 @@ pub enum EscapeRule {
 
     /// Regex-based escape pattern
     Pattern {
-        /// Regular expression to match the escape sequence
-        pattern: Regex,
+        /// Regular expression to match the escape sequence, searched anchored
+        /// at the current position; `^` matches there too
+        pattern: meta::Regex,
+    },
+
+    /// A sequence written twice standing for itself, like the `''` of SQL
+    /// strings or the `""` of CSV fields
+    Doubled {
+        /// The sequence, typically the end delimiter
+        sequence: String,
     },
 
     /// Balanced escape like ${...} or \(...\)
@@ -39,6 +51,16 @@ pub enum EscapeRule {
 }
 
 impl EscapeRule {
+    /// A pattern escape matching `pattern` at the current position; errors
+    /// are reported as `Regex::new` reports them
+    pub fn pattern(pattern: &str) -> Result<Self, regex::Error> {
+        let pattern = meta::Regex::new(pattern).map_err(|e| match e.size_limit() {
+            Some(limit) => regex::Error::CompiledTooBig(limit),
+            None => regex::Error::Syntax(e.syntax_error().map_or_else(|| e.to_string(), |e| e.to_string())),
+        })?;
+        Ok(EscapeRule::Pattern { pattern })
+    }
+
     /// Try to match an escape sequence at the current position
     /// Returns the length of the matched sequence or None if no match
     pub fn try_match(&self, input: &str, position: usize) -> Option<usize> {
@@ -47,38 +69,30 @@ impl EscapeRule {
         }
 
         match self {
-            EscapeRule::Simple { escape_char: _ } => {
-                // Process simple escape
-                if position + 1 < input.len() {
-                    return Some(2); // Escape character + the character it escapes
-                }
-                None
+            EscapeRule::Simple { escape_char } => {
+                // The escape character and the character it escapes
+                let escaped = input[position..].strip_prefix(*escape_char)?.chars().next()?;
+                Some(escape_char.len_utf8() + escaped.len_utf8())
             },
 
             EscapeRule::Named { start_char, end_char, max_length } => {
-                if input[position..].starts_with(*start_char) {
-                    // Look for end_char within the max_length
-                    let search_end = std::cmp::min(position + *max_length, input.len());
-                    for i in position + 1..search_end {
-                        if i < input.len() {
-                            if let Some(c) = input[i..].chars().next() {
-                                if c == *end_char {
-                                    return Some(i - position + 1);
-                                }
-                            }
-                        }
-                    }
-                }
-                None
+                // Look for end_char within the max_length
+                let name = input[position..].strip_prefix(*start_char)?;
+                name.char_indices()
+                    .take_while(|(i, _)| start_char.len_utf8() + i < *max_length)
+                    .find(|&(_, c)| c == *end_char)
+                    .map(|(i, c)| start_char.len_utf8() + i + c.len_utf8())
             },
 
             EscapeRule::Pattern { pattern } => {
-                if let Some(mat) = pattern.find(&input[position..]) {
-                    if mat.start() == 0 {  // Match must start at current position
-                        return Some(mat.end());
-                    }
-                }
-                None
+                // Anchored, so the search never looks past the current position for a start
+                let input = Input::new(&input[position..]).anchored(Anchored::Yes);
+                pattern.find(input).map(|mat| mat.end())
+            },
+
+            EscapeRule::Doubled { sequence } => {
+                let rest = input[position..].strip_prefix(sequence.as_str())?;
+                (!sequence.is_empty() && rest.starts_with(sequence.as_str())).then(|| 2 * sequence.len())
             },
 
             EscapeRule::Balanced { start_seq, end_seq, allow_nesting } => {
@@ -113,23 +127,109 @@ impl EscapeRule {
     }
 }
 
+/// Function of an `EscapeTransform::Custom`, called with the matched sequence
+pub type EscapeFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// How a matched escape sequence is written to the token value when escapes
+/// are transformed.
+///
+/// Rules without a transform keep their default: a simple escape becomes the
+/// mapping of the escaped character or the character itself, a named escape
+/// is resolved through the escape map (and the HTML5 references, if enabled),
+/// a pattern escape becomes the mapping of the whole sequence, a doubled
+/// escape becomes the single sequence, and a balanced escape is kept as written.
+#[derive(Clone)]
+pub enum EscapeTransform {
+    /// Keeps the sequence as written
+    Verbatim,
+
+    /// Drops the sequence from the value
+    Remove,
+
+    /// Decodes the first run of hex digits after the first character as a
+    /// code point, as in `\x41`, `\u00E9` or `\u{1F600}`. Invalid code points
+    /// become U+FFFD; a sequence without hex digits is kept as written.
+    HexCodePoint,
+
+    /// Calls the function with the matched sequence; `None` keeps the sequence as written
+    Custom(EscapeFn),
+}
+
+impl EscapeTransform {
+    /// A transform calling `transform` with the matched sequence
+    pub fn custom(transform: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        EscapeTransform::Custom(Arc::new(transform))
+    }
+
+    /// Returns the replacement of `sequence`, or `None` to keep it as written
+    fn apply(&self, sequence: &str) -> Option<String> {
+        match self {
+            EscapeTransform::Verbatim => None,
+            EscapeTransform::Remove => Some(String::new()),
+            EscapeTransform::HexCodePoint => {
+                let rest = sequence.chars().next().map_or("", |c| &sequence[c.len_utf8()..]);
+                let digits = &rest[rest.find(|c: char| c.is_ascii_hexdigit())?..];
+                let digits = &digits[..digits.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(digits.len())];
+                let ch = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                Some(ch.to_string())
+            }
+            EscapeTransform::Custom(transform) => transform(sequence),
+        }
+    }
+}
+
+/// A block split at its nested delimiters, as returned by `BlockScanner::scan_tree`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockTree {
+    /// The delimiter that opened the block
+    pub open: String,
+    /// The delimiter that closed the block
+    pub close: String,
+    /// The text and nested blocks between the delimiters, in input order
+    pub children: Vec<BlockNode>,
+}
+
+/// A part of a `BlockTree`
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockNode {
+    /// Text between delimiters, with escapes transformed if the scanner transforms them
+    Text(String),
+    /// A nested block
+    Block(BlockTree),
+}
+
+/// A delimiter found while walking a block: the index of its pair and its
+/// offset, or an escape sequence that was skipped
+enum Delimiter {
+    Open(usize, usize),
+    Close(usize, usize),
+    Escape,
+}
+
 /// `BlockScanner` implementation for parsing block structures with start and end delimiters
 /// that can be nested. This scanner handles structures like code blocks, comments blocks,
 /// string literals with multi-character delimiters, etc.
-pub struct BlockScanner {
-    /// The start delimiter that marks the beginning of a block
-    start_delimiter: String,
-
-    /// The end delimiter that marks the end of a block
-    end_delimiter: String,
+///
+/// Further delimiter pairs can be added with `add_delimiter_pair`, so one scanner
+/// handles a family such as `(`/`)`, `[`/`]` and `{`/`}`. A block opens with any
+/// of the pairs; with nesting allowed, every pair nests inside it and a closer that
+/// does not match the innermost open delimiter (`( ]`) is reported as a
+/// `MismatchedBlockDelimiter` error.
+#[derive(Clone)]
+pub struct BlockScanner<K = &'static str> {
+    /// The start and end delimiters of the blocks, the pair given to `new` first
+    pairs: Vec<(String, String)>,
 
     /// The token type to assign to matched blocks
-    token_type: &'static str,
+    token_type: K,
 
     /// An optional token subtype for more specific categorization
-    token_sub_type: Option<&'static str>,
+    token_sub_type: Option<K>,
 
-    /// Whether to support nested blocks with the same delimiters
+    /// Whether to support nested blocks with the same delimiters, or any of the pairs
     allow_nesting: bool,
 
     /// Whether to preserve the content exactly as-is (raw mode)
@@ -142,34 +242,59 @@ pub struct BlockScanner {
     /// List of escape rules to apply
     escape_rules: Vec<EscapeRule>,
 
-    /// Map for transforming escape sequences to their actual characters when processing token values
-    escape_map: HashMap<String, char>,
+    /// The transform of each escape rule, `None` for the rule's default
+    escape_transforms: Vec<Option<EscapeTransform>>,
+
+    /// Whether a backslash escapes the next character when no escape rule matches
+    default_backslash_escape: bool,
+
+    /// Map for transforming escape sequences to their replacement text when processing token values
+    escape_map: HashMap<String, String>,
 
     /// Whether to transform escaped sequences in the final token value
     transform_escapes: bool,
+
+    /// Whether named escapes missing from the escape map are looked up as
+    /// HTML5 character references, including numeric ones like `&#x1F;`
+    html_entities: bool,
+
+    /// Whether a named escape that cannot be resolved is an error
+    reject_unknown_entities: bool,
+
+    /// Whether tokens get their content, with escapes transformed, as a `TokenValue::Str` literal
+    typed_values: bool,
+
+    /// Whether a block missing its end delimiter runs to the end of the input
+    /// instead of being an error
+    allow_unterminated: bool,
 }
 
-impl BlockScanner {
+impl<K: TokenKind> BlockScanner<K> {
     pub fn new(
         start_delimiter: &str,
         end_delimiter: &str,
-        token_type: &'static str,
-        token_sub_type: Option<&'static str>,
+        token_type: K,
+        token_sub_type: Option<K>,
         allow_nesting: bool,
         raw_mode: bool,
         include_delimiters: bool,
     ) -> Self {
         Self {
-            start_delimiter: start_delimiter.to_string(),
-            end_delimiter: end_delimiter.to_string(),
+            pairs: vec![(start_delimiter.to_string(), end_delimiter.to_string())],
             token_type,
             token_sub_type,
             allow_nesting,
             raw_mode,
             include_delimiters,
             escape_rules: Vec::new(),
+            escape_transforms: Vec::new(),
+            default_backslash_escape: true,
             escape_map: HashMap::new(),
             transform_escapes: false,
+            html_entities: false,
+            reject_unknown_entities: false,
+            typed_values: false,
+            allow_unterminated: false,
         }
     }
 
@@ -178,15 +303,172 @@ impl BlockScanner {
         self.include_delimiters
     }
 
+    /// Returns the start delimiter string
+    pub fn start_delimiter(&self) -> &str {
+        &self.pairs[0].0
+    }
+
+    /// Returns the end delimiter string
+    pub fn end_delimiter(&self) -> &str {
+        &self.pairs[0].1
+    }
+
+    /// Returns every start and end delimiter pair, the pair given to `new` first
+    pub fn delimiter_pairs(&self) -> &[(String, String)] {
+        &self.pairs
+    }
+
+    /// Adds another pair of delimiters that can open and close a block
+    pub fn add_delimiter_pair(&mut self, start_delimiter: &str, end_delimiter: &str) {
+        self.pairs.push((start_delimiter.to_string(), end_delimiter.to_string()));
+    }
+
+    pub fn token_type(&self) -> K {
+        self.token_type
+    }
+
+    pub fn token_sub_type(&self) -> Option<K> {
+        self.token_sub_type
+    }
+
+    /// Returns whether nested blocks with the same delimiters are supported
+    pub fn allows_nesting(&self) -> bool {
+        self.allow_nesting
+    }
+
+    /// Returns whether escape sequence processing is disabled
+    pub fn is_raw(&self) -> bool {
+        self.raw_mode
+    }
+
+    /// Returns the escape rules, in the order they are tried
+    pub fn escape_rules(&self) -> &[EscapeRule] {
+        &self.escape_rules
+    }
+
+    /// Returns the transform of the escape rule at `index`, if it has one
+    pub fn escape_transform(&self, index: usize) -> Option<&EscapeTransform> {
+        self.escape_transforms.get(index)?.as_ref()
+    }
+
+    /// Returns whether a backslash escapes the next character when no escape rule matches
+    pub fn uses_default_backslash_escape(&self) -> bool {
+        self.default_backslash_escape
+    }
+
+    /// Returns the escape sequence to replacement text mappings
+    pub fn escape_map(&self) -> &HashMap<String, String> {
+        &self.escape_map
+    }
+
+    /// Returns whether escaped sequences are transformed in the token value
+    pub fn transforms_escapes(&self) -> bool {
+        self.transform_escapes
+    }
+
+    /// Returns whether named escapes fall back to the HTML5 character references
+    pub fn uses_html_entities(&self) -> bool {
+        self.html_entities
+    }
+
+    /// Returns whether unresolved named escapes are reported as errors
+    pub fn rejects_unknown_entities(&self) -> bool {
+        self.reject_unknown_entities
+    }
+
+    /// Returns whether tokens get their content as a string literal
+    pub fn has_typed_values(&self) -> bool {
+        self.typed_values
+    }
+
+    /// Returns whether unterminated blocks are scanned to the end of the input
+    pub fn allows_unterminated(&self) -> bool {
+        self.allow_unterminated
+    }
+
     /// Public method to find the end of a block from the input
     /// Returns the position after the end delimiter if found
     pub fn find_match_end(&self, input: &str) -> Result<Option<usize>, TokenizationError> {
         self.find_block_end(input)
     }
 
+    /// Scans a block like `scan`, returning it split at its nested delimiters
+    /// instead of as one flat value
+    pub fn scan_tree(&self, input: &str) -> Result<Option<BlockTree>, TokenizationError> {
+        // Open blocks, each with the offset its current text run starts at
+        let mut open: Vec<(BlockTree, usize)> = Vec::new();
+        let mut tree = None;
+        let push_text = |block: &mut BlockTree, text: &str| {
+            if !text.is_empty() {
+                block.children.push(BlockNode::Text(text.to_string()));
+            }
+        };
+
+        self.walk_block(input, |delimiter| match delimiter {
+            Delimiter::Open(pair, at) => {
+                let (start, end) = &self.pairs[pair];
+                if let Some((parent, text_start)) = open.last_mut() {
+                    push_text(parent, &input[*text_start..at]);
+                }
+                let block = BlockTree {
+                    open: start.clone(),
+                    close: end.clone(),
+                    children: Vec::new(),
+                };
+                open.push((block, at + start.len()));
+            }
+            Delimiter::Close(pair, at) => {
+                let (mut block, text_start) = open.pop().expect("a closer always matches an open block");
+                push_text(&mut block, &input[text_start..at]);
+                match open.last_mut() {
+                    Some((parent, text_start)) => {
+                        parent.children.push(BlockNode::Block(block));
+                        *text_start = at + self.pairs[pair].1.len();
+                    }
+                    None => tree = Some(block),
+                }
+            }
+            Delimiter::Escape => {}
+        })?;
+        if let Some(tree) = &mut tree {
+            self.escape_tree(tree)?;
+        }
+        Ok(tree)
+    }
+
+    fn escape_tree(&self, tree: &mut BlockTree) -> Result<(), TokenizationError> {
+        for child in &mut tree.children {
+            match child {
+                BlockNode::Text(text) => *text = self.escaped_value(std::mem::take(text))?,
+                BlockNode::Block(block) => self.escape_tree(block)?,
+            }
+        }
+        Ok(())
+    }
+
     /// Add an escape rule to this scanner
     pub fn add_escape_rule(&mut self, rule: EscapeRule) {
         self.escape_rules.push(rule);
+        self.escape_transforms.push(None);
+    }
+
+    /// Add an escape rule whose sequences are written to the value by `transform`
+    pub fn add_escape_rule_with_transform(&mut self, rule: EscapeRule, transform: EscapeTransform) {
+        self.escape_rules.push(rule);
+        self.escape_transforms.push(Some(transform));
+    }
+
+    /// Sets the transform of the escape rule at `index`, e.g. to decode the
+    /// sequences of a pattern escape added with `add_pattern_escape`.
+    /// Returns false, changing nothing, if there is no escape rule at `index`.
+    pub fn set_escape_transform(&mut self, index: usize, transform: EscapeTransform) -> bool {
+        match self.escape_transforms.get_mut(index) {
+            Some(slot) => {
+                *slot = Some(transform);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Add a simple escape character (typically backslash)
@@ -203,13 +485,25 @@ impl BlockScanner {
         });
     }
 
-    /// Add a regex pattern-based escape
+    /// Add a regex pattern-based escape, matched at the current position
     pub fn add_pattern_escape(&mut self, pattern: &str) -> Result<(), regex::Error> {
-        let regex = Regex::new(pattern)?;
-        self.add_escape_rule(EscapeRule::Pattern { pattern: regex });
+        self.add_escape_rule(EscapeRule::pattern(pattern)?);
         Ok(())
     }
 
+    /// Add an escape of `sequence` written twice, like `''` in SQL strings
+    pub fn add_doubled_escape(&mut self, sequence: &str) {
+        self.add_escape_rule(EscapeRule::Doubled {
+            sequence: sequence.to_string(),
+        });
+    }
+
+    /// Add an escape of the end delimiter written twice, like `""` in CSV fields
+    pub fn add_doubled_delimiter_escape(&mut self) {
+        let end_delimiter = self.pairs[0].1.clone();
+        self.add_doubled_escape(&end_delimiter);
+    }
+
     /// Add a balanced escape sequence like ${...} or \(...\)
     pub fn add_balanced_escape(&mut self, start_seq: &str, end_seq: &str, allow_nesting: bool) {
         self.add_escape_rule(EscapeRule::Balanced {
@@ -219,9 +513,32 @@ impl BlockScanner {
         });
     }
 
-    /// Add a mapping from escape sequence to character for transformation
-    pub fn add_escape_mapping(&mut self, sequence: &str, replacement: char) {
-        self.escape_map.insert(sequence.to_string(), replacement);
+    /// Add a mapping from escape sequence to its replacement, a character or
+    /// a string such as the two code points of `&NotEqualTilde;`
+    pub fn add_escape_mapping(&mut self, sequence: &str, replacement: impl Into<String>) {
+        self.escape_map.insert(sequence.to_string(), replacement.into());
+    }
+
+    /// Resolves named escapes missing from the escape map as HTML5 named
+    /// character references (`&hellip;`) and numeric references (`&#123;`,
+    /// `&#x1F;`, decoded like browsers do, see `html_entities::numeric`).
+    /// Names can be up to `html_entities::MAX_NAME_LEN` bytes long, so the
+    /// named escape rule needs a `max_length` that allows for them.
+    pub fn set_html_entities(&mut self, enabled: bool) {
+        self.html_entities = enabled;
+    }
+
+    /// Reports named escapes that are neither in the escape map nor, if
+    /// enabled, HTML5 character references as `UnknownEntity` errors
+    pub fn set_reject_unknown_entities(&mut self, reject: bool) {
+        self.reject_unknown_entities = reject;
+    }
+
+    /// Sets whether a backslash escapes the next character when no escape rule
+    /// matches (the default). Turn it off for strings where a backslash is an
+    /// ordinary character, such as SQL or CSV; escape rules still apply.
+    pub fn set_default_backslash_escape(&mut self, enabled: bool) {
+        self.default_backslash_escape = enabled;
     }
 
     /// Enable or disable escape transformation in token values
@@ -229,59 +546,92 @@ impl BlockScanner {
         self.transform_escapes = transform;
     }
 
+    /// Gives tokens their content between the delimiters, with escapes
+    /// transformed even when the value keeps them, as a `TokenValue::Str` literal
+    pub fn set_typed_values(&mut self, enabled: bool) {
+        self.typed_values = enabled;
+    }
+
+    /// Scans a block missing its end delimiter to the end of the input, flagged
+    /// `TokenFlags::UNTERMINATED`, instead of reporting an `UnmatchedBlockDelimiter`
+    /// error. Mismatched closers are still errors.
+    pub fn set_allow_unterminated(&mut self, allow: bool) {
+        self.allow_unterminated = allow;
+    }
+
     /// Helper function to find the end delimiter position, handling nesting if enabled
     fn find_block_end(&self, input: &str) -> Result<Option<usize>, TokenizationError> {
-        // Check if the input starts with the start delimiter
-        if !input.starts_with(&self.start_delimiter) {
+        self.walk_block(input, |_| {})
+    }
+
+    /// Returns the pair whose start delimiter `input` begins with, preferring the longest
+    fn opening_pair(&self, input: &str) -> Option<usize> {
+        (0..self.pairs.len())
+            .filter(|&pair| input.starts_with(&self.pairs[pair].0))
+            .max_by_key(|&pair| (self.pairs[pair].0.len(), std::cmp::Reverse(pair)))
+    }
+
+    /// Walks the block at the start of `input`, calling `visit` for every delimiter
+    /// that opens or closes a block, and returns the position after the block
+    fn walk_block(&self, input: &str, mut visit: impl FnMut(Delimiter)) -> Result<Option<usize>, TokenizationError> {
+        // Check if the input starts with a start delimiter
+        let Some(first) = self.opening_pair(input) else {
             return Ok(None);
-        }
+        };
+        visit(Delimiter::Open(first, 0));
 
-        let mut position = self.start_delimiter.len();
-        let mut nesting_level = 1;
+        // The pair and offset of every open block, innermost last
+        let mut open = vec![(first, 0)];
+        let mut position = self.pairs[first].0.len();
 
         // Process characters until we find the matching end delimiter
         while position < input.len() {
-            // Check for end delimiter
-            if input[position..].starts_with(&self.end_delimiter) {
-                nesting_level -= 1;
-                if nesting_level == 0 {
-                    return Ok(Some(position + self.end_delimiter.len()));
+            let rest = &input[position..];
+            let (innermost, opened_at) = open[open.len() - 1];
+
+            // Skip over escape sequences in non-raw mode, before delimiters so
+            // an escape can start with one, like the `''` of SQL strings
+            if !self.raw_mode {
+                if let Some((_, len)) = self.match_escape(input, position) {
+                    visit(Delimiter::Escape);
+                    position += len;
+                    continue;
                 }
-                position += self.end_delimiter.len();
-                continue;
             }
 
-            // Check for nested start delimiter if nesting is allowed
-            if self.allow_nesting && input[position..].starts_with(&self.start_delimiter) {
-                nesting_level += 1;
-                position += self.start_delimiter.len();
+            // Check for end delimiter
+            let end_delimiter = &self.pairs[innermost].1;
+            if rest.starts_with(end_delimiter.as_str()) {
+                visit(Delimiter::Close(innermost, position));
+                open.pop();
+                position += end_delimiter.len();
+                if open.is_empty() {
+                    return Ok(Some(position));
+                }
                 continue;
             }
 
-            // Handle escape sequences in non-raw mode
-            if !self.raw_mode {
-                let mut escape_handled = false;
-
-                // Try each escape rule in order
-                for rule in &self.escape_rules {
-                    if let Some(len) = rule.try_match(input, position) {
-                        // Skip over the entire escape sequence
-                        position += len;
-                        escape_handled = true;
-                        break;
-                    }
-                }
-
-                // If no escape rule matched but we still have a backslash,
-                // use the default behavior (skip backslash and next character)
-                if !escape_handled && position < input.len() - 1 && input[position..].starts_with('\\') {
-                    // Skip the escape character and the escaped character
-                    position += 2;
+            if self.allow_nesting {
+                // Check for nested start delimiter if nesting is allowed
+                if let Some(pair) = self.opening_pair(rest) {
+                    visit(Delimiter::Open(pair, position));
+                    open.push((pair, position));
+                    position += self.pairs[pair].0.len();
                     continue;
                 }
 
-                if escape_handled {
-                    continue;
+                // The end delimiter of another pair cannot close the innermost block
+                if let Some((_, end)) = self.pairs.iter().find(|(_, end)| rest.starts_with(end.as_str())) {
+                    let location = |delimiter: &str, offset| DelimiterLocation {
+                        delimiter: delimiter.to_string(),
+                        offset,
+                        line: 0,   // To be filled in by the tokenizer
+                        column: 0, // To be filled in by the tokenizer
+                    };
+                    return Err(TokenizationError::MismatchedBlockDelimiter(
+                        location(&self.pairs[innermost].0, opened_at),
+                        location(end, position),
+                    ));
                 }
             }
 
@@ -295,128 +645,174 @@ impl BlockScanner {
         }
 
         // If we get here, we didn't find a matching end delimiter
-        Err(TokenizationError::UnmatchedBlockDelimiter(
-            self.start_delimiter.clone(),
-            self.end_delimiter.clone()
-        ))
+        let (start, end) = &self.pairs[open[open.len() - 1].0];
+        Err(TokenizationError::UnmatchedBlockDelimiter(start.clone(), end.clone()))
     }
 
 
-    /// Process escape sequences in the token value
-    fn process_escape_sequences(&self, input: &str) -> String {
-        if !self.transform_escapes || self.raw_mode || self.escape_rules.is_empty() {
-            return input.to_string();
+    /// Matches an escape sequence at `position`, trying the escape rules in order,
+    /// and returns the index of the rule that matched with the sequence length.
+    /// Unless the default backslash escape is turned off, a backslash and the
+    /// character after it are skipped even when no rule matches them, with no
+    /// rule index.
+    fn match_escape(&self, input: &str, position: usize) -> Option<(Option<usize>, usize)> {
+        for (index, rule) in self.escape_rules.iter().enumerate() {
+            if let Some(len) = rule.try_match(input, position) {
+                return Some((Some(index), len));
+            }
+        }
+
+        // If no escape rule matched but we still have a backslash,
+        // use the default behavior (skip backslash and next character)
+        if !self.default_backslash_escape {
+            return None;
         }
+        let escaped = input[position..].strip_prefix('\\')?.chars().next()?;
+        Some((None, 1 + escaped.len_utf8()))
+    }
 
+    /// Returns the token value for `raw`: transformed if escapes are transformed,
+    /// and checked for unknown named escapes if those are rejected
+    fn escaped_value(&self, raw: String) -> Result<String, TokenizationError> {
+        if self.raw_mode || !(self.transform_escapes || self.reject_unknown_entities) {
+            return Ok(raw);
+        }
+        let processed = self.process_escape_sequences(&raw)?;
+        Ok(if self.transform_escapes { processed } else { raw })
+    }
+
+    /// The replacement of the named escape `name`, from the escape map or the HTML5 references
+    fn resolve_named(&self, name: &str) -> Option<Cow<'_, str>> {
+        if let Some(replacement) = self.escape_map.get(name) {
+            return Some(Cow::Borrowed(replacement));
+        }
+        if !self.html_entities {
+            return None;
+        }
+        if let Some(replacement) = html_entities::lookup(name) {
+            return Some(Cow::Borrowed(replacement));
+        }
+
+        let number = name.strip_prefix('#')?;
+        let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16),
+            None => (number, 10),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        // Too many digits is out of range too
+        let ch = html_entities::numeric(u32::from_str_radix(digits, radix).unwrap_or(u32::MAX));
+        Some(Cow::Owned(ch.to_string()))
+    }
+
+    /// Process escape sequences in the token value, matching them exactly like
+    /// `find_block_end` skips them
+    fn process_escape_sequences(&self, input: &str) -> Result<String, TokenizationError> {
         let mut result = String::with_capacity(input.len());
-        let mut chars = input.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            // Handle simple escapes
-            if let Some(EscapeRule::Simple { escape_char }) = self.escape_rules.iter().find(|r| matches!(r, EscapeRule::Simple { .. })) {
-                if c == *escape_char {
-                    if let Some(&next_c) = chars.peek() {
-                        let escaped = next_c.to_string();
-                        if let Some(&replacement) = self.escape_map.get(&escaped) {
-                            result.push(replacement);
-                        } else {
-                            result.push(next_c);
-                        }
-                        chars.next();
-                    }
-                    continue;
-                }
-            }
+        let mut position = 0;
 
-            // Handle named escapes (e.g., &lt;)
-            if let Some(EscapeRule::Named { start_char, end_char, max_length }) = self.escape_rules.iter().find(|r| matches!(r, EscapeRule::Named { .. })) {
-                if c == *start_char {
-                    // Try to collect up to max_length chars until end_char
-                    let mut name = String::new();
-                    let mut found = false;
-                    for _ in 0..*max_length {
-                        if let Some(&peek_c) = chars.peek() {
-                            if peek_c == *end_char {
-                                chars.next(); // consume end_char
-                                found = true;
-                                break;
-                            } else {
-                                name.push(peek_c);
-                                chars.next();
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                    if found {
-                        if let Some(&replacement) = self.escape_map.get(&name) {
-                            result.push(replacement);
-                            continue;
-                        } else {
-                            // Not a known escape, push as-is
-                            result.push(*start_char);
-                            result.push_str(&name);
-                            result.push(*end_char);
-                            continue;
-                        }
-                    } else {
-                        // Not a valid escape, push as-is
-                        result.push(*start_char);
-                        result.push_str(&name);
-                        continue;
-                    }
-                }
+        while position < input.len() {
+            let Some((rule, len)) = self.match_escape(input, position) else {
+                // Default: push character as-is
+                let c = input[position..].chars().next().expect("position is a character boundary");
+                result.push(c);
+                position += c.len_utf8();
+                continue;
+            };
+
+            let sequence = &input[position..position + len];
+            match rule {
+                Some(index) => result.push_str(&self.transform_escape(index, sequence)?),
+                // Skipped without a rule, so kept as written
+                None => result.push_str(sequence),
             }
+            position += len;
+        }
 
-            // Default: push character as-is
-            result.push(c);
+        Ok(result)
+    }
+
+    /// The text the escape `sequence` matched by the rule at `index` stands for
+    fn transform_escape<'a>(&'a self, index: usize, sequence: &'a str) -> Result<Cow<'a, str>, TokenizationError> {
+        if let Some(transform) = &self.escape_transforms[index] {
+            return Ok(transform.apply(sequence).map_or(Cow::Borrowed(sequence), Cow::Owned));
         }
 
-        result
+        let replacement = match &self.escape_rules[index] {
+            EscapeRule::Simple { escape_char } => {
+                let escaped = &sequence[escape_char.len_utf8()..];
+                Some(self.escape_map.get(escaped).map_or(escaped, String::as_str).into())
+            }
+            // Handle named escapes (e.g., &lt;)
+            EscapeRule::Named { start_char, end_char, .. } => {
+                let name = &sequence[start_char.len_utf8()..sequence.len() - end_char.len_utf8()];
+                let replacement = self.resolve_named(name);
+                if replacement.is_none() && self.reject_unknown_entities {
+                    return Err(TokenizationError::UnknownEntity(sequence.to_string()));
+                }
+                replacement
+            }
+            EscapeRule::Pattern { .. } => self.escape_map.get(sequence).map(|replacement| replacement.as_str().into()),
+            EscapeRule::Doubled { sequence: single } => Some(single.as_str().into()),
+            EscapeRule::Balanced { .. } => None,
+        };
+        // Not a known escape, kept as-is
+        Ok(replacement.unwrap_or(Cow::Borrowed(sequence)))
     }
 }
 
-impl Scanner for BlockScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
-        // Check if the input starts with the start delimiter
-        if !input.starts_with(&self.start_delimiter) {
+impl<K: TokenKind> Scanner<K> for BlockScanner<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
+        // Check if the input starts with a start delimiter
+        let Some(pair) = self.opening_pair(input) else {
             return Ok(None);
+        };
+        let (start_delimiter, end_delimiter) = &self.pairs[pair];
+
+        // Find the end of the block, noting whether it contains escapes
+        let mut had_escapes = false;
+        let walked = self.walk_block(input, |delimiter| had_escapes |= matches!(delimiter, Delimiter::Escape));
+        let (content_end, terminated) = match walked {
+            Ok(Some(end_pos)) => (end_pos - end_delimiter.len(), true),
+            Ok(None) => return Ok(None),
+            Err(TokenizationError::UnmatchedBlockDelimiter(..)) if self.allow_unterminated => (input.len(), false),
+            Err(e) => return Err(e),
+        };
+        let raw = &input[start_delimiter.len()..content_end];
+
+        // Process escape sequences if needed
+        let content = self.escaped_value(raw.to_string())?;
+        let literal = match self.typed_values {
+            false => None,
+            true if self.raw_mode || self.transform_escapes => Some(TokenValue::Str(content.clone())),
+            true => Some(TokenValue::Str(self.process_escape_sequences(raw)?)),
+        };
+        let token_value = match (self.include_delimiters, terminated) {
+            (true, true) => format!("{}{}{}", start_delimiter, content, end_delimiter),
+            (true, false) => format!("{}{}", start_delimiter, content),
+            (false, _) => content,
+        };
+
+        // The tokenizer measures the block again with `consumed_len`, since
+        // the value may leave out delimiters; line and column are filled in by it
+        let mut token = Token::new(self.token_type, self.token_sub_type, token_value, 0, 0);
+        token.literal = literal;
+        if had_escapes {
+            token.flags.insert(TokenFlags::HAD_ESCAPES);
+        }
+        if !terminated {
+            token.flags.insert(TokenFlags::UNTERMINATED);
         }
+        Ok(Some(token))
+    }
 
-        // Find the end of the block
+    fn consumed_len(&self, input: &str, token: &Token<K>) -> usize {
+        // The value may omit delimiters or have escapes transformed, so re-measure the block
         match self.find_block_end(input) {
-            Ok(Some(end_pos)) => {
-                let full_match = &input[0..end_pos];
-
-                let raw_value = if self.include_delimiters {
-                    full_match.to_string()
-                } else {
-                    input[self.start_delimiter.len()..end_pos - self.end_delimiter.len()].to_string()
-                };
-
-                // Process escape sequences if needed
-                let token_value = if !self.raw_mode && self.transform_escapes {
-                    self.process_escape_sequences(&raw_value)
-                } else {
-                    raw_value
-                };
-
-                // Create token with the correct length that accounts for delimiters
-                // even when they're not included in the value
-                let token = Token {
-                    token_type: self.token_type,
-                    token_sub_type: self.token_sub_type,
-                    value: token_value,
-                    line: 0,   // To be filled in by the tokenizer
-                    column: 0, // To be filled in by the tokenizer
-                };
-
-                // Return the full match end position to ensure
-                // tokenizer correctly advances past all consumed characters
-                Ok(Some(token))
-            },
-            Ok(None) => Ok(None),
-            Err(e) => Err(e),
+            Ok(Some(end_pos)) => end_pos,
+            Err(TokenizationError::UnmatchedBlockDelimiter(..)) if self.allow_unterminated => input.len(),
+            _ => token.value.len(),
         }
     }
 }
\ No newline at end of file
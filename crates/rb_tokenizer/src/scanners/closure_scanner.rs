@@ -1,21 +1,24 @@
 use super::Scanner;
 
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenKind};
 use crate::tokens::TokenizationError;
 
-pub struct ClosureScanner {
+/// Boxed scanning closure; `Send + Sync` so tokenizers can be shared across threads.
+pub type ScanFn<K = &'static str> = Box<dyn Fn(&str) -> Result<Option<Token<K>>, TokenizationError> + Send + Sync>;
+
+pub struct ClosureScanner<K = &'static str> {
     // cb is a closure that takes a string slice and returns a Result<Option<Token>, TokenizationError>
-    cb: Box<dyn Fn(&str) -> Result<Option<Token>, TokenizationError>>,
+    cb: ScanFn<K>,
 }
 
-impl ClosureScanner {
-    pub fn new(cb: Box<dyn Fn(&str) -> Result<Option<Token>, TokenizationError>>) -> Self {
+impl<K: TokenKind> ClosureScanner<K> {
+    pub fn new(cb: ScanFn<K>) -> Self {
         ClosureScanner { cb }
     }
 }
 
-impl Scanner for ClosureScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+impl<K: TokenKind> Scanner<K> for ClosureScanner<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
         (self.cb)(input)
     }
 }
@@ -0,0 +1,117 @@
+use crate::tokens::{Token, TokenKind, TokenizationError};
+use std::any::Any;
+
+/// What a context-aware scanner knows about the position it is scanning.
+///
+/// Mode changes made while scanning only take effect when the scanner
+/// returns a token, so a scanner that declines the input leaves the mode as
+/// it was. The user state passed to `Tokenizer::tokenize_with_state` is
+/// changed directly, so scanners should only change it when they return a
+/// token.
+pub struct ScanContext<'c, K: TokenKind = &'static str> {
+    previous: Option<&'c Token<K>>,
+    modes: Vec<&'static str>,
+    offset: usize,
+    line: usize,
+    column: usize,
+    state: Option<&'c mut dyn Any>,
+}
+
+impl<'c, K: TokenKind> ScanContext<'c, K> {
+    /// The mode reported while no mode has been pushed
+    pub const DEFAULT_MODE: &'static str = "default";
+
+    pub(crate) fn new(
+        previous: Option<&'c Token<K>>,
+        modes: Vec<&'static str>,
+        offset: usize,
+        line: usize,
+        column: usize,
+        state: Option<&'c mut dyn Any>,
+    ) -> Self {
+        Self {
+            previous,
+            modes,
+            offset,
+            line,
+            column,
+            state,
+        }
+    }
+
+    /// The last token produced before this position that is not whitespace
+    pub fn previous(&self) -> Option<&'c Token<K>> {
+        self.previous
+    }
+
+    /// The innermost mode, or `DEFAULT_MODE`
+    pub fn mode(&self) -> &'static str {
+        self.modes.last().copied().unwrap_or(Self::DEFAULT_MODE)
+    }
+
+    /// Every pushed mode, outermost first
+    pub fn modes(&self) -> &[&'static str] {
+        &self.modes
+    }
+
+    /// Enters `mode`, e.g. on the backtick opening a template literal
+    pub fn push_mode(&mut self, mode: &'static str) {
+        self.modes.push(mode);
+    }
+
+    /// Leaves the innermost mode and returns it
+    pub fn pop_mode(&mut self) -> Option<&'static str> {
+        self.modes.pop()
+    }
+
+    /// Byte offset of the input being scanned within the whole input
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Line of the input being scanned
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column of the input being scanned, counted in the configured `ColumnMode`
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The user state, if tokenizing with a state of type `S`
+    pub fn state<S: Any>(&self) -> Option<&S> {
+        self.state.as_deref()?.downcast_ref()
+    }
+
+    /// The user state for updating, if tokenizing with a state of type `S`
+    pub fn state_mut<S: Any>(&mut self) -> Option<&mut S> {
+        self.state.as_deref_mut()?.downcast_mut()
+    }
+
+    pub(crate) fn into_modes(self) -> Vec<&'static str> {
+        self.modes
+    }
+}
+
+/// A scanner that decides with the help of the surrounding tokens, e.g. a
+/// JavaScript regex literal, which can only follow an operator or `(`.
+pub trait ContextScanner<K: TokenKind = &'static str>: Send + Sync {
+    fn scan(&self, input: &str, context: &mut ScanContext<'_, K>) -> Result<Option<Token<K>>, TokenizationError>;
+
+    /// Returns how many bytes of `input` were consumed to produce `token`;
+    /// see `Scanner::consumed_len`
+    fn consumed_len(&self, _input: &str, token: &Token<K>) -> usize {
+        token.value.len()
+    }
+}
+
+/// Boxed context-aware scanning closure
+pub type ContextScanFn<K = &'static str> =
+    Box<dyn Fn(&str, &mut ScanContext<'_, K>) -> Result<Option<Token<K>>, TokenizationError> + Send + Sync>;
+
+impl<K: TokenKind> ContextScanner<K> for ContextScanFn<K> {
+    fn scan(&self, input: &str, context: &mut ScanContext<'_, K>) -> Result<Option<Token<K>>, TokenizationError> {
+        self(input, context)
+    }
+}
@@ -1,21 +1,36 @@
 use super::scanner::Scanner;
-use crate::tokens::{Token, TokenizationError};
+use crate::tokenizers::position::LineEndings;
+use crate::tokens::{Token, TokenKind, TokenizationError};
 
 /// `EolScanner` implementation for parsing structures that start with a specific delimiter
 /// and continue until the end of line. This scanner handles structures like line comments,
 /// preprocessor directives, and other line-oriented syntax.
-pub struct EolScanner {
+///
+/// The line terminator, as recognized by the tokenizer's `LineEndings`, ends
+/// the token value; `set_include_terminator(false)` leaves it out of the value
+/// while still consuming it, so CRLF comments carry no `\r\n`. A line
+/// ending right after the continuation sequence (e.g. `\` in C) does not end
+/// the token, and the token stops before any of its terminators (e.g. `%>`
+/// ending an EJS comment on the same line), leaving them to other scanners.
+#[derive(Clone)]
+pub struct EolScanner<K = &'static str> {
     pub delimiter: String,
-    pub token_type: &'static str,
-    pub token_sub_type: Option<&'static str>,
+    pub token_type: K,
+    pub token_sub_type: Option<K>,
     pub include_delimiter: bool,
+    /// Which sequences end the line; `None` follows the tokenizer's
+    /// `line_endings`, or `LineEndings::default()` when scanned on its own
+    pub line_endings: Option<LineEndings>,
+    pub continuation: Option<String>,
+    pub include_terminator: bool,
+    pub terminators: Vec<String>,
 }
 
-impl EolScanner {
+impl<K: TokenKind> EolScanner<K> {
     pub fn new(
         delimiter: &str,
-        token_type: &'static str,
-        token_sub_type: Option<&'static str>,
+        token_type: K,
+        token_sub_type: Option<K>,
         include_delimiter: bool,
     ) -> Self {
         Self {
@@ -23,9 +38,35 @@ impl EolScanner {
             token_type,
             token_sub_type,
             include_delimiter,
+            line_endings: None,
+            continuation: None,
+            include_terminator: true,
+            terminators: Vec::new(),
         }
     }
 
+    /// Sets the sequence that continues the token onto the next line when it
+    /// ends a line, e.g. a backslash for C preprocessor directives
+    pub fn set_line_continuation(&mut self, continuation: Option<&str>) {
+        self.continuation = continuation.map(str::to_string);
+    }
+
+    /// Sets whether the line terminator is part of the token value (default
+    /// `true`). It is consumed either way, so positions after the token are unchanged.
+    pub fn set_include_terminator(&mut self, include_terminator: bool) {
+        self.include_terminator = include_terminator;
+    }
+
+    /// Ends the token before `terminator` when it appears before the end of the line
+    pub fn add_terminator(&mut self, terminator: &str) {
+        self.terminators.push(terminator.to_string());
+    }
+
+    /// Sets which sequences end the line, instead of following the tokenizer's policy
+    pub fn set_line_endings(&mut self, line_endings: LineEndings) {
+        self.line_endings = Some(line_endings);
+    }
+
     /// Returns whether the delimiter is included in the token value
     pub fn includes_delimiter(&self) -> bool {
         self.include_delimiter
@@ -36,54 +77,71 @@ impl EolScanner {
         &self.delimiter
     }
 
-    /// Helper function to find the end of line position
-    fn find_line_end(&self, input: &str) -> Option<usize> {
-        // Check if the input starts with the delimiter
-        if !input.starts_with(&self.delimiter) {
-            return None;
-        }
+    /// Scans with `line_endings` unless the scanner sets its own; the
+    /// tokenizer passes its configured policy
+    pub fn scan_with_line_endings(
+        &self,
+        input: &str,
+        line_endings: LineEndings,
+    ) -> Result<Option<Token<K>>, TokenizationError> {
+        // Find the end of line
+        let Some((end_pos, _)) = self.find_line_end(input, line_endings) else {
+            return Ok(None);
+        };
+        let token_value = if self.include_delimiter {
+            input[0..end_pos].to_string()
+        } else {
+            input[self.delimiter.len()..end_pos].to_string()
+        };
 
-        // Find the next newline character
-        let newline_pos = input.find('\n').unwrap_or(input.len());
+        // Line and column are filled in by the tokenizer
+        Ok(Some(Token::new(self.token_type, self.token_sub_type, token_value, 0, 0)))
+    }
 
-        // Return the position after the newline, or the end of the input if no newline found
-        if newline_pos < input.len() {
-            Some(newline_pos + 1) // Include the newline in the match
-        } else {
-            Some(newline_pos) // End of input
-        }
+    /// The input consumed by a token scanned with `scan_with_line_endings`
+    pub fn consumed_len_with_line_endings(&self, input: &str, token: &Token<K>, line_endings: LineEndings) -> usize {
+        self.find_line_end(input, line_endings).map_or(token.value.len(), |(_, consumed)| consumed)
     }
-}
 
-impl Scanner for EolScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+    /// Finds where the token value ends and how much of the input it consumes
+    fn find_line_end(&self, input: &str, line_endings: LineEndings) -> Option<(usize, usize)> {
+        let line_endings = self.line_endings.unwrap_or(line_endings);
         // Check if the input starts with the delimiter
         if !input.starts_with(&self.delimiter) {
-            return Ok(None);
+            return None;
         }
 
-        // Find the end of line
-        if let Some(end_pos) = self.find_line_end(input) {
-            let full_match = &input[0..end_pos];
+        let mut from = self.delimiter.len();
+        loop {
+            let rest = &input[from..];
+            let line_end = line_endings.find(rest);
+            let searched = &rest[..line_end.map_or(rest.len(), |(at, _)| at)];
+            if let Some(at) = self.terminators.iter().filter_map(|terminator| searched.find(terminator.as_str())).min() {
+                return Some((from + at, from + at));
+            }
 
-            let token_value = if self.include_delimiter {
-                full_match.to_string()
-            } else {
-                input[self.delimiter.len()..end_pos].to_string()
+            let Some((at, len)) = line_end else {
+                return Some((input.len(), input.len()));
             };
+            let (at, end) = (from + at, from + at + len);
+            let continued = self
+                .continuation
+                .as_ref()
+                .is_some_and(|continuation| input[self.delimiter.len()..at].ends_with(continuation.as_str()));
+            if !continued {
+                return Some((if self.include_terminator { end } else { at }, end));
+            }
+            from = end;
+        }
+    }
+}
 
-            // Create token with the correct value
-            let token = Token {
-                token_type: self.token_type,
-                token_sub_type: self.token_sub_type,
-                value: token_value,
-                line: 0,   // To be filled in by the tokenizer
-                column: 0, // To be filled in by the tokenizer
-            };
+impl<K: TokenKind> Scanner<K> for EolScanner<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
+        self.scan_with_line_endings(input, LineEndings::default())
+    }
 
-            Ok(Some(token))
-        } else {
-            Ok(None)
-        }
+    fn consumed_len(&self, input: &str, token: &Token<K>) -> usize {
+        self.consumed_len_with_line_endings(input, token, LineEndings::default())
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 pub mod block_scanner;
 pub mod closure_scanner;
+pub mod context_scanner;
 pub mod eol_scanner;
+pub mod html_entities;
 pub mod regex_scanner;
 pub mod scanner;
 pub mod scanner_types;
@@ -8,9 +10,10 @@ pub mod symbol_scanner;
 
 pub use block_scanner::BlockScanner;
 pub use closure_scanner::ClosureScanner;
+pub use context_scanner::{ContextScanner, ScanContext};
 pub use eol_scanner::EolScanner;
 pub use regex_scanner::RegexScanner;
 pub use scanner::Scanner;
 pub use scanner_types::CallbackScanner;
 pub use scanner_types::ScannerType;
-pub use symbol_scanner::SymbolScanner;
+pub use symbol_scanner::{CaseMatching, SymbolOptions, SymbolScanner};
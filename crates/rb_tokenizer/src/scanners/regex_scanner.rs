@@ -1,51 +1,190 @@
 use super::Scanner;
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenKind, TokenValue};
 use crate::tokens::TokenizationError;
 use super::scanner::AcceptStrategy;
-use regex::Regex;
+use regex::{Captures, Regex};
+use std::fmt;
+use std::sync::Arc;
 
-pub struct RegexScanner {
+/// Converts the text a regex matched to the token's literal value, or `None` to leave it without one
+pub type LiteralFn = Arc<dyn Fn(&str) -> Option<TokenValue> + Send + Sync>;
+
+/// A capture group of a regex, by number or by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureGroup {
+    Index(usize),
+    Name(String),
+}
+
+impl CaptureGroup {
+    fn get<'h>(&self, captures: &Captures<'h>) -> Option<regex::Match<'h>> {
+        match self {
+            CaptureGroup::Index(index) => captures.get(*index),
+            CaptureGroup::Name(name) => captures.name(name),
+        }
+    }
+
+    fn exists_in(&self, regex: &Regex) -> bool {
+        match self {
+            CaptureGroup::Index(index) => *index < regex.captures_len(),
+            CaptureGroup::Name(name) => regex.capture_names().any(|group| group == Some(name.as_str())),
+        }
+    }
+}
+
+impl From<usize> for CaptureGroup {
+    fn from(index: usize) -> Self {
+        CaptureGroup::Index(index)
+    }
+}
+
+impl From<&str> for CaptureGroup {
+    fn from(name: &str) -> Self {
+        CaptureGroup::Name(name.to_string())
+    }
+}
+
+impl fmt::Display for CaptureGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureGroup::Index(index) => write!(f, "{}", index),
+            CaptureGroup::Name(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+pub struct RegexScanner<K = &'static str> {
     pub pattern: Regex,
-    pub token_type: &'static str,
-    pub token_sub_type: Option<&'static str>,
+    pub token_type: K,
+    pub token_sub_type: Option<K>,
     pub accept_strategy: Option<AcceptStrategy>,
+    pub literal: Option<LiteralFn>,
+    /// The group whose text is the token value, instead of the whole match
+    pub value_group: Option<CaptureGroup>,
+    /// Named groups giving the token's sub-type when they take part in the
+    /// match, the first one that does winning over `token_sub_type`
+    pub sub_type_groups: Vec<(String, K)>,
+    /// Whether the groups other than the value group are kept in `Token::captures`
+    pub capture_metadata: bool,
 }
 
-impl RegexScanner {
-    pub fn new(pattern: &str, token_type: &'static str, token_sub_type: Option<&'static str>) -> Self {
+impl<K: TokenKind> RegexScanner<K> {
+    pub fn new(pattern: &str, token_type: K, token_sub_type: Option<K>) -> Self {
         Self {
             pattern: Regex::new(pattern).unwrap(),
             token_type,
             token_sub_type,
             accept_strategy: None,
+            literal: None,
+            value_group: None,
+            sub_type_groups: Vec::new(),
+            capture_metadata: false,
         }
     }
-    pub fn with_accept_strategy(pattern: &str, token_type: &'static str, token_sub_type: Option<&'static str>, accept_strategy: AcceptStrategy) -> Self {
+    pub fn with_accept_strategy(pattern: &str, token_type: K, token_sub_type: Option<K>, accept_strategy: AcceptStrategy) -> Self {
         Self {
-            pattern: Regex::new(pattern).unwrap(),
-            token_type,
-            token_sub_type,
             accept_strategy: Some(accept_strategy),
+            ..Self::new(pattern, token_type, token_sub_type)
         }
     }
+
+    /// Fills the literal of every token with `convert`, e.g.
+    /// `|text| text.parse().ok().map(TokenValue::Int)` for integers
+    pub fn with_literal(mut self, convert: impl Fn(&str) -> Option<TokenValue> + Send + Sync + 'static) -> Self {
+        self.literal = Some(Arc::new(convert));
+        self
+    }
+
+    /// Uses the text of `group` as the token value, e.g. the content of
+    /// `^"([^"]*)"` without the quotes. The whole match is still consumed,
+    /// and the value is empty when the group does not take part in the match.
+    pub fn with_value_group(mut self, group: impl Into<CaptureGroup>) -> Self {
+        self.value_group = Some(group.into());
+        self
+    }
+
+    /// Gives tokens `sub_type` when the group `name` takes part in the match,
+    /// e.g. `Hex` for `^(?P<Hex>0x[0-9a-f]+)|^(?P<Dec>\d+)`
+    pub fn with_sub_type_group(mut self, name: &str, sub_type: K) -> Self {
+        self.sub_type_groups.push((name.to_string(), sub_type));
+        self
+    }
+
+    /// Keeps the text of the groups other than the value group in
+    /// `Token::captures`, by name or, for unnamed groups, by number
+    pub fn with_capture_metadata(mut self) -> Self {
+        self.capture_metadata = true;
+        self
+    }
+
+    /// Returns whether the scanner reads capture groups rather than only the whole match
+    pub fn uses_captures(&self) -> bool {
+        self.value_group.is_some() || !self.sub_type_groups.is_empty() || self.capture_metadata
+    }
+
+    /// Checks that every group the scanner refers to is in its pattern
+    pub fn check_groups(&self) -> Result<(), String> {
+        let named = self.sub_type_groups.iter().map(|(name, _)| CaptureGroup::Name(name.clone()));
+        for group in self.value_group.iter().cloned().chain(named) {
+            if !group.exists_in(&self.pattern) {
+                return Err(format!("capture group {} is not in regex '{}'", group, self.pattern.as_str()));
+            }
+        }
+        Ok(())
+    }
+
+    fn captured_token(&self, captures: &Captures) -> Token<K> {
+        let value = match &self.value_group {
+            Some(group) => group.get(captures).map_or("", |group| group.as_str()),
+            None => &captures[0],
+        };
+        let token_sub_type = self
+            .sub_type_groups
+            .iter()
+            .find(|(name, _)| captures.name(name).is_some())
+            .map_or(self.token_sub_type, |(_, sub_type)| Some(*sub_type));
+
+        let mut token = Token::new(self.token_type, token_sub_type, value.to_string(), 0, 0);
+        token.literal = self.literal.as_ref().and_then(|convert| convert(value));
+        if self.capture_metadata {
+            for (index, name) in self.pattern.capture_names().enumerate().skip(1) {
+                let group = name.map_or(CaptureGroup::Index(index), CaptureGroup::from);
+                if self.value_group.as_ref().is_some_and(|value| value == &group || value == &CaptureGroup::Index(index)) {
+                    continue;
+                }
+                if let Some(text) = captures.get(index) {
+                    let key = name.map_or_else(|| index.to_string(), str::to_string);
+                    token.captures.push((key, text.as_str().to_string()));
+                }
+            }
+        }
+        token
+    }
 }
 
-impl Scanner for RegexScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+impl<K: TokenKind> Scanner<K> for RegexScanner<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
         if let Some(strategy) = &self.accept_strategy {
             if !strategy.accepts(input) {
                 return Ok(None);
             }
         }
+        if self.uses_captures() {
+            return Ok(self.pattern.captures(input).map(|captures| self.captured_token(&captures)));
+        }
         if let Some(mat) = self.pattern.find(input) {
-            return Ok(Some(Token {
-                token_type: self.token_type,
-                token_sub_type: self.token_sub_type,
-                value: mat.as_str().to_string(),
-                line: 0,
-                column: 0,
-            }))
+            let mut token = Token::new(self.token_type, self.token_sub_type, mat.as_str().to_string(), 0, 0);
+            token.literal = self.literal.as_ref().and_then(|convert| convert(mat.as_str()));
+            return Ok(Some(token));
         }
         Ok(None)
     }
+
+    fn consumed_len(&self, input: &str, token: &Token<K>) -> usize {
+        // The value may be only part of the match
+        match &self.value_group {
+            Some(_) => self.pattern.find(input).map_or(token.value.len(), |mat| mat.end()),
+            None => token.value.len(),
+        }
+    }
 }
@@ -1,22 +1,30 @@
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenKind};
 use crate::tokens::TokenizationError;
 
 pub enum AcceptStrategy {
     StartChars(&'static str),
     Pattern(&'static str),
-    Fn(Box<dyn Fn(&str) -> bool + 'static>),
+    Fn(Box<dyn Fn(&str) -> bool + Send + Sync + 'static>),
 }
 
 impl AcceptStrategy {
     pub fn accepts(&self, input: &str) -> bool {
         match self {
-            AcceptStrategy::StartChars(chars) => input.chars().next().map_or(false, |c| chars.contains(c)),
+            AcceptStrategy::StartChars(chars) => input.chars().next().is_some_and(|c| chars.contains(c)),
             AcceptStrategy::Pattern(pat) => input.starts_with(pat),
             AcceptStrategy::Fn(f) => f(input),
         }
     }
 }
 
-pub trait Scanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError>;
+pub trait Scanner<K: TokenKind = &'static str>: Send + Sync {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError>;
+
+    /// Returns how many bytes of `input` were consumed to produce `token`.
+    ///
+    /// Defaults to the byte length of the token value; scanners whose value
+    /// differs from the matched text (e.g. excluded delimiters) override this.
+    fn consumed_len(&self, _input: &str, token: &Token<K>) -> usize {
+        token.value.len()
+    }
 }
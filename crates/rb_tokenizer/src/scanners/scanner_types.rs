@@ -1,27 +1,81 @@
-use crate::tokens::{Token, TokenizationError};
+use crate::tokens::{Token, TokenKind, TokenizationError};
 
 use super::regex_scanner::RegexScanner;
 use super::symbol_scanner::SymbolScanner;
 use super::block_scanner::BlockScanner;
 use super::eol_scanner::EolScanner;
+use super::context_scanner::{ContextScanner, ScanContext};
 use super::{ClosureScanner, Scanner};
 
-pub enum ScannerType {
-    Symbol(SymbolScanner),
-    Regex(RegexScanner),
-    Block(BlockScanner),
-    Eol(EolScanner),
-    Closure(ClosureScanner),
-    Scanner(Box<dyn Scanner>),
-    Callback(Box<dyn CallbackScanner>),
+pub enum ScannerType<K: TokenKind = &'static str> {
+    Symbol(SymbolScanner<K>),
+    Regex(RegexScanner<K>),
+    Block(BlockScanner<K>),
+    Eol(EolScanner<K>),
+    Closure(ClosureScanner<K>),
+    Scanner(Box<dyn Scanner<K>>),
+    Callback(Box<dyn CallbackScanner<K>>),
+    Context(Box<dyn ContextScanner<K>>),
 }
 
-pub trait CallbackScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError>;
+impl<K: TokenKind> ScannerType<K> {
+    /// Short name of the variant: `symbol`, `regex`, `block`, `eol`, `closure`, `custom`, `callback` or `context`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScannerType::Symbol(_) => "symbol",
+            ScannerType::Regex(_) => "regex",
+            ScannerType::Block(_) => "block",
+            ScannerType::Eol(_) => "eol",
+            ScannerType::Closure(_) => "closure",
+            ScannerType::Scanner(_) => "custom",
+            ScannerType::Callback(_) => "callback",
+            ScannerType::Context(_) => "context",
+        }
+    }
+}
+
+impl<K: TokenKind> From<SymbolScanner<K>> for ScannerType<K> {
+    fn from(scanner: SymbolScanner<K>) -> Self {
+        ScannerType::Symbol(scanner)
+    }
+}
+
+impl<K: TokenKind> From<RegexScanner<K>> for ScannerType<K> {
+    fn from(scanner: RegexScanner<K>) -> Self {
+        ScannerType::Regex(scanner)
+    }
 }
 
-impl Scanner for ScannerType {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+impl<K: TokenKind> From<BlockScanner<K>> for ScannerType<K> {
+    fn from(scanner: BlockScanner<K>) -> Self {
+        ScannerType::Block(scanner)
+    }
+}
+
+impl<K: TokenKind> From<EolScanner<K>> for ScannerType<K> {
+    fn from(scanner: EolScanner<K>) -> Self {
+        ScannerType::Eol(scanner)
+    }
+}
+
+impl<K: TokenKind> From<ClosureScanner<K>> for ScannerType<K> {
+    fn from(scanner: ClosureScanner<K>) -> Self {
+        ScannerType::Closure(scanner)
+    }
+}
+
+impl<K: TokenKind> From<Box<dyn Scanner<K>>> for ScannerType<K> {
+    fn from(scanner: Box<dyn Scanner<K>>) -> Self {
+        ScannerType::Scanner(scanner)
+    }
+}
+
+pub trait CallbackScanner<K: TokenKind = &'static str>: Send + Sync {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError>;
+}
+
+impl<K: TokenKind> Scanner<K> for ScannerType<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
         match self {
             ScannerType::Symbol(scanner) => scanner.scan(input),
             ScannerType::Regex(scanner) => scanner.scan(input),
@@ -30,6 +84,21 @@ impl Scanner for ScannerType {
             ScannerType::Closure(scanner) => scanner.scan(input),
             ScannerType::Scanner(scanner) => scanner.scan(input),
             ScannerType::Callback(scanner) => scanner.scan(input),
+            // Outside a tokenizer there are no surrounding tokens
+            ScannerType::Context(scanner) => scanner.scan(input, &mut ScanContext::new(None, Vec::new(), 0, 1, 1, None)),
+        }
+    }
+
+    fn consumed_len(&self, input: &str, token: &Token<K>) -> usize {
+        match self {
+            ScannerType::Symbol(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Regex(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Block(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Eol(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Closure(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Scanner(scanner) => scanner.consumed_len(input, token),
+            ScannerType::Callback(_) => token.value.len(),
+            ScannerType::Context(scanner) => scanner.consumed_len(input, token),
         }
     }
 }
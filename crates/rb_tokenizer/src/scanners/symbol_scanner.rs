@@ -1,35 +1,148 @@
 use super::scanner::Scanner;
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenKind};
 use crate::tokens::TokenizationError;
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
 
-pub struct SymbolScanner {
+/// How the letter case of the input has to match the symbol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMatching {
+    /// The input must match the symbol exactly
+    #[default]
+    Exact,
+    /// ASCII letters match in either case, e.g. `SELECT` and `select`
+    AsciiInsensitive,
+    /// Letters match when their lowercase forms are equal, e.g. `ÉTÉ` and `été`
+    UnicodeInsensitive,
+}
+
+/// Options of a `SymbolScanner` beyond exact matching
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SymbolOptions {
+    pub case: CaseMatching,
+    /// Requires a symbol ending in a word character (alphanumeric or `_`) not
+    /// to be followed by one, so `in` does not match the start of `index`
+    pub word_boundary: bool,
+    /// Compares the symbol and the input in Unicode Normalization Form C, so
+    /// a precomposed `é` matches `e` followed by a combining acute accent
+    pub normalize: bool,
+}
+
+impl SymbolOptions {
+    /// Options for keywords: ASCII case-insensitive with a word boundary
+    pub fn keyword() -> Self {
+        Self {
+            case: CaseMatching::AsciiInsensitive,
+            word_boundary: true,
+            normalize: false,
+        }
+    }
+
+    /// Returns whether these are the default options, which match exactly
+    pub fn is_exact(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone)]
+pub struct SymbolScanner<K = &'static str> {
     pub symbol: String,
-    pub token_type: &'static str,
-    pub token_sub_type: Option<&'static str>,
+    pub token_type: K,
+    pub token_sub_type: Option<K>,
+    pub options: SymbolOptions,
 }
 
-impl SymbolScanner {
-    pub fn new(symbol: &str, token_type: &'static str, token_sub_type: Option<&'static str>) -> Self {
+impl<K: TokenKind> SymbolScanner<K> {
+    pub fn new(symbol: &str, token_type: K, token_sub_type: Option<K>) -> Self {
+        Self::with_options(symbol, token_type, token_sub_type, SymbolOptions::default())
+    }
+
+    pub fn with_options(symbol: &str, token_type: K, token_sub_type: Option<K>, options: SymbolOptions) -> Self {
         Self {
             symbol: symbol.to_string(),
             token_type,
             token_sub_type,
+            options,
         }
     }
-}
 
-impl Scanner for SymbolScanner {
-    fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
-        if input.starts_with(&self.symbol) {
-            Ok(Some(Token {
-                line: 0,
-                column: 0,
-                value: self.symbol.clone(),
-                token_type: self.token_type,
-                token_sub_type: self.token_sub_type,
-            }))
+    /// Returns the length in bytes of the symbol at the start of `input`, as written in the input
+    pub fn match_len(&self, input: &str) -> Option<usize> {
+        let len = if self.options.case == CaseMatching::Exact && !self.options.normalize {
+            input.starts_with(&self.symbol).then_some(self.symbol.len())?
         } else {
-            Ok(None)
+            self.folded_match_len(input)?
+        };
+
+        let ends_word = self.symbol.chars().next_back().is_some_and(is_word_char);
+        if self.options.word_boundary && ends_word && input[len..].chars().next().is_some_and(is_word_char) {
+            return None;
+        }
+        Some(len)
+    }
+
+    /// Matches the input one character, or with `normalize` one character
+    /// and the combining marks after it, at a time until its folded form
+    /// equals the folded symbol
+    fn folded_match_len(&self, input: &str) -> Option<usize> {
+        let symbol = self.fold(&self.symbol);
+        let mut end = 0;
+        while let Some(next) = self.next_unit_end(input, end) {
+            let folded = self.fold(&input[..next]);
+            if folded == symbol {
+                // A character after the match that composes with it would change the last character
+                let composes = self.options.normalize
+                    && self
+                        .next_unit_end(input, next)
+                        .is_some_and(|after| !self.fold(&input[..after]).starts_with(symbol.as_ref()));
+                return (!composes).then_some(next);
+            }
+            if !symbol.starts_with(folded.as_ref()) {
+                return None;
+            }
+            end = next;
+        }
+        None
+    }
+
+    /// The end of the character, or with `normalize` the character and its combining marks, at `start`
+    fn next_unit_end(&self, input: &str, start: usize) -> Option<usize> {
+        let mut chars = input[start..].char_indices();
+        let (_, first) = chars.next()?;
+        let mut end = start + first.len_utf8();
+        if self.options.normalize {
+            for (offset, ch) in chars {
+                if unicode_normalization::char::canonical_combining_class(ch) == 0 {
+                    break;
+                }
+                end = start + offset + ch.len_utf8();
+            }
+        }
+        Some(end)
+    }
+
+    /// The form of `text` compared with the options
+    fn fold<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let text: Cow<str> = if self.options.normalize { Cow::Owned(text.nfc().collect()) } else { Cow::Borrowed(text) };
+        match self.options.case {
+            CaseMatching::Exact => text,
+            CaseMatching::AsciiInsensitive => Cow::Owned(text.to_ascii_lowercase()),
+            // Character by character, so a prefix folds like the whole text
+            CaseMatching::UnicodeInsensitive => Cow::Owned(text.chars().flat_map(char::to_lowercase).collect()),
+        }
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+impl<K: TokenKind> Scanner<K> for SymbolScanner<K> {
+    fn scan(&self, input: &str) -> Result<Option<Token<K>>, TokenizationError> {
+        match self.match_len(input) {
+            // The value is the text as written, which differs from the symbol when matched loosely
+            Some(len) => Ok(Some(Token::new(self.token_type, self.token_sub_type, input[..len].to_string(), 0, 0))),
+            None => Ok(None),
         }
     }
 }
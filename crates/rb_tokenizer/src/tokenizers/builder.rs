@@ -0,0 +1,750 @@
+use super::is_anchored;
+use super::position::{ColumnMode, LineEndings};
+use super::tokenizer::{Tokenizer, TokenizerConfig};
+use crate::scanners::block_scanner::{EscapeRule, EscapeTransform};
+use crate::scanners::regex_scanner::{CaptureGroup, LiteralFn};
+use crate::scanners::scanner::AcceptStrategy;
+use crate::scanners::{BlockScanner, CaseMatching, EolScanner, RegexScanner, ScannerType, SymbolOptions, SymbolScanner};
+use crate::tokens::{TokenKind, TokenValue};
+use regex::Regex;
+use std::sync::Arc;
+use std::{error::Error, fmt};
+
+/// A problem with one of the scanners given to a `TokenizerBuilder`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    /// Position of the scanner in the order it was added
+    pub scanner: usize,
+    pub message: String,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scanner {}: {}", self.scanner, self.message)
+    }
+}
+
+impl Error for BuildError {}
+
+/// Builds a `Tokenizer` one named option at a time.
+///
+/// ```ignore
+/// let tokenizer = TokenizerBuilder::new()
+///     .continue_on_error(true)
+///     .block("/*", "*/").nesting().raw().keep_delimiters().kind("Comment")
+///     .block("\"", "\"").escape('\\').escape_mapping("n", '\n').transform_escapes().kind("String")
+///     .eol("//").sub_kind("Line").kind("Comment")
+///     .symbol("+").sub_kind("Plus").kind("Operator")
+///     .regex(r"^\d+").kind("Number")
+///     .build()?;
+/// ```
+///
+/// Each scanner builder ends with `kind`, which adds the scanner and returns
+/// to the tokenizer builder. Scanners are tried by `priority` (default 0,
+/// lower first) and then in the order they were added; `name` makes a
+/// scanner manageable on the built tokenizer. `build` reports every invalid
+/// or unreachable scanner at once, then `optimize`s the tokenizer.
+pub struct TokenizerBuilder<K: TokenKind = &'static str> {
+    config: TokenizerConfig,
+    scanners: Vec<(Spec<K>, K, Placement)>,
+}
+
+/// The name and priority a scanner is registered with
+#[derive(Default)]
+struct Placement {
+    name: Option<String>,
+    priority: i64,
+}
+
+/// A scanner as described to the builder, validated by `build`
+enum Spec<K> {
+    Symbol(SymbolSpec<K>),
+    Regex(RegexSpec<K>),
+    Block(BlockSpec<K>),
+    Eol(EolSpec<K>),
+}
+
+impl Default for TokenizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenizerBuilder {
+    pub fn new() -> Self {
+        Self::new_typed()
+    }
+}
+
+impl<K: TokenKind> TokenizerBuilder<K> {
+    /// Creates a builder for a user-defined token kind
+    pub fn new_typed() -> Self {
+        Self {
+            config: TokenizerConfig::default(),
+            scanners: Vec::new(),
+        }
+    }
+
+    /// Replaces the whole configuration
+    pub fn config(mut self, config: TokenizerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn tokenize_whitespace(mut self, value: bool) -> Self {
+        self.config.tokenize_whitespace = value;
+        self
+    }
+
+    pub fn continue_on_error(mut self, value: bool) -> Self {
+        self.config.continue_on_error = value;
+        self
+    }
+
+    pub fn error_tolerance_limit(mut self, value: usize) -> Self {
+        self.config.error_tolerance_limit = value;
+        self
+    }
+
+    pub fn track_token_positions(mut self, value: bool) -> Self {
+        self.config.track_token_positions = value;
+        self
+    }
+
+    pub fn column_mode(mut self, mode: ColumnMode) -> Self {
+        self.config.column_mode = mode;
+        self
+    }
+
+    /// Sets which sequences end a line, for position tracking and every EOL scanner
+    pub fn line_endings(mut self, line_endings: LineEndings) -> Self {
+        self.config.line_endings = line_endings;
+        self
+    }
+
+    pub fn report_line_ending_style(mut self, value: bool) -> Self {
+        self.config.report_line_ending_style = value;
+        self
+    }
+
+    /// Starts a scanner matching `symbol` exactly
+    pub fn symbol(self, symbol: &str) -> SymbolBuilder<K> {
+        SymbolBuilder {
+            parent: self,
+            placement: Placement::default(),
+            spec: SymbolSpec {
+                symbol: symbol.to_string(),
+                options: SymbolOptions::default(),
+                token_sub_type: None,
+            },
+        }
+    }
+
+    /// Starts a scanner matching an anchored regex
+    pub fn regex(self, pattern: &str) -> RegexBuilder<K> {
+        RegexBuilder {
+            parent: self,
+            placement: Placement::default(),
+            spec: RegexSpec {
+                pattern: pattern.to_string(),
+                accept_strategy: None,
+                literal: None,
+                value_group: None,
+                sub_type_groups: Vec::new(),
+                capture_metadata: false,
+                token_sub_type: None,
+            },
+        }
+    }
+
+    /// Starts a scanner for blocks between `start` and `end`. Delimiters are
+    /// left out of the token value unless `keep_delimiters` is called.
+    pub fn block(self, start: &str, end: &str) -> BlockBuilder<K> {
+        BlockBuilder {
+            parent: self,
+            placement: Placement::default(),
+            spec: BlockSpec {
+                start: start.to_string(),
+                end: end.to_string(),
+                pairs: Vec::new(),
+                nesting: false,
+                raw: false,
+                keep_delimiters: false,
+                escapes: Vec::new(),
+                escape_map: Vec::new(),
+                default_backslash_escape: true,
+                transform_escapes: false,
+                html_entities: false,
+                reject_unknown_entities: false,
+                typed_value: false,
+                allow_unterminated: false,
+                token_sub_type: None,
+            },
+        }
+    }
+
+    /// Starts a scanner for the rest of the line after `delimiter`. The
+    /// delimiter is left out of the token value unless `keep_delimiter` is called.
+    pub fn eol(self, delimiter: &str) -> EolBuilder<K> {
+        EolBuilder {
+            parent: self,
+            placement: Placement::default(),
+            spec: EolSpec {
+                delimiter: delimiter.to_string(),
+                keep_delimiter: false,
+                continuation: None,
+                exclude_terminator: false,
+                terminators: Vec::new(),
+                token_sub_type: None,
+            },
+        }
+    }
+
+    /// Validates every scanner and returns the optimized tokenizer, or all
+    /// the problems found
+    pub fn build(self) -> Result<Tokenizer<K>, Vec<BuildError>> {
+        let mut errors = Vec::new();
+        let mut tokenizer = Tokenizer::with_typed_config(self.config);
+        let mut symbols: Vec<(usize, SymbolScanner<K>)> = Vec::new();
+
+        // Validate in the order the scanners will be tried, so unreachable symbols are found
+        let mut scanners: Vec<_> = self.scanners.into_iter().enumerate().collect();
+        scanners.sort_by_key(|(_, (_, _, placement))| placement.priority);
+
+        for (index, (spec, token_type, placement)) in scanners {
+            let scanner = match spec {
+                Spec::Symbol(symbol) => symbol.scanner(token_type, index, &mut symbols).map(ScannerType::Symbol),
+                Spec::Regex(regex) => regex.scanner(token_type).map(ScannerType::Regex),
+                Spec::Block(block) => block.scanner(token_type).map(ScannerType::Block),
+                Spec::Eol(eol) => eol.scanner(token_type).map(ScannerType::Eol),
+            };
+            let registered = scanner.and_then(|scanner| {
+                tokenizer
+                    .register_scanner(placement.name.as_deref(), placement.priority, scanner)
+                    .map_err(|e| e.to_string())
+            });
+            if let Err(message) = registered {
+                errors.push(BuildError { scanner: index, message });
+            }
+        }
+
+        if !errors.is_empty() {
+            errors.sort_by_key(|error| error.scanner);
+            return Err(errors);
+        }
+        tokenizer.optimize();
+        Ok(tokenizer)
+    }
+}
+
+struct SymbolSpec<K> {
+    symbol: String,
+    options: SymbolOptions,
+    token_sub_type: Option<K>,
+}
+
+impl<K: TokenKind> SymbolSpec<K> {
+    /// `symbols` holds the earlier symbol scanners and their positions
+    fn scanner(
+        self,
+        token_type: K,
+        index: usize,
+        symbols: &mut Vec<(usize, SymbolScanner<K>)>,
+    ) -> Result<SymbolScanner<K>, String> {
+        if self.symbol.is_empty() {
+            return Err("symbol must not be empty".to_string());
+        }
+        // The first match wins, so a symbol an earlier one matches the start of never matches
+        if let Some((earlier, scanner)) = symbols.iter().find(|(_, scanner)| scanner.match_len(&self.symbol).is_some()) {
+            return Err(format!(
+                "symbol '{}' is unreachable: scanner {} matches '{}' first",
+                self.symbol, earlier, scanner.symbol
+            ));
+        }
+        let scanner = SymbolScanner::with_options(&self.symbol, token_type, self.token_sub_type, self.options);
+        symbols.push((index, scanner.clone()));
+        Ok(scanner)
+    }
+}
+
+/// A symbol scanner being configured; finish it with `kind`
+#[must_use = "a scanner is only added once `kind` is called"]
+pub struct SymbolBuilder<K: TokenKind> {
+    parent: TokenizerBuilder<K>,
+    placement: Placement,
+    spec: SymbolSpec<K>,
+}
+
+impl<K: TokenKind> SymbolBuilder<K> {
+    /// Names the scanner so it can be managed on the built tokenizer
+    pub fn name(mut self, name: &str) -> Self {
+        self.placement.name = Some(name.to_string());
+        self
+    }
+
+    /// Tries the scanner before scanners with a higher priority (default 0)
+    pub fn priority(mut self, priority: i64) -> Self {
+        self.placement.priority = priority;
+        self
+    }
+
+    pub fn sub_kind(mut self, token_sub_type: K) -> Self {
+        self.spec.token_sub_type = Some(token_sub_type);
+        self
+    }
+
+    /// Matches ASCII letters in either case, e.g. `SELECT` and `select`
+    pub fn ignore_ascii_case(mut self) -> Self {
+        self.spec.options.case = CaseMatching::AsciiInsensitive;
+        self
+    }
+
+    /// Matches letters whose lowercase forms are equal, e.g. `ÉTÉ` and `été`
+    pub fn ignore_case(mut self) -> Self {
+        self.spec.options.case = CaseMatching::UnicodeInsensitive;
+        self
+    }
+
+    /// Only matches a symbol ending in a word character when no word character follows it
+    pub fn word_boundary(mut self) -> Self {
+        self.spec.options.word_boundary = true;
+        self
+    }
+
+    /// Compares the symbol and the input in Unicode Normalization Form C
+    pub fn normalize(mut self) -> Self {
+        self.spec.options.normalize = true;
+        self
+    }
+
+    /// Sets the token type and adds the scanner
+    pub fn kind(mut self, token_type: K) -> TokenizerBuilder<K> {
+        self.parent.scanners.push((Spec::Symbol(self.spec), token_type, self.placement));
+        self.parent
+    }
+}
+
+struct RegexSpec<K> {
+    pattern: String,
+    accept_strategy: Option<AcceptStrategy>,
+    literal: Option<LiteralFn>,
+    value_group: Option<CaptureGroup>,
+    sub_type_groups: Vec<(String, K)>,
+    capture_metadata: bool,
+    token_sub_type: Option<K>,
+}
+
+impl<K: TokenKind> RegexSpec<K> {
+    fn scanner(self, token_type: K) -> Result<RegexScanner<K>, String> {
+        let regex = Regex::new(&self.pattern).map_err(|e| format!("invalid regex '{}': {}", self.pattern, e))?;
+        if !is_anchored(&self.pattern) {
+            return Err(format!("regex '{}' must be anchored with '^'", self.pattern));
+        }
+        if regex.is_match("") {
+            return Err(format!(
+                "regex '{}' can match an empty string, which would never advance the tokenizer",
+                self.pattern
+            ));
+        }
+
+        let scanner = RegexScanner {
+            pattern: regex,
+            token_type,
+            token_sub_type: self.token_sub_type,
+            accept_strategy: self.accept_strategy,
+            literal: self.literal,
+            value_group: self.value_group,
+            sub_type_groups: self.sub_type_groups,
+            capture_metadata: self.capture_metadata,
+        };
+        scanner.check_groups()?;
+        Ok(scanner)
+    }
+}
+
+/// A regex scanner being configured; finish it with `kind`
+#[must_use = "a scanner is only added once `kind` is called"]
+pub struct RegexBuilder<K: TokenKind> {
+    parent: TokenizerBuilder<K>,
+    placement: Placement,
+    spec: RegexSpec<K>,
+}
+
+impl<K: TokenKind> RegexBuilder<K> {
+    /// Names the scanner so it can be managed on the built tokenizer
+    pub fn name(mut self, name: &str) -> Self {
+        self.placement.name = Some(name.to_string());
+        self
+    }
+
+    /// Tries the scanner before scanners with a higher priority (default 0)
+    pub fn priority(mut self, priority: i64) -> Self {
+        self.placement.priority = priority;
+        self
+    }
+
+    pub fn sub_kind(mut self, token_sub_type: K) -> Self {
+        self.spec.token_sub_type = Some(token_sub_type);
+        self
+    }
+
+    /// Only tries the regex when the input starts with one of `chars`
+    pub fn accept_start_chars(mut self, chars: &'static str) -> Self {
+        self.spec.accept_strategy = Some(AcceptStrategy::StartChars(chars));
+        self
+    }
+
+    /// Only tries the regex when the input starts with `prefix`
+    pub fn accept_prefix(mut self, prefix: &'static str) -> Self {
+        self.spec.accept_strategy = Some(AcceptStrategy::Pattern(prefix));
+        self
+    }
+
+    /// Fills the literal of every token with `convert` applied to the token value
+    pub fn literal(mut self, convert: impl Fn(&str) -> Option<TokenValue> + Send + Sync + 'static) -> Self {
+        self.spec.literal = Some(Arc::new(convert));
+        self
+    }
+
+    /// Uses the text of a capture group, by number or name, as the token value
+    pub fn value_group(mut self, group: impl Into<CaptureGroup>) -> Self {
+        self.spec.value_group = Some(group.into());
+        self
+    }
+
+    /// Gives tokens `sub_type` when the named group `name` takes part in the match
+    pub fn sub_kind_group(mut self, name: &str, sub_type: K) -> Self {
+        self.spec.sub_type_groups.push((name.to_string(), sub_type));
+        self
+    }
+
+    /// Keeps the text of the other capture groups in `Token::captures`
+    pub fn capture_metadata(mut self) -> Self {
+        self.spec.capture_metadata = true;
+        self
+    }
+
+    /// Sets the token type and adds the scanner
+    pub fn kind(mut self, token_type: K) -> TokenizerBuilder<K> {
+        self.parent.scanners.push((Spec::Regex(self.spec), token_type, self.placement));
+        self.parent
+    }
+}
+
+/// An escape rule as described to a `BlockBuilder`
+enum EscapeSpec {
+    Rule(EscapeRule),
+    /// Compiled by `build`, so an invalid pattern is reported with the other problems
+    Pattern(String),
+    /// The transform of the escape rule before it
+    Transform(EscapeTransform),
+}
+
+struct BlockSpec<K> {
+    start: String,
+    end: String,
+    pairs: Vec<(String, String)>,
+    nesting: bool,
+    raw: bool,
+    keep_delimiters: bool,
+    escapes: Vec<EscapeSpec>,
+    escape_map: Vec<(String, String)>,
+    default_backslash_escape: bool,
+    transform_escapes: bool,
+    html_entities: bool,
+    reject_unknown_entities: bool,
+    typed_value: bool,
+    allow_unterminated: bool,
+    token_sub_type: Option<K>,
+}
+
+impl<K: TokenKind> BlockSpec<K> {
+    fn scanner(self, token_type: K) -> Result<BlockScanner<K>, String> {
+        let mut delimiters = self.pairs.iter().flat_map(|(start, end)| [start, end]);
+        if self.start.is_empty() || self.end.is_empty() || delimiters.any(String::is_empty) {
+            return Err("block delimiters must not be empty".to_string());
+        }
+        if self.escapes.iter().any(|escape| matches!(escape, EscapeSpec::Rule(EscapeRule::Doubled { sequence }) if sequence.is_empty())) {
+            return Err("doubled escape sequences must not be empty".to_string());
+        }
+        if self.raw && (!self.escapes.is_empty() || self.transform_escapes) {
+            return Err(format!("raw block '{}' cannot have escape rules", self.start));
+        }
+
+        let mut scanner = BlockScanner::new(
+            &self.start,
+            &self.end,
+            token_type,
+            self.token_sub_type,
+            self.nesting,
+            self.raw,
+            self.keep_delimiters,
+        );
+        for (start, end) in &self.pairs {
+            scanner.add_delimiter_pair(start, end);
+        }
+        for escape in self.escapes {
+            match escape {
+                EscapeSpec::Rule(rule) => scanner.add_escape_rule(rule),
+                EscapeSpec::Pattern(pattern) => scanner
+                    .add_pattern_escape(&pattern)
+                    .map_err(|e| format!("invalid escape pattern '{}': {}", pattern, e))?,
+                EscapeSpec::Transform(transform) => {
+                    let Some(index) = scanner.escape_rules().len().checked_sub(1) else {
+                        return Err("an escape transform must follow an escape rule".to_string());
+                    };
+                    scanner.set_escape_transform(index, transform);
+                }
+            }
+        }
+        for (sequence, replacement) in self.escape_map {
+            scanner.add_escape_mapping(&sequence, replacement);
+        }
+        scanner.set_default_backslash_escape(self.default_backslash_escape);
+        scanner.set_transform_escapes(self.transform_escapes);
+        scanner.set_html_entities(self.html_entities);
+        scanner.set_reject_unknown_entities(self.reject_unknown_entities);
+        scanner.set_typed_values(self.typed_value);
+        scanner.set_allow_unterminated(self.allow_unterminated);
+        Ok(scanner)
+    }
+}
+
+/// A block scanner being configured; finish it with `kind`
+#[must_use = "a scanner is only added once `kind` is called"]
+pub struct BlockBuilder<K: TokenKind> {
+    parent: TokenizerBuilder<K>,
+    placement: Placement,
+    spec: BlockSpec<K>,
+}
+
+impl<K: TokenKind> BlockBuilder<K> {
+    /// Names the scanner so it can be managed on the built tokenizer
+    pub fn name(mut self, name: &str) -> Self {
+        self.placement.name = Some(name.to_string());
+        self
+    }
+
+    /// Tries the scanner before scanners with a higher priority (default 0)
+    pub fn priority(mut self, priority: i64) -> Self {
+        self.placement.priority = priority;
+        self
+    }
+
+    /// Adds another pair of delimiters, e.g. `[` and `]` next to `(` and `)`
+    pub fn pair(mut self, start: &str, end: &str) -> Self {
+        self.spec.pairs.push((start.to_string(), end.to_string()));
+        self
+    }
+
+    /// Allows blocks to nest, e.g. `/* outer /* inner */ still outer */`
+    pub fn nesting(mut self) -> Self {
+        self.spec.nesting = true;
+        self
+    }
+
+    /// Turns off escape sequence handling inside the block
+    pub fn raw(mut self) -> Self {
+        self.spec.raw = true;
+        self
+    }
+
+    /// Includes the start and end delimiters in the token value
+    pub fn keep_delimiters(mut self) -> Self {
+        self.spec.keep_delimiters = true;
+        self
+    }
+
+    /// Adds a single-character escape such as `\`
+    pub fn escape(mut self, escape_char: char) -> Self {
+        self.spec.escapes.push(EscapeSpec::Rule(EscapeRule::Simple { escape_char }));
+        self
+    }
+
+    /// Adds a named escape such as `&amp;`, at most `max_length` bytes long
+    pub fn named_escape(mut self, start_char: char, end_char: char, max_length: usize) -> Self {
+        self.spec.escapes.push(EscapeSpec::Rule(EscapeRule::Named {
+            start_char,
+            end_char,
+            max_length,
+        }));
+        self
+    }
+
+    /// Adds an escape matched by a regex
+    pub fn pattern_escape(mut self, pattern: &str) -> Self {
+        self.spec.escapes.push(EscapeSpec::Pattern(pattern.to_string()));
+        self
+    }
+
+    /// Adds an escape of `sequence` written twice, such as `''`
+    pub fn doubled_escape(mut self, sequence: &str) -> Self {
+        self.spec.escapes.push(EscapeSpec::Rule(EscapeRule::Doubled {
+            sequence: sequence.to_string(),
+        }));
+        self
+    }
+
+    /// Adds an escape of the end delimiter written twice, as in SQL strings or CSV fields
+    pub fn doubled_delimiter_escape(self) -> Self {
+        let end = self.spec.end.clone();
+        self.doubled_escape(&end)
+    }
+
+    /// Stops a backslash from escaping the next character when no escape rule matches
+    pub fn no_default_backslash_escape(mut self) -> Self {
+        self.spec.default_backslash_escape = false;
+        self
+    }
+
+    /// Adds a balanced escape such as `${...}`
+    pub fn balanced_escape(mut self, start_seq: &str, end_seq: &str, allow_nesting: bool) -> Self {
+        self.spec.escapes.push(EscapeSpec::Rule(EscapeRule::Balanced {
+            start_seq: start_seq.to_string(),
+            end_seq: end_seq.to_string(),
+            allow_nesting,
+        }));
+        self
+    }
+
+    /// Writes the sequences of the escape added last to the value with `transform`,
+    /// e.g. `EscapeTransform::HexCodePoint` after `.pattern_escape(r"^\\u\{[0-9a-fA-F]+\}")`
+    pub fn escape_transform(mut self, transform: EscapeTransform) -> Self {
+        self.spec.escapes.push(EscapeSpec::Transform(transform));
+        self
+    }
+
+    /// Maps an escape sequence to the character or text it stands for
+    pub fn escape_mapping(mut self, sequence: &str, replacement: impl Into<String>) -> Self {
+        self.spec.escape_map.push((sequence.to_string(), replacement.into()));
+        self
+    }
+
+    /// Resolves unmapped named escapes as HTML5 character references, e.g. `&hellip;` or `&#x1F;`
+    pub fn html_entities(mut self) -> Self {
+        self.spec.html_entities = true;
+        self
+    }
+
+    /// Reports named escapes that resolve to nothing as errors
+    pub fn reject_unknown_entities(mut self) -> Self {
+        self.spec.reject_unknown_entities = true;
+        self
+    }
+
+    /// Replaces escape sequences in the token value using the mappings
+    pub fn transform_escapes(mut self) -> Self {
+        self.spec.transform_escapes = true;
+        self
+    }
+
+    /// Gives tokens their content, with escapes transformed, as a string literal
+    pub fn typed_value(mut self) -> Self {
+        self.spec.typed_value = true;
+        self
+    }
+
+    /// Scans a block missing its end delimiter to the end of the input instead of failing
+    pub fn allow_unterminated(mut self) -> Self {
+        self.spec.allow_unterminated = true;
+        self
+    }
+
+    pub fn sub_kind(mut self, token_sub_type: K) -> Self {
+        self.spec.token_sub_type = Some(token_sub_type);
+        self
+    }
+
+    /// Sets the token type and adds the scanner
+    pub fn kind(mut self, token_type: K) -> TokenizerBuilder<K> {
+        self.parent.scanners.push((Spec::Block(self.spec), token_type, self.placement));
+        self.parent
+    }
+}
+
+struct EolSpec<K> {
+    delimiter: String,
+    keep_delimiter: bool,
+    continuation: Option<String>,
+    exclude_terminator: bool,
+    terminators: Vec<String>,
+    token_sub_type: Option<K>,
+}
+
+impl<K: TokenKind> EolSpec<K> {
+    fn scanner(self, token_type: K) -> Result<EolScanner<K>, String> {
+        if self.delimiter.is_empty() {
+            return Err("EOL delimiter must not be empty".to_string());
+        }
+        if self.continuation.as_deref() == Some("") {
+            return Err("line continuation must not be empty".to_string());
+        }
+        if self.terminators.iter().any(String::is_empty) {
+            return Err("EOL terminators must not be empty".to_string());
+        }
+        let mut scanner = EolScanner::new(&self.delimiter, token_type, self.token_sub_type, self.keep_delimiter);
+        scanner.set_line_continuation(self.continuation.as_deref());
+        scanner.set_include_terminator(!self.exclude_terminator);
+        for terminator in &self.terminators {
+            scanner.add_terminator(terminator);
+        }
+        Ok(scanner)
+    }
+}
+
+/// An EOL scanner being configured; finish it with `kind`
+#[must_use = "a scanner is only added once `kind` is called"]
+pub struct EolBuilder<K: TokenKind> {
+    parent: TokenizerBuilder<K>,
+    placement: Placement,
+    spec: EolSpec<K>,
+}
+
+impl<K: TokenKind> EolBuilder<K> {
+    /// Names the scanner so it can be managed on the built tokenizer
+    pub fn name(mut self, name: &str) -> Self {
+        self.placement.name = Some(name.to_string());
+        self
+    }
+
+    /// Tries the scanner before scanners with a higher priority (default 0)
+    pub fn priority(mut self, priority: i64) -> Self {
+        self.placement.priority = priority;
+        self
+    }
+
+    /// Includes the delimiter in the token value
+    pub fn keep_delimiter(mut self) -> Self {
+        self.spec.keep_delimiter = true;
+        self
+    }
+
+    /// Continues the token onto the next line when a line ends with `continuation`
+    pub fn continuation(mut self, continuation: &str) -> Self {
+        self.spec.continuation = Some(continuation.to_string());
+        self
+    }
+
+    /// Leaves the line terminator out of the token value; it is still consumed
+    pub fn exclude_terminator(mut self) -> Self {
+        self.spec.exclude_terminator = true;
+        self
+    }
+
+    /// Ends the token before `terminator`, e.g. `%>` closing an EJS tag
+    pub fn terminator(mut self, terminator: &str) -> Self {
+        self.spec.terminators.push(terminator.to_string());
+        self
+    }
+
+    pub fn sub_kind(mut self, token_sub_type: K) -> Self {
+        self.spec.token_sub_type = Some(token_sub_type);
+        self
+    }
+
+    /// Sets the token type and adds the scanner
+    pub fn kind(mut self, token_type: K) -> TokenizerBuilder<K> {
+        self.parent.scanners.push((Spec::Eol(self.spec), token_type, self.placement));
+        self.parent
+    }
+}
@@ -0,0 +1,131 @@
+use super::scanner_list::ScannerEntry;
+use super::tokenizer::Tokenizer;
+use crate::scanners::ScannerType;
+use crate::tokens::TokenKind;
+use std::{error::Error, fmt};
+
+/// What `Tokenizer::merge` does with a scanner that conflicts with an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Keep the existing scanner and skip the incoming one
+    KeepExisting,
+    /// Remove the existing scanner and add the incoming one
+    Replace,
+    /// Merge nothing if there is any conflict
+    Fail,
+}
+
+/// Why a scanner of a merged tokenizer clashes with the tokenizer it is merged into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// Both tokenizers have a scanner with this name
+    Name(String),
+    /// Both tokenizers have a symbol, block or EOL scanner starting with
+    /// exactly this text, so only one of them can ever match it
+    Literal(String),
+    /// The tokenizers interned different token type names for the same ids,
+    /// so their interned kinds cannot be mixed
+    Registry,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeConflict::Name(name) => write!(f, "Both tokenizers have a scanner named '{}'", name),
+            MergeConflict::Literal(text) => write!(f, "Both tokenizers have a scanner for '{}'", text),
+            MergeConflict::Registry => write!(f, "The tokenizers' token type registries are incompatible"),
+        }
+    }
+}
+
+impl Error for MergeConflict {}
+
+/// Tokenizer composition.
+///
+/// A dialect is derived by cloning a base tokenizer, which shares its
+/// scanners, and then adding, replacing or removing scanners. `merge` adds
+/// the scanners of another tokenizer, e.g. a shared set of operators.
+impl<K: TokenKind> Tokenizer<K> {
+    /// Adds every scanner of `other`, keeping its name, priority and enabled
+    /// state. Returns the conflicts found and resolved, or every conflict
+    /// without changing anything when `resolution` is `Fail`.
+    ///
+    /// Interned tokenizers can only be merged when one registry extends the
+    /// other, e.g. when both were derived from the same base tokenizer;
+    /// otherwise `Registry` is reported whatever the resolution.
+    pub fn merge(
+        &mut self,
+        other: &Tokenizer<K>,
+        resolution: ConflictResolution,
+    ) -> Result<Vec<MergeConflict>, Vec<MergeConflict>> {
+        let ours: Vec<_> = self.registry.iter().map(|(_, name)| name).collect();
+        let theirs: Vec<_> = other.registry.iter().map(|(_, name)| name).collect();
+        if !ours.starts_with(&theirs) && !theirs.starts_with(&ours) {
+            return Err(vec![MergeConflict::Registry]);
+        }
+        let extended = theirs.len() > ours.len();
+
+        let mut conflicts = Vec::new();
+        let mut incoming = Vec::new();
+        for entry in &other.scanners {
+            let clashes: Vec<_> = self
+                .scanners
+                .iter()
+                .enumerate()
+                .filter_map(|(index, existing)| conflict(existing, entry).map(|conflict| (index, conflict)))
+                .collect();
+            conflicts.extend(clashes.iter().map(|(_, conflict)| conflict.clone()));
+            incoming.push((entry, clashes));
+        }
+
+        if resolution == ConflictResolution::Fail && !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
+        let mut replaced = Vec::new();
+        let mut added = Vec::new();
+        for (entry, clashes) in incoming {
+            if clashes.is_empty() || resolution == ConflictResolution::Replace {
+                replaced.extend(clashes.into_iter().map(|(index, _)| index));
+                added.push(entry.clone());
+            }
+        }
+        replaced.sort_unstable();
+        replaced.dedup();
+        for index in replaced.into_iter().rev() {
+            self.scanners.remove(index);
+        }
+        for entry in added {
+            self.insert_entry(entry);
+        }
+
+        if extended {
+            self.registry = other.registry.clone();
+        }
+        Ok(conflicts)
+    }
+}
+
+/// How `incoming` clashes with `existing`, if it does
+fn conflict<K: TokenKind>(existing: &ScannerEntry<K>, incoming: &ScannerEntry<K>) -> Option<MergeConflict> {
+    if let (Some(ours), Some(theirs)) = (&existing.name, &incoming.name) {
+        if ours == theirs {
+            return Some(MergeConflict::Name(theirs.clone()));
+        }
+    }
+    let ours = literals(&existing.scanner);
+    literals(&incoming.scanner)
+        .into_iter()
+        .find(|theirs| ours.contains(theirs))
+        .map(|theirs| MergeConflict::Literal(theirs.to_string()))
+}
+
+/// The exact texts a scanner's match can start with, for scanners that have them
+fn literals<K: TokenKind>(scanner: &ScannerType<K>) -> Vec<&str> {
+    match scanner {
+        ScannerType::Symbol(scanner) => vec![&scanner.symbol],
+        ScannerType::Block(scanner) => scanner.delimiter_pairs().iter().map(|(start, _)| start.as_str()).collect(),
+        ScannerType::Eol(scanner) => vec![&scanner.delimiter],
+        _ => Vec::new(),
+    }
+}
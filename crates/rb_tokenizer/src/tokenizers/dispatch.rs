@@ -0,0 +1,127 @@
+use crate::scanners::{CaseMatching, ScannerType};
+use crate::tokens::TokenKind;
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+
+/// The scanners that can match at a position, indexed by the position's first byte.
+///
+/// Every list keeps the tokenizer's scanner order, so trying only the
+/// candidates gives the same result as trying every scanner.
+#[derive(Clone)]
+pub(crate) struct Dispatch {
+    candidates: Vec<Vec<usize>>,
+}
+
+impl Dispatch {
+    /// Indexes `scanners`, given in priority order with their positions in the tokenizer
+    pub(crate) fn new<'s, K: TokenKind + 's>(scanners: impl Iterator<Item = (usize, &'s ScannerType<K>)>) -> Self {
+        let mut candidates = vec![Vec::new(); 256];
+        for (index, scanner) in scanners {
+            let bytes = first_bytes(scanner).unwrap_or([true; 256]);
+            for byte in 0..256 {
+                if bytes[byte] {
+                    candidates[byte].push(index);
+                }
+            }
+        }
+        Self { candidates }
+    }
+
+    /// Indices of the scanners that may match input starting with `byte`
+    pub(crate) fn candidates(&self, byte: u8) -> &[usize] {
+        &self.candidates[byte as usize]
+    }
+}
+
+/// The bytes a scanner's match can start with, or `None` if it cannot be known
+fn first_bytes<K: TokenKind>(scanner: &ScannerType<K>) -> Option<[bool; 256]> {
+    let literal = |text: &str| {
+        let first = *text.as_bytes().first()?;
+        let mut bytes = [false; 256];
+        bytes[first as usize] = true;
+        Some(bytes)
+    };
+
+    match scanner {
+        ScannerType::Symbol(scanner) => {
+            // A normalized or Unicode case-folded match can start with a different character
+            if scanner.options.normalize || scanner.options.case == CaseMatching::UnicodeInsensitive {
+                return None;
+            }
+            let mut bytes = literal(&scanner.symbol)?;
+            if scanner.options.case == CaseMatching::AsciiInsensitive {
+                let first = scanner.symbol.as_bytes()[0];
+                bytes[first.to_ascii_lowercase() as usize] = true;
+                bytes[first.to_ascii_uppercase() as usize] = true;
+            }
+            Some(bytes)
+        }
+        ScannerType::Block(scanner) => {
+            let mut bytes = [false; 256];
+            for (start, _) in scanner.delimiter_pairs() {
+                bytes[*start.as_bytes().first()? as usize] = true;
+            }
+            Some(bytes)
+        }
+        ScannerType::Eol(scanner) => literal(&scanner.delimiter),
+        ScannerType::Regex(scanner) => {
+            let hir = regex_syntax::parse(scanner.pattern.as_str()).ok()?;
+            // An unanchored pattern may match further into the input
+            if !hir.properties().look_set_prefix().contains(Look::Start) || nullable(&hir) {
+                return None;
+            }
+            let mut bytes = [false; 256];
+            collect_first_bytes(&hir, &mut bytes);
+            Some(bytes)
+        }
+        ScannerType::Closure(_) | ScannerType::Scanner(_) | ScannerType::Callback(_) | ScannerType::Context(_) => None,
+    }
+}
+
+/// Returns whether every match of `pattern` starts at the beginning of the
+/// input, e.g. `^a`, `\Aa` or `(?i)^true`
+pub(crate) fn is_anchored(pattern: &str) -> bool {
+    regex_syntax::parse(pattern).is_ok_and(|hir| hir.properties().look_set_prefix().contains(Look::Start))
+}
+
+fn nullable(hir: &Hir) -> bool {
+    hir.properties().minimum_len() == Some(0)
+}
+
+/// Adds the bytes a non-empty match of `hir` can start with
+fn collect_first_bytes(hir: &Hir, bytes: &mut [bool; 256]) {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Literal(literal) => {
+            if let Some(&first) = literal.0.first() {
+                bytes[first as usize] = true;
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            for range in class.ranges() {
+                bytes[range.start() as usize..=range.end() as usize].fill(true);
+            }
+        }
+        HirKind::Class(Class::Unicode(class)) => {
+            // UTF-8 lead bytes grow with the code point, so a range's lead bytes are contiguous
+            for range in class.ranges() {
+                let lead = |ch: char| ch.encode_utf8(&mut [0; 4]).as_bytes()[0] as usize;
+                bytes[lead(range.start())..=lead(range.end())].fill(true);
+            }
+        }
+        HirKind::Repetition(repetition) => collect_first_bytes(&repetition.sub, bytes),
+        HirKind::Capture(capture) => collect_first_bytes(&capture.sub, bytes),
+        HirKind::Concat(items) => {
+            for item in items {
+                collect_first_bytes(item, bytes);
+                if !nullable(item) {
+                    break;
+                }
+            }
+        }
+        HirKind::Alternation(alternatives) => {
+            for alternative in alternatives {
+                collect_first_bytes(alternative, bytes);
+            }
+        }
+    }
+}
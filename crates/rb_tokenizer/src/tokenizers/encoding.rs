@@ -0,0 +1,239 @@
+use super::tokenizer::{Collector, Cursor, Tokenizer};
+use crate::tokens::{Token, TokenKind, TokenizationError};
+use std::ops::Range;
+
+/// Tokens paired with the byte range of the original buffer each was scanned from
+pub type SourceTokens<K> = Vec<(Token<K>, Range<usize>)>;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Encoding of a byte buffer handed to `Tokenizer::tokenize_bytes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// A byte sequence that could not be decoded. It appears as U+FFFD in the decoded text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSequence {
+    /// Byte offset of the replacement character in the decoded text
+    pub offset: usize,
+    /// Byte offset of the sequence in the original buffer
+    pub original_offset: usize,
+    /// The offending bytes
+    pub bytes: Vec<u8>,
+}
+
+/// The result of decoding a byte buffer: the text to tokenize plus what is
+/// needed to map positions back onto the original bytes.
+#[derive(Debug, Clone)]
+pub struct DecodedInput {
+    pub text: String,
+    pub encoding: Encoding,
+    /// Length of the byte order mark that was stripped (0 if there was none)
+    pub bom_len: usize,
+    /// Undecodable sequences, in input order
+    pub invalid: Vec<InvalidSequence>,
+}
+
+impl DecodedInput {
+    /// Detects the encoding from a byte order mark (UTF-8 without a BOM
+    /// otherwise), strips the BOM and decodes the rest.
+    pub fn decode(bytes: &[u8]) -> Self {
+        if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+            Self::decode_utf8(rest, UTF8_BOM.len())
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+            Self::decode_utf16(rest, Encoding::Utf16Le)
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+            Self::decode_utf16(rest, Encoding::Utf16Be)
+        } else {
+            Self::decode_utf8(bytes, 0)
+        }
+    }
+
+    fn decode_utf8(mut bytes: &[u8], bom_len: usize) -> Self {
+        let mut text = String::with_capacity(bytes.len());
+        let mut invalid = Vec::new();
+        let mut original_offset = bom_len;
+
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let valid_up_to = e.valid_up_to();
+                    // `error_len` is None for a sequence truncated by the end of input
+                    let invalid_len = e.error_len().unwrap_or(bytes.len() - valid_up_to);
+                    text.push_str(std::str::from_utf8(&bytes[..valid_up_to]).unwrap());
+                    invalid.push(InvalidSequence {
+                        offset: text.len(),
+                        original_offset: original_offset + valid_up_to,
+                        bytes: bytes[valid_up_to..valid_up_to + invalid_len].to_vec(),
+                    });
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    original_offset += valid_up_to + invalid_len;
+                    bytes = &bytes[valid_up_to + invalid_len..];
+                }
+            }
+        }
+
+        Self {
+            text,
+            encoding: Encoding::Utf8,
+            bom_len,
+            invalid,
+        }
+    }
+
+    fn decode_utf16(bytes: &[u8], encoding: Encoding) -> Self {
+        let bom_len = 2;
+        let units = bytes.chunks_exact(2).map(|pair| match encoding {
+            Encoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+            _ => u16::from_le_bytes([pair[0], pair[1]]),
+        });
+
+        let mut text = String::with_capacity(bytes.len() / 2);
+        let mut invalid = Vec::new();
+        let mut original_offset = bom_len;
+
+        for decoded in char::decode_utf16(units) {
+            match decoded {
+                Ok(ch) => {
+                    text.push(ch);
+                    original_offset += ch.len_utf16() * 2;
+                }
+                Err(e) => {
+                    // An unpaired surrogate
+                    let unit = e.unpaired_surrogate();
+                    invalid.push(InvalidSequence {
+                        offset: text.len(),
+                        original_offset,
+                        bytes: match encoding {
+                            Encoding::Utf16Be => unit.to_be_bytes().to_vec(),
+                            _ => unit.to_le_bytes().to_vec(),
+                        },
+                    });
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    original_offset += 2;
+                }
+            }
+        }
+
+        // A dangling odd byte cannot form a code unit
+        if bytes.len() % 2 == 1 {
+            invalid.push(InvalidSequence {
+                offset: text.len(),
+                original_offset,
+                bytes: vec![bytes[bytes.len() - 1]],
+            });
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+
+        Self {
+            text,
+            encoding,
+            bom_len,
+            invalid,
+        }
+    }
+
+    /// Maps a byte offset in the decoded text back onto the original buffer
+    pub fn original_offset(&self, offset: usize) -> usize {
+        let invalid_before = self.invalid.partition_point(|sequence| sequence.offset < offset);
+        if let Some(sequence) = self.invalid[..invalid_before].last() {
+            // Resume counting from the last undecodable sequence before `offset`
+            let replacement_end = sequence.offset + char::REPLACEMENT_CHARACTER.len_utf8();
+            let resume = sequence.original_offset + sequence.bytes.len();
+            return resume + self.encoded_len(&self.text[replacement_end..offset]);
+        }
+        self.bom_len + self.encoded_len(&self.text[..offset])
+    }
+
+    /// Maps a byte range of the decoded text back onto the original buffer
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_offset(range.start)..self.original_offset(range.end)
+    }
+
+    /// Length of valid decoded text in the original encoding
+    fn encoded_len(&self, text: &str) -> usize {
+        match self.encoding {
+            Encoding::Utf8 => text.len(),
+            Encoding::Utf16Le | Encoding::Utf16Be => text.encode_utf16().count() * 2,
+        }
+    }
+
+    pub(crate) fn describe(&self, sequence: &InvalidSequence) -> String {
+        format!(
+            "Invalid {} sequence {:02X?} at byte {}",
+            self.encoding.name(),
+            sequence.bytes,
+            sequence.original_offset
+        )
+    }
+}
+
+impl<K: TokenKind> Tokenizer<K> {
+    /// Tokenizes raw bytes, e.g. a file read from disk.
+    ///
+    /// A UTF-8 or UTF-16 byte order mark is detected and stripped (so it never
+    /// becomes a token), UTF-16LE/BE input is decoded, and bytes that are not
+    /// valid in the detected encoding are reported as `InvalidEncoding` errors
+    /// at their line/column and original byte offset instead of rejecting the
+    /// whole input. An undecodable sequence always ends the token before it.
+    pub fn tokenize_bytes(&self, bytes: &[u8]) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        let decoded = DecodedInput::decode(bytes);
+        let (collector, _) = self.scan_decoded(&decoded);
+        collector.finish(self)
+    }
+
+    /// Tokenizes input decoded with `DecodedInput::decode`, pairing every
+    /// token with the range of the original buffer it was scanned from.
+    ///
+    /// Errors are reported as by `tokenize_bytes`. The pipeline is not
+    /// applied, since the tokens its stages merge or split have no single
+    /// source range.
+    pub fn tokenize_decoded(
+        &self,
+        decoded: &DecodedInput,
+    ) -> Result<SourceTokens<K>, Vec<TokenizationError>> {
+        let (collector, ranges) = self.scan_decoded(decoded);
+        collector
+            .finish_unpiped(self)
+            .map(|tokens| tokens.into_iter().zip(ranges).collect())
+    }
+
+    /// Scans the decoded text, returning the collected steps and the original range of every token
+    fn scan_decoded(&self, decoded: &DecodedInput) -> (Collector<'_, K>, Vec<Range<usize>>) {
+        let mut cursor = Cursor::new(self, &decoded.text, 0, 1, 1).with_invalid(decoded);
+        let mut collector = Collector::new(self.config());
+        let mut ranges = Vec::new();
+
+        while let Some(step) = cursor.next_step() {
+            if step.token.is_some() {
+                ranges.push(decoded.original_range(step.start..cursor.position()));
+            }
+            if !collector.push(step) {
+                break;
+            }
+        }
+
+        self.record_line_ending_style(&decoded.text);
+        (collector, ranges)
+    }
+}
@@ -0,0 +1,99 @@
+use super::tokenizer::{Tokenizer, TokenizerConfig};
+use crate::tokens::{Token, TokenTypeId, TokenTypeRegistry};
+use crate::utils;
+use std::borrow::Cow;
+
+/// Tokenizers whose token types are named at runtime, e.g. by a grammar
+/// loaded from a config file.
+///
+/// The `add_named_*` methods intern type and sub-type names in the tokenizer's
+/// `TokenTypeRegistry`, so names need not be `&'static str`. Tokens carry
+/// compact `TokenTypeId`s that the registry resolves back to names.
+impl Tokenizer<TokenTypeId> {
+    pub fn interned() -> Self {
+        Self::new_typed()
+    }
+
+    pub fn interned_with_config(config: TokenizerConfig) -> Self {
+        Self::with_typed_config(config)
+    }
+
+    pub fn registry(&self) -> &TokenTypeRegistry {
+        &self.registry
+    }
+
+    /// Returns the id for `name`, registering it if it is new
+    pub fn intern(&mut self, name: &str) -> TokenTypeId {
+        self.registry.intern(name)
+    }
+
+    /// Returns the name of a token type or sub-type id
+    pub fn type_name(&self, id: TokenTypeId) -> Cow<'_, str> {
+        self.registry.resolve(id)
+    }
+
+    fn intern_pair(&mut self, token_type: &str, token_sub_type: Option<&str>) -> (TokenTypeId, Option<TokenTypeId>) {
+        let token_type = self.registry.intern(token_type);
+        let token_sub_type = token_sub_type.map(|name| self.registry.intern(name));
+        (token_type, token_sub_type)
+    }
+
+    pub fn add_named_regex_scanner(&mut self, pattern: &str, token_type: &str, token_sub_type: Option<&str>) {
+        let (token_type, token_sub_type) = self.intern_pair(token_type, token_sub_type);
+        self.add_regex_scanner(pattern, token_type, token_sub_type);
+    }
+
+    pub fn add_named_symbol_scanner(&mut self, symbol: &str, token_type: &str, token_sub_type: Option<&str>) {
+        let (token_type, token_sub_type) = self.intern_pair(token_type, token_sub_type);
+        self.add_symbol_scanner(symbol, token_type, token_sub_type);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_named_block_scanner(
+        &mut self,
+        start_delimiter: &str,
+        end_delimiter: &str,
+        token_type: &str,
+        token_sub_type: Option<&str>,
+        allow_nesting: bool,
+        raw_mode: bool,
+        include_delimiters: bool,
+    ) {
+        let (token_type, token_sub_type) = self.intern_pair(token_type, token_sub_type);
+        self.add_block_scanner(
+            start_delimiter,
+            end_delimiter,
+            token_type,
+            token_sub_type,
+            allow_nesting,
+            raw_mode,
+            include_delimiters,
+        );
+    }
+
+    pub fn add_named_eol_scanner(
+        &mut self,
+        delimiter: &str,
+        token_type: &str,
+        token_sub_type: Option<&str>,
+        include_delimiter: bool,
+    ) {
+        let (token_type, token_sub_type) = self.intern_pair(token_type, token_sub_type);
+        self.add_eol_scanner(delimiter, token_type, token_sub_type, include_delimiter);
+    }
+
+    /// `utils::pretty_print_tokens` with type names resolved through the registry
+    pub fn pretty_print_tokens(&self, tokens: &[Token<TokenTypeId>]) -> String {
+        utils::pretty_print_tokens_with(tokens, |id| self.registry.resolve(id))
+    }
+
+    /// Describes a token by its type name, sub-type name and position, for error messages
+    pub fn describe_token(&self, token: &Token<TokenTypeId>) -> String {
+        format!(
+            "{} at line {}, column {}",
+            utils::token_summary_with(token, |id| self.registry.resolve(id)),
+            token.line,
+            token.column
+        )
+    }
+}
@@ -1,3 +1,22 @@
+pub mod builder;
+pub mod composition;
+mod dispatch;
+pub mod encoding;
+pub mod interned;
+pub mod parallel;
+pub mod pipeline;
+pub mod position;
+pub mod scanner_list;
+pub mod state;
 pub mod tokenizer;
 
+pub use builder::{BuildError, TokenizerBuilder};
+pub use composition::{ConflictResolution, MergeConflict};
+pub(crate) use dispatch::is_anchored;
+pub use encoding::{DecodedInput, Encoding, InvalidSequence, SourceTokens};
+pub use parallel::{ParallelConfig, SplitBoundary};
+pub use pipeline::TokenPipeline;
+pub use position::{ColumnMode, LineEndingStyle, LineEndings, PositionTracker};
+pub use scanner_list::{ScannerError, ScannerInfo};
+pub use state::LexerSnapshot;
 pub use tokenizer::{Tokenizer, TokenizerConfig};
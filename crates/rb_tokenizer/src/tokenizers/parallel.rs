@@ -0,0 +1,235 @@
+use super::position::{LineEndings, PositionTracker};
+use super::tokenizer::{Collector, Cursor, Step, Tokenizer};
+use crate::tokens::{Token, TokenKind, TokenizationError};
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+
+/// Predicate deciding whether the input may be split at a given byte offset.
+pub type SplitPredicate = Arc<dyn Fn(&str, usize) -> bool + Send + Sync>;
+
+/// Where the input may be cut into chunks for parallel tokenization
+#[derive(Clone)]
+pub enum SplitBoundary {
+    /// Split at the start of a line, as defined by the tokenizer's `line_endings`
+    Newline,
+    /// Split at byte offsets for which the predicate returns `true`.
+    /// The predicate receives the whole input and a char-boundary offset into it.
+    Predicate(SplitPredicate),
+}
+
+impl fmt::Debug for SplitBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplitBoundary::Newline => write!(f, "Newline"),
+            SplitBoundary::Predicate(_) => write!(f, "Predicate(..)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParallelConfig {
+    pub threads: usize,          // Number of worker threads; 0 uses the available parallelism
+    pub min_chunk_size: usize,   // Inputs are never split into chunks smaller than this (in bytes)
+    pub boundary: SplitBoundary, // Where chunks may start
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            min_chunk_size: 1 << 20,
+            boundary: SplitBoundary::Newline,
+        }
+    }
+}
+
+/// The steps a worker produced for one chunk, in the chunk's own line/column frame.
+struct ChunkRun<K> {
+    start: usize,
+    steps: Vec<Step<K>>,
+    end: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// Maps positions from a worker's frame onto the sequential frame.
+///
+/// At byte `offset` the worker was on `from_line` while the sequential
+/// tokenizer is at `to`. Lines after that first one only need shifting,
+/// because every newline resets the column in both frames; positions on the
+/// first line are re-measured from `offset` so tab stops and grapheme
+/// clusters come out exactly as the sequential tokenizer counts them.
+struct Rebase<'i> {
+    input: &'i str,
+    offset: usize,
+    from_line: usize,
+    to_line: usize,
+    to: PositionTracker,
+}
+
+impl Rebase<'_> {
+    fn apply(&mut self, line: usize, column: usize, at: usize) -> (usize, usize) {
+        if line == self.from_line {
+            self.to.advance(&self.input[self.offset..at]);
+            self.offset = at;
+            (self.to.line, self.to.column)
+        } else {
+            (line + self.to_line - self.from_line, column)
+        }
+    }
+
+    fn step<K: TokenKind>(&mut self, step: &Step<K>) -> Step<K> {
+        let mut step = step.clone();
+        let (line, column) = self.apply(step.line, step.column, step.start);
+        if let Some(token) = step.token.as_mut().filter(|_| step.positioned) {
+            (token.line, token.column) = (line, column);
+        }
+        (step.line, step.column) = (line, column);
+        step
+    }
+}
+
+impl<K: TokenKind> Tokenizer<K> {
+    /// Tokenizes large inputs on multiple threads.
+    ///
+    /// The input is cut at `parallel.boundary` into roughly equal chunks, each
+    /// chunk is tokenized independently, and the results are stitched back
+    /// together with line/column positions rebased onto the whole input.
+    /// Tokens that straddle a cut (e.g. a block comment spanning lines) are
+    /// detected while stitching and re-scanned from the previous chunk, so the
+    /// output, errors and `last_errors` are identical to `tokenize`.
+    pub fn tokenize_parallel(&self, input: &str, parallel: &ParallelConfig) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        let threads = match parallel.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let splits = split_points(input, parallel, threads, self.config().line_endings);
+        // A chunk cannot know the tokens before it, which context-aware scanners depend on
+        if splits.len() < 2 || self.has_context_scanners() {
+            return self.tokenize(input);
+        }
+
+        let runs: Vec<ChunkRun<K>> = thread::scope(|scope| {
+            let handles: Vec<_> = splits
+                .iter()
+                .enumerate()
+                .map(|(i, &start)| {
+                    let end = splits.get(i + 1).copied().unwrap_or(input.len());
+                    scope.spawn(move || self.run_chunk(input, start, end))
+                })
+                .collect();
+
+            handles.into_iter().map(|h| h.join().expect("tokenizer worker panicked")).collect()
+        });
+
+        self.stitch(input, &runs)
+    }
+
+    /// Tokenizes `input[start..]` until the cursor reaches `end`
+    fn run_chunk(&self, input: &str, start: usize, end: usize) -> ChunkRun<K> {
+        let mut cursor = Cursor::new(self, input, start, 1, 1);
+        let mut steps = Vec::new();
+
+        while cursor.position() < end {
+            let Some(step) = cursor.next_step() else { break };
+            // Nothing after the first error is used when tokenization stops on errors
+            let stop = step.error.is_some() && !self.config().continue_on_error;
+            steps.push(step);
+            if stop {
+                break;
+            }
+        }
+
+        ChunkRun {
+            start,
+            steps,
+            end: cursor.position(),
+            end_line: cursor.line(),
+            end_column: cursor.column(),
+        }
+    }
+
+    /// Replays the chunk runs in input order as the sequential tokenizer would see them
+    fn stitch(&self, input: &str, runs: &[ChunkRun<K>]) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        let mut collector = Collector::new(self.config());
+        let (mut position, mut line, mut column) = (0, 1, 1);
+        let mut run_index = 0;
+
+        'outer: while position < input.len() {
+            while run_index + 1 < runs.len() && runs[run_index + 1].start <= position {
+                run_index += 1;
+            }
+            let run = &runs[run_index];
+
+            match run.steps.binary_search_by_key(&position, |step| step.start) {
+                Ok(first) => {
+                    // In sync with this run: adopt its steps from here on
+                    let mut rebase = Rebase {
+                        input,
+                        offset: position,
+                        from_line: run.steps[first].line,
+                        to_line: line,
+                        to: PositionTracker::at(line, column, self.config().column_mode)
+                            .with_line_endings(self.config().line_endings)
+                            .after(&input[..position]),
+                    };
+                    for step in &run.steps[first..] {
+                        if !collector.push(rebase.step(step)) {
+                            break 'outer;
+                        }
+                    }
+                    position = run.end;
+                    (line, column) = rebase.apply(run.end_line, run.end_column, run.end);
+                }
+                Err(_) => {
+                    // A token crossed into this chunk; scan sequentially until we resynchronize
+                    let mut cursor = Cursor::new(self, input, position, line, column);
+                    let Some(step) = cursor.next_step() else { break };
+                    if !collector.push(step) {
+                        break;
+                    }
+                    position = cursor.position();
+                    (line, column) = (cursor.line(), cursor.column());
+                }
+            }
+        }
+
+        self.record_line_ending_style(input);
+        collector.finish(self)
+    }
+}
+
+/// Picks chunk start offsets, always beginning with 0
+fn split_points(input: &str, parallel: &ParallelConfig, threads: usize, line_endings: LineEndings) -> Vec<usize> {
+    let min_chunk = parallel.min_chunk_size.max(1);
+    let chunks = threads.min(input.len() / min_chunk).max(1);
+    let target = input.len() / chunks;
+    let mut points = vec![0];
+
+    for k in 1..chunks {
+        let last = *points.last().unwrap();
+        let mut from = (k * target).max(last + min_chunk);
+        while from < input.len() && !input.is_char_boundary(from) {
+            from += 1;
+        }
+        if from >= input.len() {
+            break;
+        }
+
+        let found = match &parallel.boundary {
+            SplitBoundary::Newline => line_endings.find(&input[from..]).map(|(i, len)| from + i + len),
+            SplitBoundary::Predicate(predicate) => input[from..]
+                .char_indices()
+                .map(|(i, _)| from + i)
+                .find(|&at| predicate(input, at)),
+        };
+
+        match found {
+            Some(at) if at < input.len() => points.push(at),
+            _ => break,
+        }
+    }
+
+    points
+}
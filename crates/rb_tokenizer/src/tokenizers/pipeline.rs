@@ -0,0 +1,265 @@
+use super::position::{ColumnMode, LineEndings, PositionTracker};
+use crate::tokens::{Token, TokenKind};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+/// Predicate of a `filter` stage
+pub type FilterFn<K> = Arc<dyn Fn(&Token<K>) -> bool + Send + Sync>;
+/// Function of a `map` stage
+pub type MapFn<K> = Arc<dyn Fn(Token<K>) -> Token<K> + Send + Sync>;
+/// Predicate of a `merge_adjacent` stage, given the token merged so far and the next one
+pub type MergeFn<K> = Arc<dyn Fn(&Token<K>, &Token<K>) -> bool + Send + Sync>;
+/// Function of a `split` stage, returning the byte offsets to split the value at
+pub type SplitFn<K> = Arc<dyn Fn(&Token<K>) -> Vec<usize> + Send + Sync>;
+/// Function of a `reclassify` stage, returning the new type and sub-type
+pub type ReclassifyFn<K> = Arc<dyn Fn(&Token<K>) -> Option<(K, Option<K>)> + Send + Sync>;
+
+enum Stage<K> {
+    Filter(FilterFn<K>),
+    Map(MapFn<K>),
+    MergeAdjacent(MergeFn<K>),
+    Split(SplitFn<K>),
+    Reclassify(ReclassifyFn<K>),
+}
+
+impl<K> Clone for Stage<K> {
+    fn clone(&self) -> Self {
+        match self {
+            Stage::Filter(keep) => Stage::Filter(keep.clone()),
+            Stage::Map(map) => Stage::Map(map.clone()),
+            Stage::MergeAdjacent(merge) => Stage::MergeAdjacent(merge.clone()),
+            Stage::Split(split) => Stage::Split(split.clone()),
+            Stage::Reclassify(reclassify) => Stage::Reclassify(reclassify.clone()),
+        }
+    }
+}
+
+/// Passes run over tokens after scanning, such as dropping comments or
+/// reclassifying identifiers, applied lazily in the order they were added.
+///
+/// ```
+/// use rb_tokenizer::tokenizers::TokenPipeline;
+///
+/// let pipeline = TokenPipeline::new()
+///     .filter(|token| token.token_type != "Comment")
+///     .merge_adjacent(|merged, next| merged.token_type == "Text" && next.token_type == "Text")
+///     .reclassify(|token| (token.token_type == "Identifier" && token.value == "if").then_some(("Keyword", None)));
+/// ```
+///
+/// A pipeline can be applied to any token sequence with `apply`, or attached
+/// to a tokenizer with `Tokenizer::set_pipeline`, which applies it to the
+/// result of every tokenize method but `tokenize_decoded`, and to `Tokenizer::tokens`.
+pub struct TokenPipeline<K = &'static str> {
+    stages: Vec<Stage<K>>,
+}
+
+impl<K> Clone for TokenPipeline<K> {
+    fn clone(&self) -> Self {
+        Self {
+            stages: self.stages.clone(),
+        }
+    }
+}
+
+impl<K: TokenKind> Default for TokenPipeline<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: TokenKind> TokenPipeline<K> {
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Keeps only the tokens `keep` returns `true` for
+    pub fn filter(mut self, keep: impl Fn(&Token<K>) -> bool + Send + Sync + 'static) -> Self {
+        self.stages.push(Stage::Filter(Arc::new(keep)));
+        self
+    }
+
+    /// Replaces every token with the one `map` returns, e.g. to unquote strings
+    pub fn map(mut self, map: impl Fn(Token<K>) -> Token<K> + Send + Sync + 'static) -> Self {
+        self.stages.push(Stage::Map(Arc::new(map)));
+        self
+    }
+
+    /// Merges runs of consecutive tokens into one: each token is appended to
+    /// the one before it while `merge` returns `true` for the token merged so
+    /// far and the next. The merged token keeps the type and position of the
+    /// first token of the run and its value is the values joined; it has
+    /// the flags of every token of the run and no literal or captures.
+    pub fn merge_adjacent(mut self, merge: impl Fn(&Token<K>, &Token<K>) -> bool + Send + Sync + 'static) -> Self {
+        self.stages.push(Stage::MergeAdjacent(Arc::new(merge)));
+        self
+    }
+
+    /// Splits tokens at the byte offsets of their value that `split` returns.
+    /// The parts keep the token's type and flags, have no literal or captures
+    /// and are positioned where they start in the value; empty parts and
+    /// offsets that are not character boundaries are dropped.
+    ///
+    /// Stages only see tokens, so part positions are counted over the value:
+    /// they match the source only for tokens whose value is their source text.
+    /// Split tokens whose value differs, such as strings without their
+    /// delimiters or with transformed escapes, only where positions of the
+    /// parts do not matter.
+    pub fn split(mut self, split: impl Fn(&Token<K>) -> Vec<usize> + Send + Sync + 'static) -> Self {
+        self.stages.push(Stage::Split(Arc::new(split)));
+        self
+    }
+
+    /// Gives tokens the type and sub-type `reclassify` returns, leaving
+    /// tokens it returns `None` for unchanged
+    pub fn reclassify(mut self, reclassify: impl Fn(&Token<K>) -> Option<(K, Option<K>)> + Send + Sync + 'static) -> Self {
+        self.stages.push(Stage::Reclassify(Arc::new(reclassify)));
+        self
+    }
+
+    /// Returns whether the pipeline has no stages
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Applies the pipeline to `tokens` lazily. Split parts are positioned by
+    /// counting columns in `char`s, with any line ending.
+    pub fn apply<'p, I>(&'p self, tokens: I) -> impl Iterator<Item = Token<K>> + 'p
+    where
+        I: IntoIterator<Item = Token<K>>,
+        I::IntoIter: 'p,
+    {
+        self.apply_results(tokens.into_iter().map(Ok::<_, Infallible>), ColumnMode::Chars, LineEndings::Any)
+            .map(|item| match item {
+                Ok(token) => token,
+                Err(never) => match never {},
+            })
+    }
+
+    /// Applies the pipeline to the tokens of `items`, passing errors through.
+    /// Runs of tokens are not merged across an error.
+    pub(crate) fn apply_results<'p, E: 'p>(
+        &'p self,
+        items: impl Iterator<Item = Result<Token<K>, E>> + 'p,
+        column_mode: ColumnMode,
+        line_endings: LineEndings,
+    ) -> Box<dyn Iterator<Item = Result<Token<K>, E>> + 'p> {
+        let mut items: Box<dyn Iterator<Item = Result<Token<K>, E>> + 'p> = Box::new(items);
+        for stage in &self.stages {
+            items = match stage {
+                Stage::Filter(keep) => Box::new(items.filter(move |item| item.as_ref().map_or(true, |token| keep(token)))),
+                Stage::Map(map) => Box::new(items.map(move |item| item.map(|token| map(token)))),
+                Stage::Reclassify(reclassify) => Box::new(items.map(move |item| {
+                    item.map(|token| match reclassify(&token) {
+                        Some((token_type, token_sub_type)) => Token {
+                            token_type,
+                            token_sub_type,
+                            ..token
+                        },
+                        None => token,
+                    })
+                })),
+                Stage::MergeAdjacent(merge) => Box::new(Merge {
+                    items,
+                    merge,
+                    next: None,
+                }),
+                Stage::Split(split) => Box::new(Split {
+                    items,
+                    split,
+                    parts: VecDeque::new(),
+                    column_mode,
+                    line_endings,
+                }),
+            };
+        }
+        items
+    }
+}
+
+/// The iterator of a `merge_adjacent` stage
+struct Merge<'p, K, E> {
+    items: Box<dyn Iterator<Item = Result<Token<K>, E>> + 'p>,
+    merge: &'p MergeFn<K>,
+    /// The item after the run being merged, read to find the run's end
+    next: Option<Result<Token<K>, E>>,
+}
+
+impl<K, E> Iterator for Merge<'_, K, E> {
+    type Item = Result<Token<K>, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut merged = match self.next.take().or_else(|| self.items.next())? {
+            Ok(token) => token,
+            error => return Some(error),
+        };
+        for item in self.items.by_ref() {
+            match item {
+                Ok(token) if (self.merge)(&merged, &token) => {
+                    merged.value.push_str(&token.value);
+                    merged.literal = None;
+                    merged.captures.clear();
+                    merged.flags.extend(token.flags);
+                }
+                item => {
+                    self.next = Some(item);
+                    break;
+                }
+            }
+        }
+        Some(Ok(merged))
+    }
+}
+
+/// The iterator of a `split` stage
+struct Split<'p, K, E> {
+    items: Box<dyn Iterator<Item = Result<Token<K>, E>> + 'p>,
+    split: &'p SplitFn<K>,
+    /// Parts of the last token split and not yet returned
+    parts: VecDeque<Token<K>>,
+    column_mode: ColumnMode,
+    line_endings: LineEndings,
+}
+
+impl<K: TokenKind, E> Split<'_, K, E> {
+    fn split_token(&mut self, token: Token<K>) {
+        let mut offsets = (self.split)(&token);
+        offsets.retain(|&offset| offset > 0 && offset < token.value.len() && token.value.is_char_boundary(offset));
+        offsets.sort_unstable();
+        offsets.dedup();
+        if offsets.is_empty() {
+            self.parts.push_back(token);
+            return;
+        }
+
+        // Untracked tokens are at line 0 and their parts stay there
+        let mut position = PositionTracker::at(token.line, token.column, self.column_mode).with_line_endings(self.line_endings);
+        let mut start = 0;
+        for end in offsets.into_iter().chain([token.value.len()]) {
+            let part = &token.value[start..end];
+            let (line, column) = if token.line == 0 { (0, token.column) } else { (position.line, position.column) };
+            let mut part_token = Token::new(token.token_type, token.token_sub_type, part.to_string(), line, column);
+            part_token.flags = token.flags.clone();
+            self.parts.push_back(part_token);
+            position.advance(part);
+            start = end;
+        }
+    }
+}
+
+impl<K: TokenKind, E> Iterator for Split<'_, K, E> {
+    type Item = Result<Token<K>, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(part) = self.parts.pop_front() {
+            return Some(Ok(part));
+        }
+        match self.items.next()? {
+            Ok(token) => {
+                self.split_token(token);
+                self.parts.pop_front().map(Ok)
+            }
+            error => Some(error),
+        }
+    }
+}
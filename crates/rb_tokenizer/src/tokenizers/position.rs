@@ -0,0 +1,206 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// How columns are counted when tracking token positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnMode {
+    /// One column per UTF-8 byte
+    Bytes,
+    /// One column per Unicode scalar value (`char`)
+    #[default]
+    Chars,
+    /// One column per UTF-16 code unit, as expected by LSP clients
+    Utf16,
+    /// One column per extended grapheme cluster (user-perceived character)
+    Graphemes,
+    /// Terminal display width: East Asian wide characters take two columns,
+    /// zero-width characters none, and tabs advance to the next tab stop
+    DisplayWidth { tab_width: usize },
+}
+
+impl ColumnMode {
+    /// Returns the column reached after `text` (which must not contain a line break) starting at `column`
+    pub fn advance_column(&self, text: &str, column: usize) -> usize {
+        match self {
+            ColumnMode::Bytes => column + text.len(),
+            ColumnMode::Chars => column + text.chars().count(),
+            ColumnMode::Utf16 => column + text.chars().map(char::len_utf16).sum::<usize>(),
+            ColumnMode::Graphemes => column + text.graphemes(true).count(),
+            ColumnMode::DisplayWidth { tab_width } => text.chars().fold(column, |column, ch| {
+                if ch == '\t' {
+                    let tab_width = (*tab_width).max(1);
+                    ((column - 1) / tab_width + 1) * tab_width + 1
+                } else {
+                    column + ch.width().unwrap_or(0)
+                }
+            }),
+        }
+    }
+}
+
+/// Which character sequences terminate a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEndings {
+    /// Only `\n`; a `\r` is an ordinary character
+    Lf,
+    /// `\n`, `\r\n` and a lone `\r` (classic Mac)
+    #[default]
+    Any,
+    /// Everything in `Any` plus U+2028 LINE SEPARATOR and U+2029 PARAGRAPH SEPARATOR
+    Unicode,
+}
+
+impl LineEndings {
+    /// Returns the byte length of the line terminator at the start of `input`, if any
+    pub fn terminator_len(&self, input: &str) -> Option<usize> {
+        match input.chars().next()? {
+            '\n' => Some(1),
+            '\r' if *self != LineEndings::Lf => Some(if input[1..].starts_with('\n') { 2 } else { 1 }),
+            ch @ ('\u{2028}' | '\u{2029}') if *self == LineEndings::Unicode => Some(ch.len_utf8()),
+            _ => None,
+        }
+    }
+
+    /// Finds the first line terminator in `input`, returning its byte offset and length
+    pub fn find(&self, input: &str) -> Option<(usize, usize)> {
+        if *self == LineEndings::Lf {
+            return input.find('\n').map(|at| (at, 1));
+        }
+        input
+            .char_indices()
+            .find_map(|(at, _)| self.terminator_len(&input[at..]).map(|len| (at, len)))
+    }
+
+    /// Returns whether `input` contains a line terminator
+    pub fn contains(&self, input: &str) -> bool {
+        self.find(input).is_some()
+    }
+}
+
+/// The line ending convention used by a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndingStyle {
+    Lf,
+    CrLf,
+    Cr,
+    LineSeparator,
+    ParagraphSeparator,
+    /// More than one kind of line ending occurs
+    Mixed,
+}
+
+impl LineEndingStyle {
+    /// Detects the line ending style of `input` under the given policy.
+    /// Returns `None` when the input has no line terminators.
+    pub fn detect(input: &str, line_endings: LineEndings) -> Option<Self> {
+        let mut style = None;
+        let mut rest = input;
+
+        while let Some((at, len)) = line_endings.find(rest) {
+            let found = match &rest[at..at + len] {
+                "\n" => LineEndingStyle::Lf,
+                "\r\n" => LineEndingStyle::CrLf,
+                "\r" => LineEndingStyle::Cr,
+                "\u{2028}" => LineEndingStyle::LineSeparator,
+                _ => LineEndingStyle::ParagraphSeparator,
+            };
+            match style {
+                None => style = Some(found),
+                Some(previous) if previous != found => return Some(LineEndingStyle::Mixed),
+                _ => {}
+            }
+            rest = &rest[at + len..];
+        }
+
+        style
+    }
+}
+
+/// Tracks the line and column reached after consuming slices of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionTracker {
+    pub line: usize,
+    pub column: usize,
+    pub mode: ColumnMode,
+    pub line_endings: LineEndings,
+    /// Set when the last slice ended in a `\r` that may pair with a leading `\n`
+    pending_cr: bool,
+    /// In `Graphemes` mode, the last grapheme cluster of the current line,
+    /// which the next slice may extend (e.g. with a combining mark)
+    cluster_tail: String,
+}
+
+impl PositionTracker {
+    pub fn new(mode: ColumnMode) -> Self {
+        Self::at(1, 1, mode)
+    }
+
+    /// Creates a tracker that resumes from a known position
+    pub fn at(line: usize, column: usize, mode: ColumnMode) -> Self {
+        Self {
+            line,
+            column,
+            mode,
+            line_endings: LineEndings::default(),
+            pending_cr: false,
+            cluster_tail: String::new(),
+        }
+    }
+
+    /// Sets which sequences start a new line
+    pub fn with_line_endings(mut self, line_endings: LineEndings) -> Self {
+        self.line_endings = line_endings;
+        self
+    }
+
+    /// Carries over what the next slice depends on from `preceding`, the input
+    /// consumed before this tracker's position: a `\r` that a leading `\n`
+    /// completes, and the grapheme cluster a leading combining mark extends
+    pub(crate) fn after(mut self, preceding: &str) -> Self {
+        self.pending_cr = self.line_endings != LineEndings::Lf && preceding.ends_with('\r');
+        if self.mode == ColumnMode::Graphemes {
+            let last = preceding.graphemes(true).next_back().unwrap_or("");
+            if self.line_endings.find(last).is_none() {
+                self.cluster_tail = last.to_string();
+            }
+        }
+        self
+    }
+
+    /// Advances the position over a consumed slice of the input
+    pub fn advance(&mut self, consumed: &str) {
+        let mut rest = consumed;
+
+        // A "\r\n" split across two slices is a single line break
+        if self.pending_cr && rest.starts_with('\n') {
+            rest = &rest[1..];
+        }
+        self.pending_cr = false;
+
+        while let Some((at, len)) = self.line_endings.find(rest) {
+            self.line += 1;
+            self.column = 1;
+            self.pending_cr = &rest[at..at + len] == "\r";
+            self.cluster_tail.clear();
+            rest = &rest[at + len..];
+        }
+
+        if rest.is_empty() {
+            return;
+        }
+        self.pending_cr = false;
+        if self.mode == ColumnMode::Graphemes {
+            // Count the clusters of the line so far, so a cluster split between
+            // two slices is counted once
+            let had_tail = !self.cluster_tail.is_empty();
+            self.cluster_tail.push_str(rest);
+            let mut clusters = self.cluster_tail.graphemes(true);
+            let last = clusters.next_back().map_or(0, str::len);
+            let count = clusters.count() + 1;
+            self.column += count - usize::from(had_tail);
+            self.cluster_tail.drain(..self.cluster_tail.len() - last);
+        } else {
+            self.column = self.mode.advance_column(rest, self.column);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use super::tokenizer::Tokenizer;
+use crate::scanners::ScannerType;
+use crate::tokens::TokenKind;
+use std::sync::Arc;
+use std::{error::Error, fmt};
+
+/// A scanner together with how the tokenizer schedules it. Scanners are
+/// shared, so cloned and merged tokenizers reuse them.
+#[derive(Clone)]
+pub(crate) struct ScannerEntry<K: TokenKind> {
+    pub(crate) scanner: Arc<ScannerType<K>>,
+    pub(crate) name: Option<String>,
+    pub(crate) priority: i64,
+    pub(crate) enabled: bool,
+}
+
+/// A problem with a named scanner operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScannerError {
+    /// No scanner has this name
+    UnknownScanner(String),
+    /// Another scanner already has this name
+    DuplicateName(String),
+}
+
+impl fmt::Display for ScannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScannerError::UnknownScanner(name) => write!(f, "No scanner named '{}'", name),
+            ScannerError::DuplicateName(name) => write!(f, "A scanner named '{}' already exists", name),
+        }
+    }
+}
+
+impl Error for ScannerError {}
+
+/// One scanner of a tokenizer, as listed by `Tokenizer::scanners`
+pub struct ScannerInfo<'t, K: TokenKind> {
+    pub name: Option<&'t str>,
+    pub priority: i64,
+    /// Disabled scanners are kept but never tried
+    pub enabled: bool,
+    pub scanner: &'t ScannerType<K>,
+}
+
+impl<K: TokenKind> fmt::Debug for ScannerInfo<'_, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScannerInfo")
+            .field("name", &self.name)
+            .field("priority", &self.priority)
+            .field("enabled", &self.enabled)
+            .field("kind", &self.scanner.kind())
+            .finish()
+    }
+}
+
+/// Named scanner management.
+///
+/// Scanners are tried in priority order: lower priorities first, and
+/// scanners with equal priority in the order they were added. The `add_*`
+/// methods use priority 0. Naming a scanner lets a dialect derived from a
+/// base tokenizer remove, replace, disable or reorder it later.
+impl<K: TokenKind> Tokenizer<K> {
+    /// Adds a scanner with an explicit priority and an optional unique name
+    pub fn register_scanner(
+        &mut self,
+        name: Option<&str>,
+        priority: i64,
+        scanner: impl Into<ScannerType<K>>,
+    ) -> Result<(), ScannerError> {
+        if let Some(name) = name {
+            if self.position(name).is_some() {
+                return Err(ScannerError::DuplicateName(name.to_string()));
+            }
+        }
+        self.insert_entry(ScannerEntry {
+            scanner: Arc::new(scanner.into()),
+            name: name.map(str::to_string),
+            priority,
+            enabled: true,
+        });
+        Ok(())
+    }
+
+    /// Removes the named scanner and returns it
+    pub fn remove_scanner(&mut self, name: &str) -> Result<Arc<ScannerType<K>>, ScannerError> {
+        let index = self.require(name)?;
+        self.dispatch = None;
+        Ok(self.scanners.remove(index).scanner)
+    }
+
+    /// Swaps the named scanner for `scanner`, keeping its name, priority and
+    /// enabled state, and returns the old one
+    pub fn replace_scanner(
+        &mut self,
+        name: &str,
+        scanner: impl Into<ScannerType<K>>,
+    ) -> Result<Arc<ScannerType<K>>, ScannerError> {
+        let index = self.require(name)?;
+        self.dispatch = None;
+        Ok(std::mem::replace(&mut self.scanners[index].scanner, Arc::new(scanner.into())))
+    }
+
+    /// Makes a disabled scanner take part in tokenizing again
+    pub fn enable_scanner(&mut self, name: &str) -> Result<(), ScannerError> {
+        self.set_enabled(name, true)
+    }
+
+    /// Stops the named scanner from being tried without removing it
+    pub fn disable_scanner(&mut self, name: &str) -> Result<(), ScannerError> {
+        self.set_enabled(name, false)
+    }
+
+    /// Moves the named scanner behind the scanners that have `priority` or lower
+    pub fn set_scanner_priority(&mut self, name: &str, priority: i64) -> Result<(), ScannerError> {
+        let index = self.require(name)?;
+        let mut entry = self.scanners.remove(index);
+        entry.priority = priority;
+        self.insert_entry(entry);
+        Ok(())
+    }
+
+    /// Lists every scanner, enabled or not, in the order they are tried
+    pub fn scanners(&self) -> impl Iterator<Item = ScannerInfo<'_, K>> {
+        self.scanners.iter().map(|entry| ScannerInfo {
+            name: entry.name.as_deref(),
+            priority: entry.priority,
+            enabled: entry.enabled,
+            scanner: &entry.scanner,
+        })
+    }
+
+    /// The enabled scanners in the order they are tried, with their positions in `scanners()`
+    pub(crate) fn enabled_scanners(&self) -> impl Iterator<Item = (usize, &ScannerType<K>)> {
+        self.scanners
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.enabled)
+            .map(|(index, entry)| (index, &*entry.scanner))
+    }
+
+    /// Inserts after every scanner with the same or a lower priority
+    pub(crate) fn insert_entry(&mut self, entry: ScannerEntry<K>) {
+        let index = self.scanners.partition_point(|other| other.priority <= entry.priority);
+        self.scanners.insert(index, entry);
+        self.dispatch = None;
+    }
+
+    fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), ScannerError> {
+        let index = self.require(name)?;
+        self.scanners[index].enabled = enabled;
+        self.dispatch = None;
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.scanners.iter().position(|entry| entry.name.as_deref() == Some(name))
+    }
+
+    fn require(&self, name: &str) -> Result<usize, ScannerError> {
+        self.position(name).ok_or_else(|| ScannerError::UnknownScanner(name.to_string()))
+    }
+}
@@ -0,0 +1,123 @@
+use super::tokenizer::{Checkpoint, Collector, Cursor, Tokenizer};
+use crate::tokens::{Token, TokenKind, TokenizationError};
+use std::any::Any;
+
+/// A point tokenizing can restart from: the position, the context seen by
+/// context-aware scanners and a copy of the user state.
+///
+/// Snapshots taken by `tokenize_with_snapshots` stay valid for an edited
+/// input as long as the text before their offset is unchanged, which lets an
+/// editor re-tokenize from the last snapshot before an edit.
+#[derive(Debug, Clone)]
+pub struct LexerSnapshot<K, S> {
+    checkpoint: Checkpoint<K>,
+    state: S,
+}
+
+impl<K, S> LexerSnapshot<K, S> {
+    /// Byte offset tokenizing resumes at
+    pub fn offset(&self) -> usize {
+        self.checkpoint.offset
+    }
+
+    pub fn line(&self) -> usize {
+        self.checkpoint.position.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.checkpoint.position.column
+    }
+
+    /// The user state as it was at the snapshot
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// The modes pushed by context-aware scanners, outermost first
+    pub fn modes(&self) -> &[&'static str] {
+        &self.checkpoint.modes
+    }
+}
+
+/// Tokenizing with user state.
+///
+/// The state is handed to context-aware scanners, which read and update it
+/// through `ScanContext::state` and `state_mut`, e.g. to count the brace
+/// depth inside template literals or queue heredoc terminators.
+impl<K: TokenKind> Tokenizer<K> {
+    /// Tokenizes `input`, threading `state` through the context-aware scanners
+    pub fn tokenize_with_state<S: Any>(&self, input: &str, state: &mut S) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        let mut cursor = Cursor::new(self, input, 0, 1, 1);
+        let mut collector = Collector::new(self.config());
+
+        while let Some(step) = cursor.next_step_with(Some(state)) {
+            if !collector.push(step) {
+                break;
+            }
+        }
+
+        self.record_line_ending_style(input);
+        collector.finish(self)
+    }
+
+    /// Like `tokenize_with_state`, also returning a snapshot taken at the start
+    /// of the input and before the first token or whitespace run of every
+    /// later line
+    #[allow(clippy::type_complexity)]
+    pub fn tokenize_with_snapshots<S: Any + Clone>(
+        &self,
+        input: &str,
+        state: &mut S,
+    ) -> Result<(Vec<Token<K>>, Vec<LexerSnapshot<K, S>>), Vec<TokenizationError>> {
+        let mut cursor = Cursor::new(self, input, 0, 1, 1);
+        let mut collector = Collector::new(self.config());
+        let mut snapshots: Vec<LexerSnapshot<K, S>> = Vec::new();
+
+        loop {
+            if snapshots.last().is_none_or(|snapshot| snapshot.line() < cursor.line()) {
+                snapshots.push(LexerSnapshot {
+                    checkpoint: cursor.checkpoint(),
+                    state: state.clone(),
+                });
+            }
+            let Some(step) = cursor.next_step_with(Some(state)) else { break };
+            if !collector.push(step) {
+                break;
+            }
+        }
+
+        self.record_line_ending_style(input);
+        collector.finish(self).map(|tokens| (tokens, snapshots))
+    }
+
+    /// Restores `state` from `snapshot` and tokenizes the rest of `input`
+    /// from the snapshot's offset, returning the tokens after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot's offset is not a character boundary of `input`.
+    pub fn resume_from_snapshot<S: Any + Clone>(
+        &self,
+        input: &str,
+        snapshot: &LexerSnapshot<K, S>,
+        state: &mut S,
+    ) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        assert!(
+            input.is_char_boundary(snapshot.offset()),
+            "snapshot offset {} is not a character boundary of the input",
+            snapshot.offset()
+        );
+        state.clone_from(&snapshot.state);
+        let mut cursor = Cursor::resume(self, input, &snapshot.checkpoint);
+        let mut collector = Collector::new(self.config());
+
+        while let Some(step) = cursor.next_step_with(Some(state)) {
+            if !collector.push(step) {
+                break;
+            }
+        }
+
+        self.record_line_ending_style(input);
+        collector.finish(self)
+    }
+}
@@ -1,6 +1,16 @@
-use crate::scanners::{self, BlockScanner, EolScanner, RegexScanner, Scanner, ScannerType, SymbolScanner};
-use crate::tokens::{Token, TokenizationError};
-use std::cell::RefCell;
+use super::builder::TokenizerBuilder;
+use super::dispatch::Dispatch;
+use super::encoding::DecodedInput;
+use super::pipeline::TokenPipeline;
+use super::position::{ColumnMode, LineEndingStyle, LineEndings, PositionTracker};
+use super::scanner_list::ScannerEntry;
+use crate::scanners::{
+    self, BlockScanner, ContextScanner, EolScanner, RegexScanner, ScanContext, Scanner, ScannerType, SymbolOptions,
+    SymbolScanner,
+};
+use crate::tokens::{DelimiterTable, Token, TokenKind, TokenTree, TokenTypeRegistry, TokenizationError};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone)]
 pub struct TokenizerConfig {
@@ -8,6 +18,9 @@ pub struct TokenizerConfig {
     pub continue_on_error: bool,
     pub error_tolerance_limit: usize,
     pub track_token_positions: bool,        // Controls whether line/column tracking is performed
+    pub column_mode: ColumnMode,            // How columns are counted (chars, UTF-16 units, display width, ...)
+    pub line_endings: LineEndings,          // Which sequences end a line (positions, EOL scanners, whitespace)
+    pub report_line_ending_style: bool,     // Records the document's line ending style after tokenizing
 }
 
 impl Default for TokenizerConfig {
@@ -17,30 +30,83 @@ impl Default for TokenizerConfig {
             continue_on_error: false,
             error_tolerance_limit: 10,
             track_token_positions: true,     // Default to tracking positions
+            column_mode: ColumnMode::Chars,
+            line_endings: LineEndings::Any,
+            report_line_ending_style: false,
         }
     }
 }
 
-pub struct Tokenizer {
-    scanners: Vec<ScannerType>,
+/// Tokenizer producing `Token<K>`s. `K` defaults to `&'static str`; see
+/// `new_typed` for tokenizers over a user-defined `TokenKind` and `interned`
+/// for token types named at runtime.
+pub struct Tokenizer<K: TokenKind = &'static str> {
+    /// Kept in priority order
+    pub(super) scanners: Vec<ScannerEntry<K>>,
     config: TokenizerConfig,
-    last_errors: RefCell<Option<Vec<TokenizationError>>>,
+    /// Names of the `TokenTypeId`s used by an interned tokenizer
+    pub(crate) registry: TokenTypeRegistry,
+    /// Scanners indexed by first byte, built by `optimize`
+    pub(super) dispatch: Option<Dispatch>,
+    /// Applied to the tokens of every tokenize method
+    pipeline: Option<TokenPipeline<K>>,
+    last_errors: Mutex<Option<Vec<TokenizationError>>>,
+    last_line_ending_style: Mutex<Option<LineEndingStyle>>,
 }
 
-impl Tokenizer {
-    pub fn new() -> Self {
+/// Clones share the scanners, so deriving a dialect from a base tokenizer is
+/// cheap; adding, removing or replacing scanners on a clone leaves the
+/// original unchanged. The last errors and line ending style are not copied.
+impl<K: TokenKind> Clone for Tokenizer<K> {
+    fn clone(&self) -> Self {
         Tokenizer {
-            scanners: Vec::new(),
-            config: TokenizerConfig::default(),
-            last_errors: RefCell::new(None),
+            scanners: self.scanners.clone(),
+            config: self.config.clone(),
+            registry: self.registry.clone(),
+            dispatch: self.dispatch.clone(),
+            pipeline: self.pipeline.clone(),
+            last_errors: Mutex::new(None),
+            last_line_ending_style: Mutex::new(None),
         }
     }
+}
+
+impl<K: TokenKind> Default for Tokenizer<K> {
+    fn default() -> Self {
+        Self::new_typed()
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self::new_typed()
+    }
 
     pub fn with_config(config: TokenizerConfig) -> Self {
+        Self::with_typed_config(config)
+    }
+
+    /// Starts a `TokenizerBuilder`, e.g. `Tokenizer::builder().symbol("+").kind("Plus").build()`
+    pub fn builder() -> TokenizerBuilder {
+        TokenizerBuilder::new()
+    }
+}
+
+impl<K: TokenKind> Tokenizer<K> {
+    /// Creates a tokenizer for a user-defined token kind, e.g. `Tokenizer::<MyKind>::new_typed()`
+    pub fn new_typed() -> Self {
+        Self::with_typed_config(TokenizerConfig::default())
+    }
+
+    pub fn with_typed_config(config: TokenizerConfig) -> Self {
         Tokenizer {
             scanners: Vec::new(),
             config,
-            last_errors: RefCell::new(None),
+            registry: TokenTypeRegistry::new(),
+            dispatch: None,
+            pipeline: None,
+            last_errors: Mutex::new(None),
+            last_line_ending_style: Mutex::new(None),
         }
     }
 
@@ -54,56 +120,116 @@ impl Tokenizer {
 
     /// Returns any errors encountered during the last tokenization operation
     pub fn last_errors(&self) -> Option<Vec<TokenizationError>> {
-        self.last_errors.borrow().clone()
+        self.last_errors.lock().unwrap().clone()
     }
 
-    pub fn add_scanner(&mut self, scanner: Box<dyn scanners::Scanner>) {
-        self.scanners.push(ScannerType::Scanner(scanner));
+    /// Returns the line ending style detected during the last tokenization.
+    /// Only recorded when `report_line_ending_style` is enabled; `None` if the
+    /// input had no line breaks.
+    pub fn last_line_ending_style(&self) -> Option<LineEndingStyle> {
+        *self.last_line_ending_style.lock().unwrap()
     }
 
-    pub fn add_scanner_with_priority(&mut self, scanner: Box<dyn scanners::Scanner>, priority: usize) {
-        // Insert scanner at the specified priority (lower index = higher priority)
-        if priority >= self.scanners.len() {
-            self.scanners.push(ScannerType::Scanner(scanner));
-        } else {
-            self.scanners.insert(priority, ScannerType::Scanner(scanner));
-        }
+    pub fn add_scanner(&mut self, scanner: Box<dyn scanners::Scanner<K>>) {
+        self.push_scanner(ScannerType::Scanner(scanner));
+    }
+
+    /// Adds a custom scanner that is tried before scanners with a higher
+    /// `priority` and after those with the same or a lower one. The `add_*`
+    /// methods use priority 0, so a negative priority goes first.
+    pub fn add_scanner_with_priority(&mut self, scanner: Box<dyn scanners::Scanner<K>>, priority: i64) {
+        self.insert_entry(ScannerEntry {
+            scanner: Arc::new(ScannerType::Scanner(scanner)),
+            name: None,
+            priority,
+            enabled: true,
+        });
+    }
+
+    /// Appends an already configured scanner with priority 0, keeping its concrete type
+    fn push_scanner(&mut self, scanner: ScannerType<K>) {
+        self.insert_entry(ScannerEntry {
+            scanner: Arc::new(scanner),
+            name: None,
+            priority: 0,
+            enabled: true,
+        });
+    }
+
+    /// Indexes the scanners by the bytes their matches can start with, so
+    /// each position only tries the scanners that could match there. Results
+    /// are unchanged. Adding a scanner afterwards drops the index; call this
+    /// again once the tokenizer is complete.
+    pub fn optimize(&mut self) {
+        self.dispatch = Some(Dispatch::new(self.enabled_scanners()));
+    }
+
+    /// Whether `optimize` has indexed the current scanners
+    pub fn is_optimized(&self) -> bool {
+        self.dispatch.is_some()
     }
 
     pub fn add_regex_scanner(
         &mut self,
         pattern: &str,
-        token_type: &'static str,
-        sub_token_type: Option<&'static str>,
+        token_type: K,
+        sub_token_type: Option<K>,
     ) {
         let scanner = ScannerType::Regex(RegexScanner::new(pattern, token_type, sub_token_type));
-        self.scanners.push(scanner);
+        self.push_scanner(scanner);
     }
 
-    pub fn add_symbol_scanner(&mut self, symbol: &str, token_type: &'static str, default_scanner: Option<&'static str>) {
+    pub fn add_symbol_scanner(&mut self, symbol: &str, token_type: K, default_scanner: Option<K>) {
         let scanner = ScannerType::Symbol(SymbolScanner::new(symbol, token_type, default_scanner));
-        self.scanners.push(scanner);
+        self.push_scanner(scanner);
     }
 
-    pub fn add_closure_scanner(
+    /// Adds a symbol scanner matching case-insensitively, at word boundaries
+    /// or after normalization, as set by `options`
+    pub fn add_symbol_scanner_with_options(
         &mut self,
-        cb: Box<dyn Fn(&str) -> Result<Option<Token>, TokenizationError>>,
+        symbol: &str,
+        token_type: K,
+        token_sub_type: Option<K>,
+        options: SymbolOptions,
     ) {
+        let scanner = ScannerType::Symbol(SymbolScanner::with_options(symbol, token_type, token_sub_type, options));
+        self.push_scanner(scanner);
+    }
+
+    pub fn add_closure_scanner(&mut self, cb: scanners::closure_scanner::ScanFn<K>) {
         let scanner = ScannerType::Closure(scanners::ClosureScanner::new(cb));
-        self.scanners.push(scanner);
+        self.push_scanner(scanner);
     }
 
-    pub fn add_callback_scanner(&mut self, cb: Box<dyn scanners::CallbackScanner>) {
+    pub fn add_callback_scanner(&mut self, cb: Box<dyn scanners::CallbackScanner<K>>) {
         let scanner = ScannerType::Callback(cb);
-        self.scanners.push(scanner);
+        self.push_scanner(scanner);
+    }
+
+    /// Adds a scanner that sees the previous token, the current mode and the
+    /// position; see `ScanContext`
+    pub fn add_context_scanner(&mut self, scanner: Box<dyn ContextScanner<K>>) {
+        self.push_scanner(ScannerType::Context(scanner));
+    }
+
+    /// Adds a context-aware closure, e.g. `Box::new(|input, context| ...)`
+    pub fn add_context_closure_scanner(&mut self, cb: scanners::context_scanner::ContextScanFn<K>) {
+        self.add_context_scanner(Box::new(cb));
+    }
+
+    /// Whether tokenizing has to track the context of a context-aware scanner
+    pub(crate) fn has_context_scanners(&self) -> bool {
+        self.scanners.iter().any(|entry| matches!(*entry.scanner, ScannerType::Context(_)))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_block_scanner(
         &mut self,
         start_delimiter: &str,
         end_delimiter: &str,
-        token_type: &'static str,
-        token_sub_type: Option<&'static str>,
+        token_type: K,
+        token_sub_type: Option<K>,
         allow_nesting: bool,
         raw_mode: bool,
         include_delimiters: bool,
@@ -117,7 +243,7 @@ impl Tokenizer {
             raw_mode,
             include_delimiters,
         ));
-        self.scanners.push(scanner);
+        self.push_scanner(scanner);
     }
 
     /// Adds an End-of-Line scanner to the tokenizer.
@@ -131,193 +257,85 @@ impl Tokenizer {
     pub fn add_eol_scanner(
         &mut self,
         delimiter: &str,
-        token_type: &'static str,
-        token_sub_type: Option<&'static str>,
+        token_type: K,
+        token_sub_type: Option<K>,
         include_delimiter: bool,
     ) {
-        let scanner = ScannerType::Eol(EolScanner::new(
-            delimiter,
-            token_type,
-            token_sub_type,
-            include_delimiter,
-        ));
-        self.scanners.push(scanner);
+        self.push_scanner(ScannerType::Eol(EolScanner::new(delimiter, token_type, token_sub_type, include_delimiter)));
     }
 
     // Enhanced tokenize method with improved whitespace handling
-    pub fn tokenize(&self, input: &str) -> Result<Vec<Token>, Vec<TokenizationError>> {
-        let mut tokens = Vec::new();
-        let mut errors = Vec::new();
-
-        let mut current_line = 1;
-        let mut current_column = 1;
-        let mut chars = input.char_indices().peekable();
-
-        while let Some((start, next_char)) = chars.peek().copied() {
-            let mut matched = false;
-            let current_input = &input[start..];
-
-            // Try to match complex scanners first (like strings which can contain whitespace)
-            for scanner in &self.scanners {
-                match scanner.scan(current_input) {
-                    Ok(Some(token)) => {
-                        // Special handling for scanners with excluded delimiters or line-based scanners
-                        let mut token_len = token.value.len();
-
-                        // For block scanners excluding delimiters, need to find the actual consumed length
-                        if let ScannerType::Block(block_scanner) = scanner {
-                            if !block_scanner.includes_delimiters() {
-                                // Calculate full length including delimiters
-                                if let Ok(Some(end_pos)) = block_scanner.find_match_end(current_input) {
-                                    token_len = end_pos;
-                                }
-                            }
-                        }
+    pub fn tokenize(&self, input: &str) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        let mut cursor = Cursor::new(self, input, 0, 1, 1);
+        let mut collector = Collector::new(&self.config);
 
-                        // For line scanners excluding delimiters, need to calculate correct length
-                        if let ScannerType::Eol(eol_scanner) = scanner {
-                            if !eol_scanner.includes_delimiter() {
-                                // The full match length is the token length plus the delimiter length
-                                token_len = token_len + eol_scanner.delimiter().len();
-                            }
-                        }
+        while let Some(step) = cursor.next_step() {
+            if !collector.push(step) {
+                break;
+            }
+        }
 
-                        // Track position if configured
-                        let token_with_position = if self.config.track_token_positions {
-                            Token {
-                                line: current_line,
-                                column: current_column,
-                                ..token
-                            }
-                        } else {
-                            token
-                        };
-
-                        tokens.push(token_with_position);
-
-                        // Advance the iterator and update positions
-                        for _ in 0..token_len {
-                            if let Some((_, char)) = chars.next() {
-                                if char == '\n' {
-                                    current_line += 1;
-                                    current_column = 1;
-                                } else {
-                                    current_column += 1;
-                                }
-                            }
-                        }
-                        matched = true;
-                        break;
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        // Preserve the original error and add position information
-                        errors.push(e);
-
-                        if errors.len() >= self.config.error_tolerance_limit {
-                            *self.last_errors.borrow_mut() = Some(errors.clone());
-                            return Err(errors);
-                        }
+        self.record_line_ending_style(input);
+        collector.finish(self)
+    }
 
-                        // If we encounter an error but want to continue, we need to skip this character
-                        if self.config.continue_on_error {
-                            chars.next();
-                            current_column += 1;
-                            matched = true; // Mark as matched so we don't double-count this error
-                            break;
-                        } else {
-                            *self.last_errors.borrow_mut() = Some(errors.clone());
-                            return Err(errors);
-                        }
-                    }
-                }
-            }
+    /// Tokenizes `input` lazily, one token or error at a time, with the
+    /// pipeline (if any) applied as the tokens are produced.
+    ///
+    /// Errors are returned in place of the tokens that could not be scanned;
+    /// the iterator stops where `tokenize` would: after the first error unless
+    /// `continue_on_error` is set, and after a scanner or encoding error that
+    /// brings the errors to `error_tolerance_limit`. `last_errors` is not
+    /// updated.
+    pub fn tokens<'t>(&'t self, input: &'t str) -> impl Iterator<Item = Result<Token<K>, TokenizationError>> + 't {
+        let stream = TokenStream {
+            cursor: Cursor::new(self, input, 0, 1, 1),
+            config: &self.config,
+            errors: 0,
+            done: false,
+        };
+        match &self.pipeline {
+            Some(pipeline) => pipeline.apply_results(stream, self.config.column_mode, self.config.line_endings),
+            None => Box::new(stream),
+        }
+    }
 
-            if !matched {
-                if next_char.is_whitespace() {
-                    if self.config.tokenize_whitespace {
-                        let start_line = current_line;
-                        let start_column = current_column;
-                        let mut whitespace = String::new();
-                        let mut has_newline = false;
-
-                        // Consume whitespace characters one by one
-                        while let Some((_, ch)) = chars.peek() {
-                            if !ch.is_whitespace() {
-                                break;
-                            }
-
-                            // Clone the character to avoid borrowing issues
-                            let current_char = *ch;
-                            whitespace.push(current_char);
-                            has_newline |= current_char == '\n';
-
-                            // Advance the iterator
-                            chars.next();
-
-                            if current_char == '\n' {
-                                current_line += 1;
-                                current_column = 1;
-                            } else {
-                                current_column += 1;
-                            }
-                        }
+    /// Attaches `pipeline`, replacing any pipeline attached before
+    pub fn set_pipeline(&mut self, pipeline: TokenPipeline<K>) -> &mut Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
 
-                        // Create the whitespace token
-                        tokens.push(Token {
-                            token_type: "Whitespace",
-                            token_sub_type: if has_newline { Some("Newline") } else { None },
-                            value: whitespace,
-                            line: start_line,
-                            column: start_column,
-                        });
-                    } else {
-                        // Skip whitespace when not tokenizing it
-                        while let Some((_, ch)) = chars.peek() {
-                            if !ch.is_whitespace() {
-                                break;
-                            }
-
-                            let current_char = *ch;
-                            chars.next();
-
-                            if current_char == '\n' {
-                                current_line += 1;
-                                current_column = 1;
-                            } else {
-                                current_column += 1;
-                            }
-                        }
-                    }
-                } else {
-                    let error = TokenizationError::UnrecognizedToken(
-                        format!("Unrecognized token at line {}, column {}: '{}'",
-                            current_line, current_column, next_char)
-                    );
-                    errors.push(error);
+    /// Detaches the pipeline, returning it
+    pub fn take_pipeline(&mut self) -> Option<TokenPipeline<K>> {
+        self.pipeline.take()
+    }
 
-                    if self.config.continue_on_error {
-                        chars.next();
-                        current_column += 1;
-                    } else {
-                        break;
-                    }
-                }
-            }
-        }
+    /// The pipeline applied to the tokens of every tokenize method, if any
+    pub fn pipeline(&self) -> Option<&TokenPipeline<K>> {
+        self.pipeline.as_ref()
+    }
 
-        if errors.is_empty() {
-            *self.last_errors.borrow_mut() = None;
-            Ok(tokens)
-        } else if self.config.continue_on_error {
-            *self.last_errors.borrow_mut() = Some(errors.clone());
-            Ok(tokens)
-        } else {
-            *self.last_errors.borrow_mut() = Some(errors.clone());
-            Err(errors)
+    /// Tokenizes `input` and groups the tokens between the delimiters of `delimiters`
+    pub fn tokenize_trees(
+        &self,
+        input: &str,
+        delimiters: &DelimiterTable<K>,
+    ) -> Result<Vec<TokenTree<K>>, Vec<TokenizationError>> {
+        self.tokenize(input).map(|tokens| delimiters.group(tokens))
+    }
+
+    pub(crate) fn record_line_ending_style(&self, input: &str) {
+        if self.config.report_line_ending_style {
+            *self.last_line_ending_style.lock().unwrap() = LineEndingStyle::detect(input, self.config.line_endings);
         }
     }
 
+    /// Records the errors of the last tokenization so `last_errors` can report them
+    pub(crate) fn store_errors(&self, errors: Option<Vec<TokenizationError>>) {
+        *self.last_errors.lock().unwrap() = errors;
+    }
+
     /// Sets whether the tokenizer should continue on errors
     pub fn set_continue_on_error(&mut self, value: bool) -> &mut Self {
         self.config.continue_on_error = value;
@@ -342,6 +360,27 @@ impl Tokenizer {
         self
     }
 
+    /// Sets how columns are counted for tokens, whitespace and error positions
+    pub fn set_column_mode(&mut self, mode: ColumnMode) -> &mut Self {
+        self.config.column_mode = mode;
+        self
+    }
+
+    /// Sets which sequences end a line. The policy is shared by position
+    /// tracking, whitespace `Newline` sub-typing and every EOL scanner that
+    /// does not set its own, and is read when scanning, so changing
+    /// `config_mut().line_endings` has the same effect.
+    pub fn set_line_endings(&mut self, line_endings: LineEndings) -> &mut Self {
+        self.config.line_endings = line_endings;
+        self
+    }
+
+    /// Sets whether the document's line ending style is recorded for `last_line_ending_style`
+    pub fn set_report_line_ending_style(&mut self, value: bool) -> &mut Self {
+        self.config.report_line_ending_style = value;
+        self
+    }
+
     /// Updates the tokenizer configuration with the provided values
     pub fn with_options(&mut self,
         continue_on_error: Option<bool>,
@@ -368,3 +407,410 @@ impl Tokenizer {
         self
     }
 }
+
+/// An error produced by a single step of the scanning loop.
+#[derive(Clone)]
+pub(crate) enum StepError {
+    /// A scanner rejected the input at this position
+    Scanner(TokenizationError),
+    /// No scanner matched the character at this position
+    Unrecognized(char),
+    /// The input bytes at this position could not be decoded
+    InvalidEncoding(String),
+}
+
+impl StepError {
+    /// The error reported for a step at `line` and `column`, and whether it
+    /// can end tokenization at the error tolerance limit (see `stops_after`)
+    fn into_error(self, line: usize, column: usize) -> (TokenizationError, bool) {
+        match self {
+            StepError::Scanner(e) => (e, true),
+            StepError::InvalidEncoding(message) => (
+                TokenizationError::InvalidEncoding(format!("{} (line {}, column {})", message, line, column)),
+                true,
+            ),
+            StepError::Unrecognized(ch) => (
+                TokenizationError::UnrecognizedToken(format!(
+                    "Unrecognized token at line {}, column {}: '{}'",
+                    line, column, ch
+                )),
+                false,
+            ),
+        }
+    }
+}
+
+/// Whether tokenization ends after an error, given the number of errors so
+/// far including it. Every error counts towards the tolerance limit, but only
+/// scanner and encoding errors stop at it; unrecognized characters are skipped.
+fn stops_after(config: &TokenizerConfig, errors: usize, limited: bool) -> bool {
+    !config.continue_on_error || (limited && errors >= config.error_tolerance_limit)
+}
+
+/// The outcome of one iteration of the scanning loop, starting at byte offset `start`.
+#[derive(Clone)]
+pub(crate) struct Step<K> {
+    pub(crate) start: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) token: Option<Token<K>>,
+    /// Whether the token's line/column were assigned by the cursor rather than the scanner
+    pub(crate) positioned: bool,
+    pub(crate) error: Option<StepError>,
+}
+
+/// A cursor's position and scanning context, saved by `Cursor::checkpoint`
+#[derive(Debug, Clone)]
+pub(crate) struct Checkpoint<K> {
+    pub(crate) offset: usize,
+    pub(crate) position: PositionTracker,
+    pub(crate) previous: Option<Token<K>>,
+    pub(crate) modes: Vec<&'static str>,
+}
+
+/// Walks the input one scanner match (or whitespace run, or error) at a time.
+///
+/// The cursor moves over byte offsets (as reported by `Scanner::consumed_len`)
+/// and keeps line/column in step by measuring each consumed slice, so tokens
+/// containing multibyte characters never over- or under-consume the input. A cursor can be started at any
+/// offset with any line/column, which lets the parallel tokenizer run it over
+/// chunks of the input independently.
+pub(crate) struct Cursor<'t, 'i, K: TokenKind> {
+    tokenizer: &'t Tokenizer<K>,
+    input: &'i str,
+    offset: usize,
+    position: PositionTracker,
+    decoded: Option<&'i DecodedInput>,
+    /// Whether any scanner needs a `ScanContext`, so the previous token is worth keeping
+    contextual: bool,
+    /// The last non-whitespace token, for context-aware scanners
+    previous: Option<Token<K>>,
+    modes: Vec<&'static str>,
+}
+
+impl<'t, 'i, K: TokenKind> Cursor<'t, 'i, K> {
+    pub(crate) fn new(tokenizer: &'t Tokenizer<K>, input: &'i str, offset: usize, line: usize, column: usize) -> Self {
+        Self {
+            tokenizer,
+            input,
+            offset,
+            position: {
+                // Resuming between "\r" and "\n" must not count a second line break,
+                // nor a combining mark at `offset` a second cluster
+                PositionTracker::at(line, column, tokenizer.config.column_mode)
+                    .with_line_endings(tokenizer.config.line_endings)
+                    .after(&input[..offset])
+            },
+            decoded: None,
+            contextual: tokenizer.has_context_scanners(),
+            previous: None,
+            modes: Vec::new(),
+        }
+    }
+
+    /// Resumes from where `checkpoint` was taken, as if the input before it had just been scanned
+    pub(crate) fn resume(tokenizer: &'t Tokenizer<K>, input: &'i str, checkpoint: &Checkpoint<K>) -> Self {
+        Self {
+            tokenizer,
+            input,
+            offset: checkpoint.offset,
+            position: checkpoint.position.clone(),
+            decoded: None,
+            contextual: tokenizer.has_context_scanners(),
+            previous: checkpoint.previous.clone(),
+            modes: checkpoint.modes.clone(),
+        }
+    }
+
+    /// Everything needed to resume scanning from the current position
+    pub(crate) fn checkpoint(&self) -> Checkpoint<K> {
+        Checkpoint {
+            offset: self.offset,
+            position: self.position.clone(),
+            previous: self.previous.clone(),
+            modes: self.modes.clone(),
+        }
+    }
+
+    /// Treats the undecodable sequences of `decoded` as errors and hard token boundaries
+    pub(crate) fn with_invalid(mut self, decoded: &'i DecodedInput) -> Self {
+        self.decoded = Some(decoded);
+        self
+    }
+
+    /// Byte offset of the next character to be scanned
+    pub(crate) fn position(&self) -> usize {
+        self.offset
+    }
+
+    pub(crate) fn line(&self) -> usize {
+        self.position.line
+    }
+
+    pub(crate) fn column(&self) -> usize {
+        self.position.column
+    }
+
+    /// Consumes `len` bytes (rounded up to a character boundary) and moves the line/column over them
+    fn advance(&mut self, len: usize) {
+        let mut end = (self.offset + len).min(self.input.len());
+        while !self.input.is_char_boundary(end) {
+            end += 1;
+        }
+        self.position.advance(&self.input[self.offset..end]);
+        self.offset = end;
+    }
+
+    /// Turns the delimiter offsets of a scanner error at `start` into input positions
+    fn locate(&self, mut error: TokenizationError, start: usize) -> TokenizationError {
+        if let TokenizationError::MismatchedBlockDelimiter(open, close) = &mut error {
+            for location in [open, close] {
+                let mut position = self.position.clone();
+                position.advance(&self.input[start..start + location.offset]);
+                location.offset += start;
+                location.line = position.line;
+                location.column = position.column;
+            }
+        }
+        error
+    }
+
+    /// Runs one iteration of the scanning loop, or returns `None` at the end of input
+    pub(crate) fn next_step(&mut self) -> Option<Step<K>> {
+        self.next_step_with(None)
+    }
+
+    /// Like `next_step`, handing `state` to context-aware scanners
+    pub(crate) fn next_step_with(&mut self, mut state: Option<&mut dyn Any>) -> Option<Step<K>> {
+        let start = self.offset;
+        if start >= self.input.len() {
+            return None;
+        }
+
+        // Scanners never see past an undecodable sequence
+        let mut end = self.input.len();
+        if let Some(decoded) = self.decoded {
+            let next = decoded.invalid.partition_point(|sequence| sequence.offset < start);
+            if let Some(sequence) = decoded.invalid.get(next) {
+                if sequence.offset == start {
+                    let (line, column) = (self.position.line, self.position.column);
+                    self.advance(char::REPLACEMENT_CHARACTER.len_utf8());
+                    return Some(Step {
+                        start,
+                        line,
+                        column,
+                        token: None,
+                        positioned: false,
+                        error: Some(StepError::InvalidEncoding(decoded.describe(sequence))),
+                    });
+                }
+                end = sequence.offset;
+            }
+        }
+
+        let current_input = &self.input[start..end];
+        let next_char = current_input.chars().next()?;
+        let config = &self.tokenizer.config;
+        let mut step = Step {
+            start,
+            line: self.position.line,
+            column: self.position.column,
+            token: None,
+            positioned: false,
+            error: None,
+        };
+
+        // Try to match complex scanners first (like strings which can contain whitespace)
+        let scanners = &self.tokenizer.scanners;
+        let candidates = match &self.tokenizer.dispatch {
+            Some(dispatch) => Candidates::Indexed(dispatch.candidates(current_input.as_bytes()[0]).iter()),
+            None => Candidates::All(0..scanners.len()),
+        };
+        for index in candidates {
+            let entry = &scanners[index];
+            if !entry.enabled {
+                continue;
+            }
+            let scanner = &entry.scanner;
+            let result = match &**scanner {
+                ScannerType::Context(context_scanner) => {
+                    let mut context = ScanContext::new(
+                        self.previous.as_ref(),
+                        self.modes.clone(),
+                        start,
+                        self.position.line,
+                        self.position.column,
+                        state.as_deref_mut(),
+                    );
+                    let result = context_scanner.scan(current_input, &mut context);
+                    if let Ok(Some(_)) = result {
+                        self.modes = context.into_modes();
+                    }
+                    result
+                }
+                ScannerType::Eol(eol_scanner) => eol_scanner.scan_with_line_endings(current_input, config.line_endings),
+                scanner => scanner.scan(current_input),
+            };
+            match result {
+                Ok(Some(token)) => {
+                    let token_len = match &**scanner {
+                        ScannerType::Eol(eol_scanner) => {
+                            eol_scanner.consumed_len_with_line_endings(current_input, &token, config.line_endings)
+                        }
+                        scanner => scanner.consumed_len(current_input, &token),
+                    };
+
+                    // Track position if configured
+                    step.positioned = config.track_token_positions;
+                    step.token = Some(if config.track_token_positions {
+                        Token {
+                            line: self.position.line,
+                            column: self.position.column,
+                            ..token
+                        }
+                    } else {
+                        token
+                    });
+                    if self.contextual {
+                        self.previous = step.token.clone().filter(|token| token.token_type != K::whitespace());
+                    }
+
+                    self.advance(token_len);
+                    return Some(step);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let e = self.locate(e, start);
+                    // Skip the offending character so a tolerant caller can carry on
+                    self.advance(next_char.len_utf8());
+                    step.error = Some(StepError::Scanner(e));
+                    return Some(step);
+                }
+            }
+        }
+
+        if next_char.is_whitespace() {
+            // Consume the whole run of whitespace characters
+            let run = current_input
+                .find(|ch: char| !ch.is_whitespace())
+                .unwrap_or(current_input.len());
+            let whitespace = &current_input[..run];
+            self.advance(run);
+
+            if config.tokenize_whitespace {
+                step.positioned = true;
+                let token_sub_type = if config.line_endings.contains(whitespace) { Some(K::newline()) } else { None };
+                step.token = Some(Token::new(K::whitespace(), token_sub_type, whitespace.to_string(), step.line, step.column));
+            }
+        } else {
+            self.advance(next_char.len_utf8());
+            step.error = Some(StepError::Unrecognized(next_char));
+        }
+
+        Some(step)
+    }
+}
+
+/// The scanner indices a step tries, in priority order
+enum Candidates<'d> {
+    All(std::ops::Range<usize>),
+    Indexed(std::slice::Iter<'d, usize>),
+}
+
+impl Iterator for Candidates<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self {
+            Candidates::All(range) => range.next(),
+            Candidates::Indexed(indices) => indices.next().copied(),
+        }
+    }
+}
+
+/// The iterator of `Tokenizer::tokens`, applying the error policy as it goes
+struct TokenStream<'t, K: TokenKind> {
+    cursor: Cursor<'t, 't, K>,
+    config: &'t TokenizerConfig,
+    /// Errors reported so far
+    errors: usize,
+    done: bool,
+}
+
+impl<K: TokenKind> Iterator for TokenStream<'_, K> {
+    type Item = Result<Token<K>, TokenizationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let step = self.cursor.next_step()?;
+            if let Some(token) = step.token {
+                return Some(Ok(token));
+            }
+            let Some(error) = step.error else { continue };
+            let (error, limited) = error.into_error(step.line, step.column);
+            self.errors += 1;
+            self.done = stops_after(self.config, self.errors, limited);
+            return Some(Err(error));
+        }
+        None
+    }
+}
+
+/// Accumulates steps into the final token list, applying the error policy.
+pub(crate) struct Collector<'c, K> {
+    config: &'c TokenizerConfig,
+    tokens: Vec<Token<K>>,
+    errors: Vec<TokenizationError>,
+    failed: bool,
+}
+
+impl<'c, K: TokenKind> Collector<'c, K> {
+    pub(crate) fn new(config: &'c TokenizerConfig) -> Self {
+        Self {
+            config,
+            tokens: Vec::new(),
+            errors: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Adds a step's output; returns `false` once tokenization must stop
+    pub(crate) fn push(&mut self, step: Step<K>) -> bool {
+        if let Some(token) = step.token {
+            self.tokens.push(token);
+        }
+
+        let Some(error) = step.error else {
+            return true;
+        };
+        let (error, limited) = error.into_error(step.line, step.column);
+        // Preserve the original error
+        self.errors.push(error);
+        if stops_after(self.config, self.errors.len(), limited) {
+            self.failed = true;
+            return false;
+        }
+        true
+    }
+
+    pub(crate) fn finish(mut self, tokenizer: &Tokenizer<K>) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        if let Some(pipeline) = &tokenizer.pipeline {
+            self.tokens = pipeline.apply(std::mem::take(&mut self.tokens)).collect();
+        }
+        self.finish_unpiped(tokenizer)
+    }
+
+    /// Like `finish`, without applying the tokenizer's pipeline
+    pub(crate) fn finish_unpiped(self, tokenizer: &Tokenizer<K>) -> Result<Vec<Token<K>>, Vec<TokenizationError>> {
+        if self.errors.is_empty() {
+            tokenizer.store_errors(None);
+            Ok(self.tokens)
+        } else if self.config.continue_on_error && !self.failed {
+            tokenizer.store_errors(Some(self.errors));
+            Ok(self.tokens)
+        } else {
+            tokenizer.store_errors(Some(self.errors.clone()));
+            Err(self.errors)
+        }
+    }
+}
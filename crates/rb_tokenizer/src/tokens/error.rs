@@ -1,9 +1,24 @@
 use std::{error::Error, fmt};
 
+/// Where a block delimiter was found. Scanners report `offset` relative to
+/// their input; the tokenizer makes it absolute and fills in the line and column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelimiterLocation {
+    pub delimiter: String,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum TokenizationError {
     UnrecognizedToken(String),
     UnmatchedBlockDelimiter(String, String),
+    /// A closing delimiter (second) that does not match the innermost open delimiter (first)
+    MismatchedBlockDelimiter(DelimiterLocation, DelimiterLocation),
+    InvalidEncoding(String),
+    /// A named escape such as `&foo;` that resolves to nothing
+    UnknownEntity(String),
     // Define additional error types as needed.
 }
 
@@ -15,6 +30,19 @@ impl fmt::Display for TokenizationError {
             },
             TokenizationError::UnmatchedBlockDelimiter(start, end) => {
                 write!(f, "Unmatched block delimiter: start '{}' missing matching end '{}'", start, end)
+            },
+            TokenizationError::MismatchedBlockDelimiter(open, close) => {
+                write!(
+                    f,
+                    "Mismatched block delimiter: '{}' at line {}, column {} does not close '{}' opened at line {}, column {}",
+                    close.delimiter, close.line, close.column, open.delimiter, open.line, open.column
+                )
+            },
+            TokenizationError::InvalidEncoding(message) => {
+                write!(f, "Invalid encoding: {}", message)
+            },
+            TokenizationError::UnknownEntity(entity) => {
+                write!(f, "Unknown entity: '{}'", entity)
             }
         }
     }
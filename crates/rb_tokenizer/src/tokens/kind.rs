@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The type used for `Token::token_type` and `Token::token_sub_type`.
+///
+/// `&'static str` is the default kind, so existing string-typed grammars keep
+/// working; a user `enum TokenKind` can be used instead to match on kinds
+/// without comparing strings.
+pub trait TokenKind: Copy + Eq + Hash + Debug + Send + Sync + 'static {
+    /// Kind given to whitespace tokens when `tokenize_whitespace` is enabled
+    fn whitespace() -> Self;
+
+    /// Sub-kind given to whitespace tokens that contain a line break
+    fn newline() -> Self;
+
+    /// Human-readable name used by the debug utilities
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("{:?}", self))
+    }
+}
+
+impl TokenKind for &'static str {
+    fn whitespace() -> Self {
+        "Whitespace"
+    }
+
+    fn newline() -> Self {
+        "Newline"
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(self)
+    }
+}
@@ -1,5 +1,11 @@
 pub mod error;
+pub mod kind;
+pub mod registry;
 pub mod token;
+pub mod tree;
 
-pub use error::TokenizationError;
-pub use token::Token;
+pub use error::{DelimiterLocation, TokenizationError};
+pub use kind::TokenKind;
+pub use registry::{TokenTypeId, TokenTypeRegistry};
+pub use token::{Token, TokenFlags, TokenValue};
+pub use tree::{DelimiterTable, TokenTree, TreeCursor};
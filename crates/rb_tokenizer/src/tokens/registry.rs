@@ -0,0 +1,114 @@
+use super::TokenKind;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Compact id of an interned token type or sub-type name.
+///
+/// Ids are only meaningful together with the `TokenTypeRegistry` that issued
+/// them. `WHITESPACE` and `NEWLINE` are reserved in every registry so whitespace
+/// tokens get the same ids everywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TokenTypeId(u32);
+
+impl TokenTypeId {
+    pub const WHITESPACE: TokenTypeId = TokenTypeId(0);
+    pub const NEWLINE: TokenTypeId = TokenTypeId(1);
+
+    /// Position of the name in its registry
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl TokenKind for TokenTypeId {
+    fn whitespace() -> Self {
+        TokenTypeId::WHITESPACE
+    }
+
+    fn newline() -> Self {
+        TokenTypeId::NEWLINE
+    }
+
+    /// Only the reserved ids can be named without their registry;
+    /// use `TokenTypeRegistry::resolve` for the others.
+    fn name(&self) -> Cow<'static, str> {
+        match *self {
+            TokenTypeId::WHITESPACE => Cow::Borrowed("Whitespace"),
+            TokenTypeId::NEWLINE => Cow::Borrowed("Newline"),
+            TokenTypeId(id) => Cow::Owned(format!("#{}", id)),
+        }
+    }
+}
+
+/// Interns token type and sub-type names for grammars defined at runtime.
+///
+/// Names are registered once, tokens carry the resulting `TokenTypeId`s, and
+/// the registry resolves ids back to names for printing and error messages.
+#[derive(Debug, Clone)]
+pub struct TokenTypeRegistry {
+    names: Vec<Box<str>>,
+    ids: HashMap<Box<str>, TokenTypeId>,
+}
+
+impl Default for TokenTypeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenTypeRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            names: Vec::new(),
+            ids: HashMap::new(),
+        };
+        registry.intern("Whitespace");
+        registry.intern("Newline");
+        registry
+    }
+
+    /// Returns the id for `name`, registering it if it is new
+    pub fn intern(&mut self, name: &str) -> TokenTypeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = TokenTypeId(u32::try_from(self.names.len()).expect("too many token types"));
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        id
+    }
+
+    /// Returns the id of an already registered name
+    pub fn get(&self, name: &str) -> Option<TokenTypeId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the name of `id`, or `None` if it was not issued by this registry
+    pub fn name(&self, id: TokenTypeId) -> Option<&str> {
+        self.names.get(id.index()).map(|name| &**name)
+    }
+
+    /// Returns the name of `id`, falling back to `#<id>` for unknown ids
+    pub fn resolve(&self, id: TokenTypeId) -> Cow<'_, str> {
+        match self.name(id) {
+            Some(name) => Cow::Borrowed(name),
+            None => id.name(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterates over all registered names in id order
+    pub fn iter(&self) -> impl Iterator<Item = (TokenTypeId, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (TokenTypeId(i as u32), &**name))
+    }
+}
@@ -1,9 +1,246 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
 /// `Token` struct represents a token in a programming language.
+///
+/// `K` is the kind type used for `token_type` and `token_sub_type`
+/// (see `TokenKind`); it defaults to `&'static str`.
 #[derive(Debug, PartialEq, Clone)]
-pub struct Token {
-    pub token_type: &'static str,
-    pub token_sub_type: Option<&'static str>,
+pub struct Token<K = &'static str> {
+    pub token_type: K,
+    pub token_sub_type: Option<K>,
     pub value: String,
     pub line: usize,
     pub column: usize,
+    /// The value decoded by the scanner, e.g. the number a numeric literal
+    /// stands for or a string with its escapes resolved
+    pub literal: Option<TokenValue>,
+    /// Facts the scanner noted about the token, such as `TokenFlags::UNTERMINATED`
+    pub flags: TokenFlags,
+    /// Text of the regex groups the scanner kept as metadata, by group name
+    /// or number, in the order of the groups
+    pub captures: Vec<(String, String)>,
+}
+
+impl<K> Token<K> {
+    /// Creates a token with no literal value, flags or captures
+    pub fn new(token_type: K, token_sub_type: Option<K>, value: String, line: usize, column: usize) -> Self {
+        Self {
+            token_type,
+            token_sub_type,
+            value,
+            line,
+            column,
+            literal: None,
+            flags: TokenFlags::default(),
+            captures: Vec::new(),
+        }
+    }
+
+    /// Sets the literal value of the token
+    pub fn with_literal(mut self, literal: impl Into<TokenValue>) -> Self {
+        self.literal = Some(literal.into());
+        self
+    }
+
+    /// Adds `flag` to the token's flags
+    pub fn with_flag(mut self, flag: impl Into<Cow<'static, str>>) -> Self {
+        self.flags.insert(flag);
+        self
+    }
+
+    /// Returns whether the token has `flag`
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// Returns the text captured by the group `name` (or number), if the scanner kept it
+    pub fn capture(&self, name: &str) -> Option<&str> {
+        self.captures.iter().find(|(group, _)| group == name).map(|(_, text)| text.as_str())
+    }
+}
+
+/// A typed value decoded from a token's text, so consumers do not have to
+/// parse `Token::value` again.
+///
+/// `Custom` holds any other value; it is shared between clones of the token,
+/// and two custom values are only equal when they are the same allocation.
+#[derive(Clone)]
+pub enum TokenValue {
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Char(char),
+    Custom(Arc<dyn Any + Send + Sync>),
+}
+
+impl TokenValue {
+    /// Wraps a value of any other type
+    pub fn custom(value: impl Any + Send + Sync) -> Self {
+        TokenValue::Custom(Arc::new(value))
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            TokenValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            TokenValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TokenValue::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TokenValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(&self) -> Option<char> {
+        match self {
+            TokenValue::Char(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the custom value if it is a `T`
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            TokenValue::Custom(value) => value.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for TokenValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenValue::Int(value) => f.debug_tuple("Int").field(value).finish(),
+            TokenValue::Float(value) => f.debug_tuple("Float").field(value).finish(),
+            TokenValue::Str(value) => f.debug_tuple("Str").field(value).finish(),
+            TokenValue::Bool(value) => f.debug_tuple("Bool").field(value).finish(),
+            TokenValue::Char(value) => f.debug_tuple("Char").field(value).finish(),
+            TokenValue::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for TokenValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TokenValue::Int(a), TokenValue::Int(b)) => a == b,
+            (TokenValue::Float(a), TokenValue::Float(b)) => a == b,
+            (TokenValue::Str(a), TokenValue::Str(b)) => a == b,
+            (TokenValue::Bool(a), TokenValue::Bool(b)) => a == b,
+            (TokenValue::Char(a), TokenValue::Char(b)) => a == b,
+            (TokenValue::Custom(a), TokenValue::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl From<i128> for TokenValue {
+    fn from(value: i128) -> Self {
+        TokenValue::Int(value)
+    }
+}
+
+impl From<f64> for TokenValue {
+    fn from(value: f64) -> Self {
+        TokenValue::Float(value)
+    }
+}
+
+impl From<String> for TokenValue {
+    fn from(value: String) -> Self {
+        TokenValue::Str(value)
+    }
+}
+
+impl From<&str> for TokenValue {
+    fn from(value: &str) -> Self {
+        TokenValue::Str(value.to_string())
+    }
+}
+
+impl From<bool> for TokenValue {
+    fn from(value: bool) -> Self {
+        TokenValue::Bool(value)
+    }
+}
+
+impl From<char> for TokenValue {
+    fn from(value: char) -> Self {
+        TokenValue::Char(value)
+    }
+}
+
+/// Free-form names describing a token, kept in the order they were added
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenFlags(Vec<Cow<'static, str>>);
+
+impl TokenFlags {
+    /// A block whose end delimiter was missing, scanned to the end of the input
+    pub const UNTERMINATED: &'static str = "unterminated";
+    /// A block whose content contains escape sequences
+    pub const HAD_ESCAPES: &'static str = "had_escapes";
+
+    /// Adds `flag`, unless it is already set
+    pub fn insert(&mut self, flag: impl Into<Cow<'static, str>>) {
+        let flag = flag.into();
+        if !self.contains(&flag) {
+            self.0.push(flag);
+        }
+    }
+
+    /// Removes `flag`, returning whether it was set
+    pub fn remove(&mut self, flag: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|set| set != flag);
+        self.0.len() != before
+    }
+
+    pub fn contains(&self, flag: &str) -> bool {
+        self.0.iter().any(|set| set == flag)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|flag| flag.as_ref())
+    }
+}
+
+impl<F: Into<Cow<'static, str>>> Extend<F> for TokenFlags {
+    fn extend<I: IntoIterator<Item = F>>(&mut self, flags: I) {
+        for flag in flags {
+            self.insert(flag);
+        }
+    }
+}
+
+impl IntoIterator for TokenFlags {
+    type Item = Cow<'static, str>;
+    type IntoIter = std::vec::IntoIter<Cow<'static, str>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
 }
@@ -0,0 +1,290 @@
+use super::{Token, TokenKind};
+
+/// An opening and closing delimiter, matched by token value and optionally
+/// by token type
+#[derive(Debug, Clone)]
+struct DelimiterPair<K> {
+    open: String,
+    close: String,
+    token_type: Option<K>,
+}
+
+impl<K: TokenKind> DelimiterPair<K> {
+    fn opens(&self, token: &Token<K>) -> bool {
+        token.value == self.open && self.token_type.is_none_or(|token_type| token_type == token.token_type)
+    }
+
+    fn closes(&self, token: &Token<K>) -> bool {
+        token.value == self.close && self.token_type.is_none_or(|token_type| token_type == token.token_type)
+    }
+}
+
+/// The delimiters `DelimiterTable::group` turns into groups.
+///
+/// ```
+/// use rb_tokenizer::tokens::DelimiterTable;
+///
+/// let table = DelimiterTable::new().pair("(", ")").typed_pair("Tag", "<", ">");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DelimiterTable<K = &'static str> {
+    pairs: Vec<DelimiterPair<K>>,
+}
+
+impl<K: TokenKind> Default for DelimiterTable<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: TokenKind> DelimiterTable<K> {
+    pub fn new() -> Self {
+        Self { pairs: Vec::new() }
+    }
+
+    /// `(`/`)`, `[`/`]` and `{`/`}` of any token type
+    pub fn brackets() -> Self {
+        Self::new().pair("(", ")").pair("[", "]").pair("{", "}")
+    }
+
+    /// Groups tokens between an `open` and a `close` token of any type
+    pub fn pair(mut self, open: &str, close: &str) -> Self {
+        self.pairs.push(DelimiterPair {
+            open: open.to_string(),
+            close: close.to_string(),
+            token_type: None,
+        });
+        self
+    }
+
+    /// Groups tokens between `open` and `close` tokens of `token_type` only,
+    /// e.g. so a `<` operator does not open a group
+    pub fn typed_pair(mut self, token_type: K, open: &str, close: &str) -> Self {
+        self.pairs.push(DelimiterPair {
+            open: open.to_string(),
+            close: close.to_string(),
+            token_type: Some(token_type),
+        });
+        self
+    }
+
+    /// Groups a flat token list into trees.
+    ///
+    /// A closer matching an outer group instead of the innermost one closes
+    /// the outer group, leaving the groups inside it `Unclosed`; a closer
+    /// matching no open group becomes `Unexpected`. A delimiter that both
+    /// opens and closes a pair, like `|` in `pair("|", "|")`, closes the
+    /// innermost open group of that pair and only opens a group when there is
+    /// none. Every token ends up in the result exactly once, in input order.
+    pub fn group(&self, tokens: impl IntoIterator<Item = Token<K>>) -> Vec<TokenTree<K>> {
+        let mut open: Vec<OpenGroup<K>> = Vec::new();
+        let mut top = Vec::new();
+
+        for token in tokens {
+            let depth = open.iter().rposition(|group| self.pairs[group.pair].closes(&token));
+            let closes_own_pair = depth.is_some_and(|depth| self.pairs[open[depth].pair].opens(&token));
+            if !closes_own_pair {
+                if let Some(pair) = self.pairs.iter().position(|pair| pair.opens(&token)) {
+                    open.push(OpenGroup {
+                        open: token,
+                        pair,
+                        children: Vec::new(),
+                    });
+                    continue;
+                }
+            }
+            match depth {
+                Some(depth) => {
+                    while open.len() > depth + 1 {
+                        let group = open.pop().unwrap();
+                        push(&mut open, &mut top, group.unclosed());
+                    }
+                    let group = open.pop().unwrap();
+                    let group = TokenTree::Group {
+                        open: group.open,
+                        close: token,
+                        children: group.children,
+                    };
+                    push(&mut open, &mut top, group);
+                }
+                None if self.pairs.iter().any(|pair| pair.closes(&token)) => {
+                    push(&mut open, &mut top, TokenTree::Unexpected(token));
+                }
+                None => push(&mut open, &mut top, TokenTree::Token(token)),
+            }
+        }
+
+        while let Some(group) = open.pop() {
+            push(&mut open, &mut top, group.unclosed());
+        }
+        top
+    }
+}
+
+/// A group whose closing delimiter has not been seen yet
+struct OpenGroup<K> {
+    open: Token<K>,
+    /// Index of the delimiter pair that opened the group
+    pair: usize,
+    children: Vec<TokenTree<K>>,
+}
+
+impl<K> OpenGroup<K> {
+    fn unclosed(self) -> TokenTree<K> {
+        TokenTree::Unclosed {
+            open: self.open,
+            children: self.children,
+        }
+    }
+}
+
+/// Adds `tree` to the innermost open group, or the top level
+fn push<K>(open: &mut [OpenGroup<K>], top: &mut Vec<TokenTree<K>>, tree: TokenTree<K>) {
+    match open.last_mut() {
+        Some(group) => group.children.push(tree),
+        None => top.push(tree),
+    }
+}
+
+/// A token or a group of tokens between paired delimiters, built by
+/// `DelimiterTable::group`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenTree<K = &'static str> {
+    Token(Token<K>),
+    Group {
+        open: Token<K>,
+        close: Token<K>,
+        children: Vec<TokenTree<K>>,
+    },
+    /// An opening delimiter that is never closed, with the trees that followed it
+    Unclosed {
+        open: Token<K>,
+        children: Vec<TokenTree<K>>,
+    },
+    /// A closing delimiter without a matching opening delimiter
+    Unexpected(Token<K>),
+}
+
+impl<K> TokenTree<K> {
+    /// The trees inside a group, or an empty slice for single tokens
+    pub fn children(&self) -> &[TokenTree<K>] {
+        match self {
+            TokenTree::Group { children, .. } | TokenTree::Unclosed { children, .. } => children,
+            TokenTree::Token(_) | TokenTree::Unexpected(_) => &[],
+        }
+    }
+
+    /// The token itself, or the opening delimiter of a group
+    pub fn first_token(&self) -> &Token<K> {
+        match self {
+            TokenTree::Token(token) | TokenTree::Unexpected(token) => token,
+            TokenTree::Group { open, .. } | TokenTree::Unclosed { open, .. } => open,
+        }
+    }
+
+    /// Whether this tree or any tree inside it is `Unclosed` or `Unexpected`
+    pub fn has_errors(&self) -> bool {
+        match self {
+            TokenTree::Token(_) => false,
+            TokenTree::Group { children, .. } => children.iter().any(TokenTree::has_errors),
+            TokenTree::Unclosed { .. } | TokenTree::Unexpected(_) => true,
+        }
+    }
+
+    /// Every token of the tree, delimiters included, in input order
+    pub fn tokens(&self) -> Tokens<'_, K> {
+        Tokens {
+            stack: vec![Frame::Tree(self)],
+        }
+    }
+}
+
+/// Depth-first iterator over the tokens of a `TokenTree`
+pub struct Tokens<'t, K> {
+    stack: Vec<Frame<'t, K>>,
+}
+
+enum Frame<'t, K> {
+    Tree(&'t TokenTree<K>),
+    Token(&'t Token<K>),
+}
+
+impl<'t, K> Iterator for Tokens<'t, K> {
+    type Item = &'t Token<K>;
+
+    fn next(&mut self) -> Option<&'t Token<K>> {
+        let tree = match self.stack.pop()? {
+            Frame::Token(token) => return Some(token),
+            Frame::Tree(tree) => tree,
+        };
+        match tree {
+            TokenTree::Token(token) | TokenTree::Unexpected(token) => Some(token),
+            TokenTree::Group { open, close, children } => {
+                self.stack.push(Frame::Token(close));
+                self.stack.extend(children.iter().rev().map(Frame::Tree));
+                Some(open)
+            }
+            TokenTree::Unclosed { open, children } => {
+                self.stack.extend(children.iter().rev().map(Frame::Tree));
+                Some(open)
+            }
+        }
+    }
+}
+
+/// A position in a sequence of sibling trees, for parsers walking a token
+/// tree. Cursors are cheap to copy, so a parser can save one and backtrack.
+pub struct TreeCursor<'t, K = &'static str> {
+    trees: &'t [TokenTree<K>],
+    position: usize,
+}
+
+impl<K> Clone for TreeCursor<'_, K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K> Copy for TreeCursor<'_, K> {}
+
+impl<'t, K> TreeCursor<'t, K> {
+    pub fn new(trees: &'t [TokenTree<K>]) -> Self {
+        Self { trees, position: 0 }
+    }
+
+    /// The tree at the cursor, or `None` at the end
+    pub fn current(&self) -> Option<&'t TokenTree<K>> {
+        self.trees.get(self.position)
+    }
+
+    /// The tree `n` trees after the cursor
+    pub fn peek(&self, n: usize) -> Option<&'t TokenTree<K>> {
+        self.trees.get(self.position + n)
+    }
+
+    /// Returns the tree at the cursor and moves past it
+    pub fn bump(&mut self) -> Option<&'t TokenTree<K>> {
+        let tree = self.current()?;
+        self.position += 1;
+        Some(tree)
+    }
+
+    /// A cursor over the children of the group at the cursor, if it is one
+    pub fn enter(&self) -> Option<TreeCursor<'t, K>> {
+        match self.current()? {
+            TokenTree::Group { children, .. } | TokenTree::Unclosed { children, .. } => {
+                Some(TreeCursor::new(children))
+            }
+            TokenTree::Token(_) | TokenTree::Unexpected(_) => None,
+        }
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position >= self.trees.len()
+    }
+
+    /// The trees from the cursor on
+    pub fn remaining(&self) -> &'t [TokenTree<K>] {
+        &self.trees[self.position.min(self.trees.len())..]
+    }
+}
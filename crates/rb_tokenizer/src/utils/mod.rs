@@ -1,8 +1,15 @@
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenKind};
+use std::borrow::Cow;
 use std::fmt::Write;
 
 /// Pretty prints a list of tokens in a human-readable format
-pub fn pretty_print_tokens(tokens: &[Token]) -> String {
+pub fn pretty_print_tokens<K: TokenKind>(tokens: &[Token<K>]) -> String {
+    pretty_print_tokens_with(tokens, |kind| kind.name())
+}
+
+/// Like `pretty_print_tokens`, with kind names looked up through `name`
+/// (e.g. `|id| registry.resolve(id)` for interned token types)
+pub fn pretty_print_tokens_with<'n, K: TokenKind>(tokens: &[Token<K>], name: impl Fn(K) -> Cow<'n, str>) -> String {
     let mut output = String::new();
     writeln!(&mut output, "Tokens:").unwrap();
     for (i, token) in tokens.iter().enumerate() {
@@ -10,8 +17,8 @@ pub fn pretty_print_tokens(tokens: &[Token]) -> String {
             &mut output,
             "> {:3}: {:15} {:20} '{}' (line {}, col {}) \n\n",
             i,
-            token.token_type,
-            token.token_sub_type.as_deref().unwrap_or(""),
+            name(token.token_type),
+            token.token_sub_type.map(&name).unwrap_or_default(),
             token.value.replace('\n', "\\n"),
             token.line,
             token.column
@@ -22,17 +29,22 @@ pub fn pretty_print_tokens(tokens: &[Token]) -> String {
 }
 
 /// Returns a compact single-line representation of a token for debug output
-pub fn token_summary(token: &Token) -> String {
+pub fn token_summary<K: TokenKind>(token: &Token<K>) -> String {
+    token_summary_with(token, |kind| kind.name())
+}
+
+/// Like `token_summary`, with kind names looked up through `name`
+pub fn token_summary_with<'n, K: TokenKind>(token: &Token<K>, name: impl Fn(K) -> Cow<'n, str>) -> String {
     format!(
         "{}:{} '{}'",
-        token.token_type,
-        token.token_sub_type.as_deref().unwrap_or(""),
+        name(token.token_type),
+        token.token_sub_type.map(&name).unwrap_or_default(),
         token.value.replace('\n', "\\n")
     )
 }
 
 /// Creates a table-like view of tokens for comparing expected vs actual results
-pub fn compare_tokens(expected: &[Token], actual: &[Token]) -> String {
+pub fn compare_tokens<K: TokenKind>(expected: &[Token<K>], actual: &[Token<K>]) -> String {
     let mut output = String::new();
     writeln!(&mut output, "Token Comparison:").unwrap();
     writeln!(&mut output, "{:3} {:^40} {:^40}", "", "Expected", "Actual").unwrap();
@@ -60,7 +72,7 @@ pub fn compare_tokens(expected: &[Token], actual: &[Token]) -> String {
 }
 
 /// Generates a visual representation of token positions in the input text
-pub fn visualize_token_positions(input: &str, tokens: &[Token]) -> String {
+pub fn visualize_token_positions<K: TokenKind>(input: &str, tokens: &[Token<K>]) -> String {
     let mut output = String::new();
     let mut lines: Vec<String> = input.lines().map(|s| s.to_string()).collect();
     if !input.ends_with('\n') {
@@ -97,7 +109,7 @@ pub fn visualize_token_positions(input: &str, tokens: &[Token]) -> String {
 }
 
 /// Provides detailed token analysis including statistics and potential issues
-pub fn analyze_tokens(tokens: &[Token]) -> String {
+pub fn analyze_tokens<K: TokenKind>(tokens: &[Token<K>]) -> String {
     let mut output = String::new();
     let mut type_counts = std::collections::HashMap::new();
     let mut subtype_counts = std::collections::HashMap::new();
@@ -105,9 +117,9 @@ pub fn analyze_tokens(tokens: &[Token]) -> String {
     let mut tokens_per_line = std::collections::HashMap::new();
 
     for token in tokens {
-        *type_counts.entry(&token.token_type).or_insert(0) += 1;
+        *type_counts.entry(token.token_type.name()).or_insert(0) += 1;
         if let Some(subtype) = &token.token_sub_type {
-            *subtype_counts.entry(*subtype).or_insert(0) += 1;
+            *subtype_counts.entry(subtype.name()).or_insert(0) += 1;
         }
         max_line = max_line.max(token.line);
         *tokens_per_line.entry(token.line).or_insert(0) += 1;
@@ -0,0 +1,307 @@
+use rb_tokenizer::codegen::{CodegenError, LexerGenerator};
+use rb_tokenizer::scanners::block_scanner::{EscapeRule, EscapeTransform};
+use rb_tokenizer::scanners::{BlockScanner, EolScanner, ScannerType};
+use rb_tokenizer::tokens::{TokenFlags, TokenValue};
+use rb_tokenizer::{LineEndings, Tokenizer, TokenizerConfig};
+
+// Generated from `calc_tokenizer` by `test_generated_source_is_up_to_date`
+mod calc_lexer {
+    include!("generated/calc_lexer.rs");
+}
+
+const GENERATED_PATH: &str = "tests/codegen/generated/calc_lexer.rs";
+
+fn calc_tokenizer() -> Tokenizer {
+    let config = TokenizerConfig {
+        tokenize_whitespace: true,
+        continue_on_error: true,
+        error_tolerance_limit: 5,
+        ..TokenizerConfig::default()
+    };
+    let mut tokenizer = Tokenizer::with_config(config);
+
+    tokenizer.add_block_scanner("\"", "\"", "String", None, false, false, true);
+    tokenizer.add_block_scanner("/*", "*/", "Comment", Some("Block"), false, true, true);
+    tokenizer.add_eol_scanner("//", "Comment", Some("Line"), false);
+    tokenizer.add_symbol_scanner("==", "Operator", Some("Equal"));
+    tokenizer.add_symbol_scanner("=", "Operator", Some("Assign"));
+    tokenizer.add_symbol_scanner("+", "Operator", Some("Plus"));
+    tokenizer.add_symbol_scanner("/", "Operator", Some("Divide"));
+    tokenizer.add_symbol_scanner("(", "Paren", Some("Open"));
+    tokenizer.add_symbol_scanner(")", "Paren", Some("Close"));
+    tokenizer.add_regex_scanner(r"^\d+(\.\d+)?", "Number", None);
+    tokenizer.add_regex_scanner(r"^(let|print)([^a-z]|$)", "Keyword", None);
+    tokenizer.add_regex_scanner(r"^[a-zA-Z_][a-zA-Z0-9_]*", "Identifier", None);
+
+    tokenizer
+}
+
+const SAMPLES: &[&str] = &[
+    "",
+    "let x = 1 + 2.5",
+    "x == y/2 // compare\nprint(x)",
+    "lettuce = \"a \\\" b\" /* one **/ done /*/ no */",
+    "\"unterminated",
+    "x /* unterminated",
+    "1 # 2 $ 3",
+    "naïve = \"héllo\" // ünïcode\r\nz",
+    "\"\\\\\" \"\\\"",
+    "a\r\nb\rc\n",
+    "3.x 4. /",
+];
+
+#[cfg(test)]
+mod generator_tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_source_is_up_to_date() {
+        let tokenizer = calc_tokenizer();
+        let source = LexerGenerator::new(&tokenizer)
+            .lexer_name("CalcLexer")
+            .constructor_name("calc_tokenizer")
+            .generate()
+            .expect("the calculator tokenizer should compile");
+
+        // Regenerate the checked-in lexer with `RB_TOKENIZER_BLESS=1 cargo test`
+        if std::env::var_os("RB_TOKENIZER_BLESS").is_some() {
+            std::fs::write(GENERATED_PATH, &source).unwrap();
+        }
+        assert_eq!(source, include_str!("generated/calc_lexer.rs"), "{} is stale", GENERATED_PATH);
+    }
+
+    #[test]
+    fn test_generated_lexer_matches_interpreted_tokenizer() {
+        let tokenizer = calc_tokenizer();
+        let generated = calc_lexer::calc_tokenizer();
+
+        let result = LexerGenerator::new(&tokenizer).verify(&generated, SAMPLES);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_compiled_lexer_matches_interpreted_tokenizer() {
+        let tokenizer = calc_tokenizer();
+        let generator = LexerGenerator::new(&tokenizer);
+        let compiled = generator.compile().unwrap();
+        assert!(compiled.state_count() > 2);
+
+        assert_eq!(generator.verify(&compiled.into_tokenizer(), SAMPLES), Ok(()));
+    }
+
+    #[test]
+    fn test_first_matching_scanner_wins() {
+        // Earlier scanners win even when a later one would match more input
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_symbol_scanner("=", "Assign", None);
+        tokenizer.add_symbol_scanner("==", "Equal", None);
+        tokenizer.add_regex_scanner(r"^\d+", "Integer", None);
+        tokenizer.add_regex_scanner(r"^\d+\.\d+", "Float", None);
+        tokenizer.add_regex_scanner(r"^(a|ab)", "Word", None);
+        tokenizer.add_symbol_scanner("b", "B", None);
+
+        let generator = LexerGenerator::new(&tokenizer);
+        let compiled = generator.compile().unwrap().into_tokenizer();
+        let tokens = compiled.tokenize("== 12 ab").unwrap();
+        let types: Vec<_> = tokens.iter().map(|token| token.token_type).collect();
+        assert_eq!(types, ["Assign", "Assign", "Integer", "Word", "B"]);
+
+        assert_eq!(generator.verify(&compiled, &["== 12 ab", "1.5", "ab ba"]), Ok(()));
+    }
+
+    #[test]
+    fn test_generated_matcher_runs_without_a_tokenizer() {
+        assert_eq!(calc_lexer::match_token("== 1"), Some(("Operator", Some("Equal"), 2)));
+        assert_eq!(calc_lexer::match_token("lettuce"), Some(("Identifier", None, 7)));
+        assert_eq!(calc_lexer::match_token("let x"), Some(("Keyword", None, 4)));
+        assert_eq!(calc_lexer::match_token("# 1"), None);
+        assert_eq!(calc_lexer::match_token(r#""a\"" b"#), Some(("String", None, 5)));
+        assert_eq!(calc_lexer::match_token("// note\nx"), Some(("Comment", Some("Line"), 8)));
+        assert_eq!(calc_lexer::match_token("\"open"), None);
+    }
+
+    #[test]
+    fn test_block_escapes() {
+        let config = TokenizerConfig {
+            continue_on_error: true,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        // SQL strings, with their escapes resolved into typed values
+        let mut sql = BlockScanner::new("'", "'", "Sql", None, false, false, false);
+        sql.add_doubled_delimiter_escape();
+        sql.set_typed_values(true);
+        tokenizer.register_scanner(None, 0, sql).unwrap();
+        // Transformed escapes, through the escape map and a hex transform
+        let mut string = BlockScanner::new("\"", "\"", "String", None, false, false, true);
+        string.add_simple_escape('\\');
+        string.add_simple_escape('%');
+        string.set_escape_transform(1, EscapeTransform::HexCodePoint);
+        string.add_escape_mapping("n", "\n");
+        string.set_transform_escapes(true);
+        tokenizer.register_scanner(None, 0, string).unwrap();
+        // Blocks that may run to the end of the input
+        let mut heredoc = BlockScanner::new("<<", ">>>", "Heredoc", None, false, false, true);
+        heredoc.set_allow_unterminated(true);
+        tokenizer.register_scanner(None, 0, heredoc).unwrap();
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Word", None);
+
+        let samples = [
+            "'it''s' 'a''''b' '''' ''",
+            "'x'''",
+            "'open'' end",
+            "'é''ü'x",
+            r#""a\nb\q" "%a%G" "a\"#,
+            r"<<a > >> b>>> <<\\>>",
+            r"<<never closed \",
+        ];
+        let generator = LexerGenerator::new(&tokenizer);
+        let compiled = generator.compile().unwrap().into_tokenizer();
+        assert_eq!(generator.verify(&compiled, &samples), Ok(()));
+
+        let tokens = compiled.tokenize(r#"'it''s' "a\n%a""#).unwrap();
+        assert_eq!(tokens[0].value, "it''s");
+        assert!(matches!(&tokens[0].literal, Some(TokenValue::Str(value)) if value == "it's"));
+        assert!(tokens[0].flags.contains(TokenFlags::HAD_ESCAPES));
+        assert_eq!(tokens[1].value, "\"a\n\n\"");
+    }
+
+    #[test]
+    fn test_eol_line_endings() {
+        let samples = ["# a\r\nb # c\rd # e\u{2028}f", "# end", "#\n#\r\n", "-- a\r\nb --\n"];
+        for line_endings in [LineEndings::Lf, LineEndings::Any, LineEndings::Unicode] {
+            let config = TokenizerConfig {
+                line_endings,
+                ..TokenizerConfig::default()
+            };
+            let mut tokenizer = Tokenizer::with_config(config);
+            tokenizer.add_eol_scanner("#", "Comment", None, false);
+            let mut raw = EolScanner::new("--", "Raw", None, true);
+            raw.set_include_terminator(false);
+            raw.set_line_endings(LineEndings::Lf);
+            tokenizer.register_scanner(None, 0, raw).unwrap();
+            tokenizer.add_regex_scanner(r"^[a-z]+", "Word", None);
+
+            let generator = LexerGenerator::new(&tokenizer);
+            let compiled = generator.compile().unwrap().into_tokenizer();
+            assert_eq!(generator.verify(&compiled, &samples), Ok(()), "{:?}", line_endings);
+        }
+    }
+
+    #[test]
+    fn test_irregular_scanners_are_rejected() {
+        let reason = |scanner: ScannerType| {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.add_symbol_scanner("+", "Plus", None);
+            tokenizer.register_scanner(None, 1, scanner).unwrap();
+            match LexerGenerator::new(&tokenizer).compile() {
+                Err(CodegenError::UnsupportedScanner { index: 1, reason }) => reason,
+                other => panic!("expected scanner 1 to be rejected, got {:?}", other.err()),
+            }
+        };
+
+        let nesting = BlockScanner::new("/*", "*/", "Comment", None, true, true, true);
+        assert_eq!(reason(nesting.into()), "nesting block scanners are not supported");
+        let mut brackets = BlockScanner::new("(", ")", "Group", None, false, false, true);
+        brackets.add_delimiter_pair("[", "]");
+        assert_eq!(reason(brackets.into()), "block scanners with several delimiter pairs are not supported");
+
+        let mut custom = BlockScanner::new("\"", "\"", "String", None, false, false, true);
+        custom.add_escape_rule_with_transform(EscapeRule::Simple { escape_char: '\\' }, EscapeTransform::custom(|_| None));
+        assert_eq!(reason(custom.into()), "custom escape transforms are not supported");
+        let mut entities = BlockScanner::new("\"", "\"", "Attribute", None, false, false, true);
+        entities.add_named_escape('&', ';', 33);
+        entities.set_html_entities(true);
+        assert_eq!(reason(entities.into()), "named escapes and entities are not supported");
+        let mut quoted = BlockScanner::new("'", "'", "Char", None, false, false, true);
+        quoted.add_simple_escape('\'');
+        assert_eq!(reason(quoted.into()), "end delimiters starting with an escape character are not supported");
+
+        let mut directive = EolScanner::new("#", "Directive", None, true);
+        directive.set_line_continuation(Some("\\"));
+        assert_eq!(reason(directive.into()), "EOL scanners with a line continuation are not supported");
+        let mut comment = EolScanner::new("<%#", "Comment", None, true);
+        comment.add_terminator("%>");
+        assert_eq!(reason(comment.into()), "EOL scanners with terminators are not supported");
+    }
+
+    #[test]
+    fn test_verify_reports_first_difference() {
+        let tokenizer = calc_tokenizer();
+        let mut other = calc_tokenizer();
+        other.add_symbol_scanner("#", "Hash", None);
+
+        let mismatch = LexerGenerator::new(&tokenizer)
+            .verify(&other, &["1 + 2", "1 # 2"])
+            .unwrap_err();
+        assert_eq!(mismatch.sample, 1);
+        assert_eq!(mismatch.expected, "Whitespace \" \" at 1:4");
+        assert_eq!(mismatch.actual, "Hash \"#\" at 1:3");
+    }
+
+    #[test]
+    fn test_unsupported_scanners_are_rejected() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_symbol_scanner("+", "Plus", None);
+        tokenizer.add_closure_scanner(Box::new(|_| Ok(None)));
+        assert!(matches!(
+            LexerGenerator::new(&tokenizer).compile(),
+            Err(CodegenError::UnsupportedScanner { index: 1, .. })
+        ));
+
+        let tokenizer = Tokenizer::builder()
+            .regex(r"^\d+").literal(|text| text.parse().ok().map(TokenValue::Int)).kind("Number")
+            .build()
+            .unwrap();
+        assert_eq!(
+            LexerGenerator::new(&tokenizer).compile().err().map(|error| error.to_string()),
+            Some("Scanner 0 cannot be compiled: regex scanners with a literal conversion are not supported".to_string())
+        );
+        let tokenizer = Tokenizer::builder().regex(r#"^"([^"]*)""#).value_group(1).kind("String").build().unwrap();
+        assert!(matches!(
+            LexerGenerator::new(&tokenizer).compile(),
+            Err(CodegenError::UnsupportedScanner { index: 0, reason }) if reason.contains("capture groups")
+        ));
+        let tokenizer = Tokenizer::builder().symbol("if").word_boundary().kind("If").build().unwrap();
+        assert!(matches!(
+            LexerGenerator::new(&tokenizer).compile(),
+            Err(CodegenError::UnsupportedScanner { index: 0, reason }) if reason.contains("matching options")
+        ));
+
+        for pattern in [r"\d+", r"^\d*", r"^(true|false)\b"] {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.add_regex_scanner(pattern, "Bad", None);
+            let error = LexerGenerator::new(&tokenizer).generate().unwrap_err();
+            assert!(
+                matches!(&error, CodegenError::UnsupportedPattern { index: 0, pattern: p, .. } if p == pattern),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[cfg(feature = "grammar")]
+    #[test]
+    fn test_interned_kinds_are_generated_by_name() {
+        use rb_tokenizer::grammar::GrammarFormat;
+
+        let grammar = r#"
+[[scanners]]
+type = "regex"
+pattern = '^\d+'
+token_type = "Number"
+
+[[scanners]]
+type = "symbol"
+symbol = "+"
+token_type = "Operator"
+token_sub_type = "Plus"
+"#;
+        let tokenizer = Tokenizer::from_grammar(grammar, GrammarFormat::Toml).unwrap();
+        let generator = LexerGenerator::new(&tokenizer);
+
+        let source = generator.generate().unwrap();
+        assert!(source.contains(r#"("Operator", Some("Plus"), Action::Token),"#), "{}", source);
+        assert_eq!(generator.verify(&generator.compile().unwrap().into_tokenizer(), &["1+22", "1 - 2"]), Ok(()));
+    }
+}
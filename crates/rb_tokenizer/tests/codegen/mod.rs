@@ -0,0 +1,3 @@
+// Include all lexer generation test modules
+#[cfg(test)]
+mod generator_tests;
@@ -0,0 +1,618 @@
+use rb_tokenizer::{
+    grammar::{GrammarError, GrammarFormat},
+    tokens::{TokenFlags, TokenTypeId, TokenValue, TokenizationError},
+    ColumnMode, LineEndings, Tokenizer,
+};
+
+#[cfg(test)]
+mod loader_tests {
+    use super::*;
+
+    const JSON_GRAMMAR_TOML: &str = r#"
+[config]
+continue_on_error = false
+error_tolerance_limit = 1
+
+[[scanners]]
+type = "symbol"
+symbol = "{"
+token_type = "Brace"
+token_sub_type = "OpenBrace"
+
+[[scanners]]
+type = "symbol"
+symbol = "}"
+token_type = "Brace"
+token_sub_type = "CloseBrace"
+
+[[scanners]]
+type = "symbol"
+symbol = ":"
+token_type = "Colon"
+
+[[scanners]]
+type = "symbol"
+symbol = ","
+token_type = "Comma"
+
+[[scanners]]
+type = "regex"
+pattern = '^"([^"\\]|\\.)*"'
+token_type = "String"
+
+[[scanners]]
+type = "regex"
+pattern = '^-?\d+(\.\d+)?'
+token_type = "Number"
+accept_start_chars = "-0123456789"
+"#;
+
+    fn load_toml(text: &str) -> Result<Tokenizer<TokenTypeId>, Vec<GrammarError>> {
+        Tokenizer::from_grammar(text, GrammarFormat::Toml)
+    }
+
+    fn load_json(text: &str) -> Result<Tokenizer<TokenTypeId>, Vec<GrammarError>> {
+        Tokenizer::from_grammar(text, GrammarFormat::Json)
+    }
+
+    fn names(tokenizer: &Tokenizer<TokenTypeId>, input: &str) -> Vec<(String, String)> {
+        tokenizer
+            .tokenize(input)
+            .unwrap()
+            .iter()
+            .map(|t| (tokenizer.type_name(t.token_type).into_owned(), t.value.clone()))
+            .collect()
+    }
+
+    fn single_error(result: Result<Tokenizer<TokenTypeId>, Vec<GrammarError>>) -> GrammarError {
+        let errors = result.err().expect("grammar should be rejected");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        errors.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_toml_grammar() {
+        let tokenizer = load_toml(JSON_GRAMMAR_TOML).unwrap();
+        let tokens = tokenizer.tokenize(r#"{"key": -12.5}"#).unwrap();
+
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokenizer.type_name(tokens[0].token_type), "Brace");
+        assert_eq!(tokenizer.type_name(tokens[0].token_sub_type.unwrap()), "OpenBrace");
+        assert_eq!(tokenizer.type_name(tokens[1].token_type), "String");
+        assert_eq!(tokens[3].value, "-12.5");
+        assert_eq!((tokens[3].line, tokens[3].column), (1, 9));
+        assert_eq!(tokenizer.config().error_tolerance_limit, 1);
+    }
+
+    #[test]
+    fn test_json_grammar() {
+        let grammar = r#"{
+            "config": { "tokenize_whitespace": true },
+            "scanners": [
+                { "type": "eol", "delimiter": "--", "token_type": "Comment", "include_delimiter": false },
+                { "type": "regex", "pattern": "^[a-z]+", "token_type": "Word" }
+            ]
+        }"#;
+        let tokenizer = load_json(grammar).unwrap();
+
+        assert_eq!(
+            names(&tokenizer, "hi -- note\n"),
+            vec![
+                ("Word".to_string(), "hi".to_string()),
+                ("Whitespace".to_string(), " ".to_string()),
+                ("Comment".to_string(), " note\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_section() {
+        let tokenizer = load_toml(
+            r#"
+[config]
+continue_on_error = true
+track_token_positions = false
+column_mode = "display_width"
+tab_width = 8
+line_endings = "unicode"
+report_line_ending_style = true
+"#,
+        )
+        .unwrap();
+
+        let config = tokenizer.config();
+        assert!(config.continue_on_error);
+        assert!(!config.track_token_positions);
+        assert_eq!(config.column_mode, ColumnMode::DisplayWidth { tab_width: 8 });
+        assert_eq!(config.line_endings, LineEndings::Unicode);
+        assert!(config.report_line_ending_style);
+    }
+
+    #[test]
+    fn test_block_scanner_with_escapes() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = "'"
+end = "'"
+token_type = "String"
+include_delimiters = false
+transform_escapes = true
+escapes = [
+    { kind = "named", start_char = "&", end_char = ";", max_length = 8 },
+]
+escape_map = { amp = "&", lt = "<" }
+
+[[scanners]]
+type = "block"
+start = "/*"
+end = "*/"
+token_type = "Comment"
+nesting = true
+raw = true
+
+[[scanners]]
+type = "block"
+start = "`"
+end = "`"
+token_type = "Template"
+escapes = [
+    { kind = "balanced", start_seq = "${", end_seq = "}", allow_nesting = true },
+    { kind = "pattern", pattern = '^\\u[0-9a-f]{4}' },
+]
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+
+        let tokens = tokenizer.tokenize("'a&amp;b&lt;' /* x /* y */ */ `x${ `y` }z`").unwrap();
+        assert_eq!(tokens[0].value, "a&b<");
+        assert_eq!(tokens[1].value, "/* x /* y */ */");
+        // The balanced escape hides the inner backticks
+        assert_eq!(tokens[2].value, "`x${ `y` }z`");
+    }
+
+    #[test]
+    fn test_priorities_reorder_scanners() {
+        let grammar = r#"
+[[scanners]]
+type = "regex"
+pattern = '^[a-z]+'
+token_type = "Identifier"
+
+[[scanners]]
+type = "regex"
+pattern = '^(if|else)\b'
+token_type = "Keyword"
+priority = -1
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+
+        assert_eq!(
+            names(&tokenizer, "if x"),
+            vec![
+                ("Keyword".to_string(), "if".to_string()),
+                ("Identifier".to_string(), "x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loaded_tokenizer_reports_errors() {
+        let tokenizer = load_toml(JSON_GRAMMAR_TOML).unwrap();
+        let errors = tokenizer.tokenize("{?}").unwrap_err();
+        assert!(matches!(errors[0], TokenizationError::UnrecognizedToken(_)));
+    }
+
+    #[test]
+    fn test_invalid_regex_reports_line() {
+        let grammar = "[[scanners]]\ntype = \"regex\"\ntoken_type = \"Bad\"\npattern = '^(unclosed'\n";
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(4));
+        assert!(error.message.contains("Invalid regex pattern"), "{}", error.message);
+        assert!(error.to_string().starts_with("Grammar error at line 4: "));
+    }
+
+    #[test]
+    fn test_unanchored_and_empty_matching_patterns() {
+        let grammar = r#"{
+  "scanners": [
+    { "type": "regex", "pattern": "[a-z]+", "token_type": "Word" },
+    { "type": "regex",
+      "pattern": "^[0-9]*",
+      "token_type": "Number" }
+  ]
+}"#;
+        let errors = load_json(grammar).err().unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, Some(3));
+        assert!(errors[0].message.contains("anchored"));
+        assert_eq!(errors[1].line, Some(5));
+        assert!(errors[1].message.contains("empty string"));
+    }
+
+    #[test]
+    fn test_anchors_after_inline_flags() {
+        let grammar = r#"
+[[scanners]]
+type = "regex"
+pattern = '(?i)^true\b'
+token_type = "Boolean"
+
+[[scanners]]
+type = "regex"
+pattern = '(?x) ^ [a-z]+ # identifier'
+token_type = "Identifier"
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        let tokens = tokenizer.tokenize("TRUE").unwrap();
+        assert_eq!(tokenizer.type_name(tokens[0].token_type), "Boolean");
+
+        let error = single_error(load_toml(&grammar.replace("(?i)^true", "(?i)true")));
+        assert_eq!(error.line, Some(4));
+        assert!(error.message.contains("anchored"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_unknown_field_reports_its_line() {
+        let grammar = "[[scanners]]\ntype = \"symbol\"\nsymbol = \"+\"\ntoken_type = \"Plus\"\n\n[[scanners]]\ntype = \"eol\"\ndelimiter = \"//\"\ntoken_type = \"Comment\"\ninclude_delimiters = true\n";
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(10));
+        assert!(error.message.contains("unknown field `include_delimiters`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_unknown_scanner_type() {
+        let grammar = "{\n  \"scanners\": [\n    {\n      \"type\": \"magic\",\n      \"token_type\": \"X\"\n    }\n  ]\n}";
+        let error = single_error(load_json(grammar));
+
+        assert_eq!(error.line, Some(4));
+        assert!(error.message.contains("unknown variant `magic`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_missing_field_reports_entry_line() {
+        let grammar = "\n[[scanners]]\ntype = \"symbol\"\ntoken_type = \"Plus\"\n";
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(2));
+        assert!(error.message.contains("missing field `symbol`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_duplicate_symbols() {
+        let grammar = r#"
+[[scanners]]
+type = "symbol"
+symbol = "+"
+token_type = "Plus"
+
+[[scanners]]
+type = "symbol"
+symbol = "+"
+token_type = "Add"
+"#;
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(9));
+        assert_eq!(error.message, "Duplicate symbol '+' (first defined at line 4)");
+    }
+
+    #[test]
+    fn test_duplicate_symbols_follow_matching_options() {
+        let grammar = r#"
+[[scanners]]
+type = "symbol"
+symbol = "IF"
+token_type = "Keyword"
+
+[[scanners]]
+type = "symbol"
+symbol = "if"
+token_type = "If"
+case_insensitive = "ascii"
+"#;
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(9));
+        assert_eq!(error.message, "Duplicate symbol 'if' (matches 'IF', first defined at line 4)");
+
+        let tokenizer = load_toml(&grammar.replace("case_insensitive = \"ascii\"\n", "")).unwrap();
+        assert_eq!(tokenizer.tokenize("if").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_named_scanners() {
+        let grammar = r#"
+[[scanners]]
+type = "regex"
+pattern = '^[a-z]+'
+token_type = "Identifier"
+name = "identifier"
+
+[[scanners]]
+type = "regex"
+pattern = '^(if|else)\b'
+token_type = "Keyword"
+name = "keyword"
+"#;
+        let mut tokenizer = load_toml(grammar).unwrap();
+        tokenizer.set_scanner_priority("keyword", -1).unwrap();
+        assert_eq!(names(&tokenizer, "if")[0].0, "Keyword");
+
+        tokenizer.remove_scanner("keyword").unwrap();
+        assert_eq!(names(&tokenizer, "if")[0].0, "Identifier");
+    }
+
+    #[test]
+    fn test_block_delimiter_pairs() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = "("
+end = ")"
+pairs = [["[", "]"], ["{", "}"]]
+nesting = true
+token_type = "Group"
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+
+        assert_eq!(
+            names(&tokenizer, "[a {b}] (c)"),
+            [
+                ("Group".to_string(), "[a {b}]".to_string()),
+                ("Group".to_string(), "(c)".to_string()),
+            ]
+        );
+        assert!(tokenizer.tokenize("(a]").is_err());
+    }
+
+    #[test]
+    fn test_eol_continuation_and_terminators() {
+        let grammar = r##"
+[[scanners]]
+type = "eol"
+delimiter = "#"
+token_type = "Directive"
+continuation = '\'
+include_terminator = false
+terminators = ["%>"]
+
+[[scanners]]
+type = "symbol"
+symbol = "%>"
+token_type = "TagEnd"
+"##;
+        let tokenizer = load_toml(grammar).unwrap();
+
+        assert_eq!(
+            names(&tokenizer, "#a \\\nb\n#c %>"),
+            [
+                ("Directive".to_string(), "#a \\\nb".to_string()),
+                ("Directive".to_string(), "#c ".to_string()),
+                ("TagEnd".to_string(), "%>".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_scanner_names() {
+        let grammar = "[[scanners]]\ntype = \"symbol\"\nsymbol = \"+\"\ntoken_type = \"Plus\"\nname = \"op\"\n\n[[scanners]]\ntype = \"symbol\"\nsymbol = \"-\"\ntoken_type = \"Minus\"\nname = \"op\"\n";
+        let error = single_error(load_toml(grammar));
+
+        assert_eq!(error.line, Some(11));
+        assert_eq!(error.message, "Duplicate scanner name 'op' (first defined at line 5)");
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let error = single_error(load_toml("[config]\ncolumn_mode = \"chars\"\ntab_width = 4\n"));
+        assert_eq!(error.line, Some(3));
+        assert!(error.message.contains("display_width"));
+
+        let error = single_error(load_toml("[config]\nline_endings = \"crlf\"\n"));
+        assert_eq!(error.line, Some(1));
+        assert!(error.message.contains("unknown variant `crlf`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_invalid_escape_rules() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = '"'
+end = '"'
+token_type = "String"
+escapes = [{ kind = "named", start_char = "&", end_char = ";", max_length = 0 }]
+escape_map = { amp = "ab" }
+"#;
+        // Replacements may be longer than one character
+        let error = single_error(load_toml(grammar));
+        assert_eq!(error.line, Some(7));
+        assert!(error.message.contains("max_length"));
+    }
+
+    #[test]
+    fn test_html_entities() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = '"'
+end = '"'
+token_type = "String"
+include_delimiters = false
+escapes = [{ kind = "named", start_char = "&", end_char = ";", max_length = 40 }]
+escape_map = { dots = "..." }
+transform_escapes = true
+html_entities = true
+reject_unknown_entities = true
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+
+        assert_eq!(names(&tokenizer, "\"&dots;&#65;&NotEqualTilde;\"")[0].1, "...A\u{2242}\u{338}");
+        assert!(tokenizer.tokenize("\"&bogus;\"").is_err());
+    }
+
+    #[test]
+    fn test_escape_transforms() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = '"'
+end = '"'
+token_type = "String"
+include_delimiters = false
+escapes = [
+    { kind = "pattern", pattern = '^\\u\{[0-9a-fA-F]+\}', transform = "hex_code_point" },
+    { kind = "balanced", start_seq = "${", end_seq = "}", transform = "remove" },
+    { kind = "simple", escape_char = '\' },
+]
+transform_escapes = true
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        assert_eq!(names(&tokenizer, r#""\u{48}i${name}\!""#)[0].1, "Hi!");
+
+        let error = single_error(load_toml(&grammar.replace("remove", "upper")));
+        assert!(error.message.contains("unknown variant `upper`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_doubled_delimiter_escape() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = "'"
+end = "'"
+token_type = "String"
+include_delimiters = false
+escapes = [{ kind = "doubled" }, { kind = "doubled", sequence = "$$" }]
+default_backslash_escape = false
+transform_escapes = true
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        let values: Vec<_> = names(&tokenizer, r"'it''s' '$$$$\'").into_iter().map(|(_, value)| value).collect();
+        assert_eq!(values, ["it's", "$$\\"]);
+
+        let error = single_error(load_toml(&grammar.replace(r#"sequence = "$$""#, r#"sequence = """#)));
+        assert_eq!(error.line, Some(8));
+    }
+
+    #[test]
+    fn test_typed_values_and_unterminated_blocks() {
+        let grammar = r#"
+[[scanners]]
+type = "block"
+start = '"'
+end = '"'
+token_type = "String"
+escapes = [{ kind = "simple", escape_char = '\' }]
+escape_map = { t = "\t" }
+typed_value = true
+allow_unterminated = true
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        let tokens = tokenizer.tokenize(r#""a\tb" "open"#).unwrap();
+        assert_eq!(tokens[0].value, r#""a\tb""#);
+        assert_eq!(tokens[0].literal, Some(TokenValue::Str("a\tb".to_string())));
+        assert!(tokens[0].has_flag(TokenFlags::HAD_ESCAPES));
+        assert_eq!(tokens[1].value, "\"open");
+        assert!(tokens[1].has_flag(TokenFlags::UNTERMINATED));
+    }
+
+    #[test]
+    fn test_regex_capture_groups() {
+        let grammar = r#"
+[[scanners]]
+type = "regex"
+pattern = '^(?:(?P<Hex>0x[0-9a-f]+)|(?P<Dec>\d+))(?P<suffix>[iu]\d+)?'
+token_type = "Number"
+value_group = 1
+sub_type_groups = ["Hex", "Dec"]
+capture_metadata = true
+
+[[scanners]]
+type = "regex"
+pattern = '^"(?P<content>[^"]*)"'
+token_type = "String"
+value_group = "content"
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        let tokens = tokenizer.tokenize(r#"0xff "hi" 7u8"#).unwrap();
+        let sub_types: Vec<_> = tokens
+            .iter()
+            .map(|token| token.token_sub_type.map(|sub_type| tokenizer.type_name(sub_type).to_string()))
+            .collect();
+        assert_eq!(sub_types, [Some("Hex".to_string()), None, Some("Dec".to_string())]);
+        assert_eq!(tokens[0].value, "0xff");
+        assert_eq!(tokens[1].value, "hi");
+        assert_eq!(tokens[2].value, "");
+        assert_eq!(tokens[2].capture("Dec"), Some("7"));
+        assert_eq!(tokens[2].capture("suffix"), Some("u8"));
+
+        let error = single_error(load_toml(&grammar.replace(r#"value_group = "content""#, r#"value_group = "body""#)));
+        assert_eq!(error.line, Some(14));
+        assert!(error.message.contains("capture group 'body'"), "{}", error.message);
+        let error = single_error(load_toml(&grammar.replace(r#"["Hex", "Dec"]"#, r#"["Hex", "Oct"]"#)));
+        assert_eq!(error.line, Some(7));
+    }
+
+    #[test]
+    fn test_symbol_matching_options() {
+        let grammar = r#"
+[[scanners]]
+type = "symbol"
+symbol = "select"
+token_type = "Keyword"
+case_insensitive = "ascii"
+word_boundary = true
+
+[[scanners]]
+type = "symbol"
+symbol = "café"
+token_type = "Drink"
+case_insensitive = "unicode"
+normalize = true
+
+[[scanners]]
+type = "regex"
+pattern = '^\w+'
+token_type = "Word"
+"#;
+        let tokenizer = load_toml(grammar).unwrap();
+        let tokens = tokenizer.tokenize("SELECT selected CAFE\u{301}").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| (tokenizer.type_name(token.token_type).to_string(), token.value.as_str())).collect();
+        assert_eq!(values, [("Keyword".to_string(), "SELECT"), ("Word".to_string(), "selected"), ("Drink".to_string(), "CAFE\u{301}")]);
+
+        let error = single_error(load_toml(&grammar.replace(r#""ascii""#, r#""turkish""#)));
+        assert_eq!(error.line, Some(3));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = single_error(load_toml("[[scanners]]\ntype = \n"));
+        assert_eq!(error.line, Some(2));
+
+        let error = single_error(load_json("{\n  \"scanners\": [\n    { \"type\": \"symbol\" \n  ]\n}"));
+        assert_eq!(error.line, Some(4));
+        assert!(!error.message.contains(" at line "));
+
+        let error = single_error(load_toml("[scannerz]\n"));
+        assert!(error.message.contains("unknown field `scannerz`"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_grammar_file() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("rb_tokenizer_grammar_{}.toml", std::process::id()));
+        std::fs::write(&path, JSON_GRAMMAR_TOML).unwrap();
+
+        let tokenizer = Tokenizer::from_grammar_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(tokenizer.tokenize("{}").unwrap().len(), 2);
+
+        let errors = Tokenizer::from_grammar_file(dir.join("grammar.yaml")).err().unwrap();
+        assert!(errors[0].message.contains("Unknown grammar format"));
+        assert_eq!(GrammarFormat::from_path("x.json".as_ref()), Some(GrammarFormat::Json));
+    }
+}
@@ -0,0 +1,3 @@
+// Include all grammar-loading test modules
+#[cfg(test)]
+mod loader_tests;
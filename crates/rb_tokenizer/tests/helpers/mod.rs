@@ -0,0 +1,13 @@
+// Helpers shared by the test modules
+
+use rb_tokenizer::tokens::Token;
+
+/// A token of `token_type` holding `value`, at line 0, column 0
+pub fn token(token_type: &'static str, value: &str) -> Token {
+    Token::new(token_type, None, value.to_string(), 0, 0)
+}
+
+/// The type and value of each token
+pub fn values(tokens: &[Token]) -> Vec<(&str, &str)> {
+    tokens.iter().map(|token| (token.token_type, token.value.as_str())).collect()
+}
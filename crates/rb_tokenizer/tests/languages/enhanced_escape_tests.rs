@@ -9,6 +9,7 @@ fn get_js_style_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -119,6 +120,7 @@ fn get_html_style_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -194,6 +196,7 @@ fn get_c_style_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -267,6 +270,7 @@ fn get_ruby_style_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
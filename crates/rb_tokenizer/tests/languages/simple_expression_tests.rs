@@ -8,6 +8,7 @@ fn get_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -1,6 +1,7 @@
 use rb_tokenizer::{Tokenizer, TokenizerConfig};
-use rb_tokenizer::scanners::block_scanner::BlockScanner;
+use rb_tokenizer::scanners::block_scanner::{BlockNode, BlockScanner, BlockTree, EscapeRule, EscapeTransform};
 use rb_tokenizer::scanners::scanner::Scanner;
+use rb_tokenizer::tokens::{DelimiterLocation, TokenFlags, TokenValue, TokenizationError};
 
 fn get_block_scanner_tokenizer() -> Tokenizer {
     let config = TokenizerConfig {
@@ -8,6 +9,7 @@ fn get_block_scanner_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -72,6 +74,7 @@ fn get_enhanced_escape_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -144,6 +147,37 @@ fn get_enhanced_escape_tokenizer() -> Tokenizer {
     tokenizer
 }
 
+/// `(`/`)`, `[`/`]` and `{`/`}` as one nesting family
+fn bracket_scanner() -> BlockScanner {
+    let mut scanner = BlockScanner::new("(", ")", "Group", None, true, false, true);
+    scanner.add_delimiter_pair("[", "]");
+    scanner.add_delimiter_pair("{", "}");
+    scanner
+}
+
+/// A string without delimiters in the value, transforming escapes
+fn escaping_scanner() -> BlockScanner {
+    let mut scanner = BlockScanner::new("\"", "\"", "String", None, false, false, false);
+    scanner.set_transform_escapes(true);
+    scanner
+}
+
+fn value(scanner: &BlockScanner, input: &str) -> String {
+    scanner.scan(input).unwrap().unwrap().value
+}
+
+fn text(value: &str) -> BlockNode {
+    BlockNode::Text(value.to_string())
+}
+
+fn block(open: &str, close: &str, children: Vec<BlockNode>) -> BlockNode {
+    BlockNode::Block(BlockTree {
+        open: open.to_string(),
+        close: close.to_string(),
+        children,
+    })
+}
+
 #[cfg(test)]
 mod block_scanner_tests {
     use super::*;
@@ -287,6 +321,7 @@ mod block_scanner_tests {
             continue_on_error: true,
             error_tolerance_limit: 5,
             track_token_positions: true,
+            ..TokenizerConfig::default()
         };
         let mut tokenizer = Tokenizer::with_config(config);
 
@@ -344,7 +379,7 @@ mod block_scanner_tests {
         println!("Match end result: {:?}", result);
 
         // Should find the correct end position (the entire string)
-        // assert_eq!(result, Some(input.len()));
+        assert_eq!(result, Some(input.len()));
 
         // Now test the full scan with transformation
         let token = scanner.scan(input).unwrap().unwrap();
@@ -498,13 +533,10 @@ mod block_scanner_tests {
             println!("Token {}: Type='{}', Value='{}'", i, token.token_type, token.value);
         }
 
-        // Modify the test to match the actual number of tokens we're getting
-        // TODO: Eventually fix the tokenizer to properly recognize the string as a single token
-        assert_eq!(result.len(), 7);
-        // Verify these are all Identifier tokens as we're seeing in the debug output
-        for token in &result {
-            assert_eq!(token.token_type, "Identifier");
-        }
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].token_type, "String");
+        assert_eq!(result[0].value, "\"String with \n and \t escapes\"");
+        assert_eq!(result[1].token_type, "Identifier");
     }
 
     #[test]
@@ -525,4 +557,374 @@ mod block_scanner_tests {
         assert_eq!(result[1].token_type, "Identifier");
         assert_eq!(result[1].value, "after");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_delimiter_pairs() {
+        let scanner = bracket_scanner();
+
+        for input in ["(a [b] {c})", "[a (b)]", "{}"] {
+            let token = scanner.scan(input).unwrap().unwrap();
+            assert_eq!(token.value, input);
+        }
+        assert_eq!(scanner.scan("[a] rest").unwrap().unwrap().value, "[a]");
+        assert!(scanner.scan("a").unwrap().is_none());
+
+        // Without delimiters the value drops the pair the block opened with
+        let mut scanner = BlockScanner::new("(", ")", "Group", None, false, false, false);
+        scanner.add_delimiter_pair("[", "]");
+        assert_eq!(scanner.scan("[a)b]").unwrap().unwrap().value, "a)b");
+    }
+
+    #[test]
+    fn test_longest_start_delimiter_wins() {
+        let mut scanner = BlockScanner::new("(", ")", "Group", None, false, false, true);
+        scanner.add_delimiter_pair("(*", "*)");
+
+        assert_eq!(scanner.scan("(* a ) b *) c").unwrap().unwrap().value, "(* a ) b *)");
+    }
+
+    #[test]
+    fn test_mismatched_closer_reports_both_locations() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_scanner(Box::new(bracket_scanner()));
+
+        let errors = tokenizer.tokenize("\n  (a [b\n )").unwrap_err();
+        let TokenizationError::MismatchedBlockDelimiter(open, close) = &errors[0] else {
+            panic!("expected a mismatched closer, got {:?}", errors);
+        };
+
+        let location = |delimiter: &str, offset, line, column| DelimiterLocation {
+            delimiter: delimiter.to_string(),
+            offset,
+            line,
+            column,
+        };
+        assert_eq!(*open, location("[", 6, 2, 6));
+        assert_eq!(*close, location(")", 10, 3, 2));
+        assert_eq!(
+            errors[0].to_string(),
+            "Mismatched block delimiter: ')' at line 3, column 2 does not close '[' opened at line 2, column 6"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_inner_pair() {
+        let scanner = bracket_scanner();
+
+        match scanner.scan("(a {b)").unwrap_err() {
+            TokenizationError::MismatchedBlockDelimiter(open, close) => {
+                assert_eq!((open.delimiter.as_str(), open.offset), ("{", 3));
+                assert_eq!((close.delimiter.as_str(), close.offset), (")", 5));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(matches!(
+            scanner.scan("(a [b]").unwrap_err(),
+            TokenizationError::UnmatchedBlockDelimiter(start, end) if start == "(" && end == ")"
+        ));
+        assert!(matches!(
+            scanner.scan("(a [b").unwrap_err(),
+            TokenizationError::UnmatchedBlockDelimiter(start, end) if start == "[" && end == "]"
+        ));
+    }
+
+    #[test]
+    fn test_other_closers_are_text_without_nesting() {
+        let mut scanner = BlockScanner::new("(", ")", "Group", None, false, false, true);
+        scanner.add_delimiter_pair("[", "]");
+
+        assert_eq!(scanner.scan("(a ] [b)").unwrap().unwrap().value, "(a ] [b)");
+    }
+
+    #[test]
+    fn test_scan_tree() {
+        let scanner = bracket_scanner();
+
+        let tree = scanner.scan_tree("(f [a, {b}] c) d").unwrap().unwrap();
+        assert_eq!(
+            BlockNode::Block(tree),
+            block(
+                "(",
+                ")",
+                vec![
+                    text("f "),
+                    block("[", "]", vec![text("a, "), block("{", "}", vec![text("b")])]),
+                    text(" c"),
+                ]
+            )
+        );
+        assert_eq!(scanner.scan_tree("()").unwrap().unwrap().children, []);
+        assert!(scanner.scan_tree("x").unwrap().is_none());
+        assert!(scanner.scan_tree("(]").is_err());
+    }
+
+    #[test]
+    fn test_builder_delimiter_pairs() {
+        let tokenizer = Tokenizer::builder()
+            .block("(", ")").pair("[", "]").nesting().keep_delimiters().kind("Group")
+            .regex(r"^[a-z]+").kind("Identifier")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("a [b (c)] d").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| token.value.as_str()).collect();
+        assert_eq!(values, ["a", "[b (c)]", "d"]);
+
+        let errors = Tokenizer::builder()
+            .block("(", ")").pair("[", "").kind("Group")
+            .build()
+            .err()
+            .expect("the empty delimiter should be rejected");
+        assert_eq!(errors[0].message, "block delimiters must not be empty");
+    }
+
+    #[test]
+    fn test_every_simple_escape_rule_applies() {
+        let mut scanner = escaping_scanner();
+        scanner.add_simple_escape('\\');
+        scanner.add_simple_escape('^');
+        scanner.add_escape_mapping("n", '\n');
+
+        assert_eq!(value(&scanner, r#""a\"b^"c^^\n" rest"#), "a\"b\"c^\n");
+        // Characters other than the escape characters do not start an escape
+        assert_eq!(value(&scanner, "\"abc\" rest"), "abc");
+        assert_eq!(value(&scanner, "\"\\é^ü\""), "éü");
+    }
+
+    #[test]
+    fn test_pattern_escape_transforms() {
+        let mut scanner = escaping_scanner();
+        scanner.add_pattern_escape(r"^\\u\{[0-9a-fA-F]{1,6}\}").unwrap();
+        scanner.set_escape_transform(0, EscapeTransform::HexCodePoint);
+        scanner.add_pattern_escape(r"^\\x[0-9a-fA-F]{2}").unwrap();
+        scanner.set_escape_transform(1, EscapeTransform::HexCodePoint);
+        scanner.add_pattern_escape(r"^\\\n\s*").unwrap();
+        scanner.set_escape_transform(2, EscapeTransform::Remove);
+        scanner.add_pattern_escape(r"^\\N\{[A-Z ]+\}").unwrap();
+        scanner.add_simple_escape('\\');
+        scanner.add_escape_mapping("n", '\n');
+        scanner.add_escape_mapping("\\N{BULLET}", '\u{2022}');
+
+        assert_eq!(value(&scanner, r#""\u{1F600} \x41\n\q""#), "\u{1F600} A\nq");
+        assert_eq!(value(&scanner, "\"a\\\n    b\""), "ab");
+        // Unmapped pattern escapes are kept as written
+        assert_eq!(value(&scanner, r#""\N{BULLET} \N{NOPE}""#), "\u{2022} \\N{NOPE}");
+        // Surrogates are not code points
+        assert_eq!(value(&scanner, r#""\u{D800}""#), "\u{FFFD}");
+        assert!(scanner.escape_transform(3).is_none());
+    }
+
+    #[test]
+    fn test_balanced_escape_with_custom_transform() {
+        let mut scanner = escaping_scanner();
+        scanner.add_balanced_escape("${", "}", true);
+        scanner.set_escape_transform(0, EscapeTransform::custom(|sequence| {
+            (sequence == "${name}").then(|| "World".to_string())
+        }));
+
+        assert_eq!(value(&scanner, r#""Hello ${name}, ${other}!""#), "Hello World, ${other}!");
+        // The closing quote inside the escape does not end the block
+        assert_eq!(value(&scanner, r#""${a + "b"}" rest"#), "${a + \"b\"}");
+    }
+
+    #[test]
+    fn test_escapes_are_transformed_where_they_were_skipped() {
+        let mut scanner = escaping_scanner();
+        scanner.add_pattern_escape(r"^\$\$").unwrap();
+        scanner.set_escape_transform(0, EscapeTransform::custom(|_| Some("$".to_string())));
+        scanner.add_simple_escape('$');
+
+        // `$$` is matched by the first rule, so `$$x` is not a `$` escape of `x`
+        assert_eq!(value(&scanner, "\"$$x $\"\""), "$x \"");
+
+        // A backslash skipped without a rule is kept as written
+        let mut scanner = escaping_scanner();
+        scanner.add_simple_escape('%');
+        assert_eq!(value(&scanner, r#""a\"b%c""#), "a\\\"bc");
+
+        // The delimiters kept in the value are not escapes
+        let mut scanner = BlockScanner::new("&", ";", "Entity", None, false, false, true);
+        scanner.add_named_escape('&', ';', 10);
+        scanner.add_escape_mapping("amp", "&");
+        scanner.set_transform_escapes(true);
+        assert_eq!(value(&scanner, "&amp; rest"), "&amp;");
+    }
+
+    #[test]
+    fn test_builder_escape_transform() {
+        let tokenizer = Tokenizer::builder()
+            .block("'", "'")
+            .pattern_escape(r"^\\u[0-9a-fA-F]{4}")
+            .escape_transform(EscapeTransform::HexCodePoint)
+            .escape('\\')
+            .transform_escapes()
+            .kind("String")
+            .build()
+            .unwrap();
+        assert_eq!(tokenizer.tokenize(r"'\u00e9\''").unwrap()[0].value, "é'");
+
+        let errors = Tokenizer::builder()
+            .block("'", "'").escape_transform(EscapeTransform::Remove).kind("String")
+            .build()
+            .err()
+            .expect("a transform without a rule should be rejected");
+        assert_eq!(errors[0].message, "an escape transform must follow an escape rule");
+    }
+
+    #[test]
+    fn test_simple_escape_only_matches_its_character() {
+        let rule = EscapeRule::Simple { escape_char: '%' };
+        assert_eq!(rule.try_match("a%b", 0), None);
+        assert_eq!(rule.try_match("a%b", 1), Some(2));
+        assert_eq!(rule.try_match("%é", 0), Some(3));
+        assert_eq!(rule.try_match("a%", 1), None);
+
+        let mut scanner = escaping_scanner();
+        scanner.add_simple_escape('%');
+        scanner.set_default_backslash_escape(false);
+        assert_eq!(value(&scanner, r#""a\b%"c" rest"#), "a\\b\"c");
+    }
+
+    #[test]
+    fn test_pattern_escapes_are_anchored() {
+        let rule = EscapeRule::pattern(r"\\u[0-9a-fA-F]{4}").unwrap();
+        assert_eq!(rule.try_match(r"a\u0041", 0), None);
+        assert_eq!(rule.try_match(r"a\u0041", 1), Some(6));
+
+        // A start anchor matches at the current position
+        let rule = EscapeRule::pattern(r"^\\x[0-9a-f]{2}").unwrap();
+        assert_eq!(rule.try_match(r"a\x41", 1), Some(4));
+
+        let rule = EscapeRule::pattern(r"(?x) \\ x [0-9a-f]{2} # hex byte").unwrap();
+        assert_eq!(rule.try_match(r"\x41", 0), Some(4));
+        assert_eq!(rule.try_match(r"a\x41", 0), None);
+        assert!(EscapeRule::pattern("(").is_err());
+    }
+
+    #[test]
+    fn test_escape_transform_without_a_rule() {
+        let mut scanner = escaping_scanner();
+        assert!(!scanner.set_escape_transform(0, EscapeTransform::Remove));
+        assert!(scanner.escape_transform(0).is_none());
+
+        scanner.add_simple_escape('%');
+        assert!(scanner.set_escape_transform(0, EscapeTransform::Remove));
+        assert!(!scanner.set_escape_transform(1, EscapeTransform::Remove));
+        assert_eq!(value(&scanner, r#""a%"b" rest"#), "ab");
+    }
+
+    #[test]
+    fn test_default_backslash_escape() {
+        let mut scanner = escaping_scanner();
+        assert!(scanner.uses_default_backslash_escape());
+        // Skipped but kept as written
+        assert_eq!(value(&scanner, r#""a\"b" rest"#), "a\\\"b");
+        assert!(scanner.scan(r#""C:\" x"#).is_err());
+
+        scanner.set_default_backslash_escape(false);
+        assert_eq!(value(&scanner, r#""C:\" x"#), "C:\\");
+    }
+
+    #[test]
+    fn test_doubled_delimiter_escape() {
+        // SQL strings
+        let mut scanner = BlockScanner::new("'", "'", "String", None, false, false, false);
+        scanner.add_doubled_delimiter_escape();
+        scanner.set_default_backslash_escape(false);
+        assert_eq!(value(&scanner, "'it''s' rest"), "it''s");
+        scanner.set_transform_escapes(true);
+        assert_eq!(value(&scanner, "'it''s' rest"), "it's");
+        assert_eq!(value(&scanner, "'' || 'x'"), "");
+        assert_eq!(value(&scanner, "''''"), "'");
+        assert_eq!(value(&scanner, r"'C:\' x"), "C:\\");
+        assert!(scanner.scan("'it''").is_err());
+
+        // CSV fields, with a multi-character sequence as well
+        let mut scanner = BlockScanner::new("\"", "\"", "Field", None, false, false, true);
+        scanner.add_doubled_delimiter_escape();
+        scanner.add_doubled_escape("$$");
+        scanner.set_transform_escapes(true);
+        assert_eq!(value(&scanner, r#""say ""hi"" $$$$",next"#), r#""say "hi" $$""#);
+    }
+
+    #[test]
+    fn test_builder_doubled_delimiter_escape() {
+        let tokenizer = Tokenizer::builder()
+            .block("'", "'")
+            .doubled_delimiter_escape()
+            .no_default_backslash_escape()
+            .transform_escapes()
+            .kind("String")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize(r"'it''s' 'C:\'").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| token.value.as_str()).collect();
+        assert_eq!(values, ["it's", "C:\\"]);
+
+        let errors = Tokenizer::builder()
+            .block("'", "'").doubled_escape("").kind("String")
+            .build()
+            .err()
+            .expect("an empty doubled escape should be rejected");
+        assert_eq!(errors[0].message, "doubled escape sequences must not be empty");
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let mut scanner = BlockScanner::new("\"", "\"", "String", None, false, false, true);
+        scanner.add_escape_mapping("n", '\n');
+        scanner.add_simple_escape('\\');
+        scanner.set_typed_values(true);
+
+        // The value keeps the escapes, the literal has them transformed
+        let token = scanner.scan(r#""a\nb\"c" rest"#).unwrap().unwrap();
+        assert_eq!(token.value, r#""a\nb\"c""#);
+        assert_eq!(token.literal, Some(TokenValue::Str("a\nb\"c".to_string())));
+        assert!(token.has_flag(TokenFlags::HAD_ESCAPES));
+
+        scanner.set_transform_escapes(true);
+        let token = scanner.scan(r#""a\nb""#).unwrap().unwrap();
+        assert_eq!(token.value, "\"a\nb\"");
+        assert_eq!(token.literal, Some(TokenValue::Str("a\nb".to_string())));
+
+        let token = scanner.scan(r#""plain""#).unwrap().unwrap();
+        assert_eq!(token.literal, Some(TokenValue::Str("plain".to_string())));
+        assert!(token.flags.is_empty());
+
+        // Raw blocks have their content as written
+        let mut raw = BlockScanner::new("r\"", "\"", "String", None, false, true, false);
+        raw.set_typed_values(true);
+        let token = raw.scan(r#"r"a\n" x"#).unwrap().unwrap();
+        assert_eq!(token.literal, Some(TokenValue::Str("a\\n".to_string())));
+        assert!(!token.has_flag(TokenFlags::HAD_ESCAPES));
+    }
+
+    #[test]
+    fn test_unterminated_blocks() {
+        let mut scanner = BlockScanner::new("/*", "*/", "Comment", None, true, false, true);
+        assert!(matches!(scanner.scan("/* open"), Err(TokenizationError::UnmatchedBlockDelimiter(..))));
+
+        scanner.set_allow_unterminated(true);
+        let token = scanner.scan("/* a /* b */ c").unwrap().unwrap();
+        assert_eq!(token.value, "/* a /* b */ c");
+        assert!(token.has_flag(TokenFlags::UNTERMINATED));
+        assert_eq!(scanner.consumed_len("/* a /* b */ c", &token), 14);
+
+        let token = scanner.scan("/* done */ after").unwrap().unwrap();
+        assert!(!token.has_flag(TokenFlags::UNTERMINATED));
+
+        // Only the tokens of the block that ran to the end are flagged
+        let tokenizer = Tokenizer::builder()
+            .block("\"", "\"").allow_unterminated().typed_value().kind("String")
+            .regex(r"^\w+").kind("Word")
+            .build()
+            .unwrap();
+        let tokens = tokenizer.tokenize("\"ok\" x \"open\\\" y").unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert!(!tokens[0].has_flag(TokenFlags::UNTERMINATED));
+        assert_eq!(tokens[2].value, "open\\\" y");
+        assert_eq!(tokens[2].flags.iter().collect::<Vec<_>>(), [TokenFlags::HAD_ESCAPES, TokenFlags::UNTERMINATED]);
+        assert_eq!(tokens[2].literal, Some(TokenValue::Str("open\\\" y".to_string())));
+    }
+}
@@ -0,0 +1,185 @@
+use rb_tokenizer::{
+    scanners::{context_scanner::ContextScanFn, ScanContext, Scanner, ScannerType},
+    tokens::{Token, TokenizationError},
+    ParallelConfig, SplitBoundary, Tokenizer,
+};
+use std::sync::{Arc, Mutex};
+use crate::helpers::{token, values};
+
+/// `/.../` is a regex literal unless it follows something that ends an operand
+fn regex_literal(input: &str, context: &mut ScanContext) -> Result<Option<Token>, TokenizationError> {
+    if !input.starts_with('/') {
+        return Ok(None);
+    }
+    let after_operand = context
+        .previous()
+        .is_some_and(|previous| matches!(previous.token_type, "Identifier" | "Number") || previous.value == ")");
+    if after_operand {
+        return Ok(None);
+    }
+    Ok(input[1..].find('/').map(|end| token("Regex", &input[..end + 2])))
+}
+
+fn javascript_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.add_context_closure_scanner(Box::new(regex_literal));
+    tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+    tokenizer.add_regex_scanner(r"^\d+", "Number", None);
+    for symbol in ["/", "(", ")", "=", "-"] {
+        tokenizer.add_symbol_scanner(symbol, "Operator", None);
+    }
+    tokenizer
+}
+
+#[cfg(test)]
+mod context_scanner_tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_literal_depends_on_previous_token() {
+        let tokenizer = javascript_tokenizer();
+
+        assert_eq!(
+            values(&tokenizer.tokenize("x = a / b / c").unwrap()),
+            [
+                ("Identifier", "x"),
+                ("Operator", "="),
+                ("Identifier", "a"),
+                ("Operator", "/"),
+                ("Identifier", "b"),
+                ("Operator", "/"),
+                ("Identifier", "c"),
+            ]
+        );
+        assert_eq!(
+            values(&tokenizer.tokenize("x = (/b c/)").unwrap()),
+            [
+                ("Identifier", "x"),
+                ("Operator", "="),
+                ("Operator", "("),
+                ("Regex", "/b c/"),
+                ("Operator", ")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_whitespace_tokens_are_not_significant() {
+        let mut tokenizer = javascript_tokenizer();
+        tokenizer.set_tokenize_whitespace(true);
+
+        let tokens = tokenizer.tokenize("(  /a/").unwrap();
+        assert_eq!(values(&tokens), [("Operator", "("), ("Whitespace", "  "), ("Regex", "/a/")]);
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_context_closure_scanner(Box::new(|input, context| {
+            let unary = context.previous().is_none_or(|previous| previous.token_type == "Operator");
+            Ok((unary && input.starts_with('-')).then(|| token("Negate", "-")))
+        }));
+        tokenizer.add_regex_scanner(r"^\d+", "Number", None);
+        tokenizer.add_symbol_scanner("-", "Operator", None);
+
+        assert_eq!(
+            values(&tokenizer.tokenize("-1 - -2").unwrap()),
+            [("Negate", "-"), ("Number", "1"), ("Operator", "-"), ("Negate", "-"), ("Number", "2")]
+        );
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut tokenizer = Tokenizer::new();
+        // A backtick toggles between code and template text
+        tokenizer.add_context_closure_scanner(Box::new(|input, context| {
+            if input.starts_with('`') {
+                if context.mode() == "template" {
+                    context.pop_mode();
+                } else {
+                    context.push_mode("template");
+                }
+                return Ok(Some(token("Backtick", "`")));
+            }
+            if context.mode() == "template" {
+                let end = input.find('`').unwrap_or(input.len());
+                return Ok(Some(token("Text", &input[..end])));
+            }
+            Ok(None)
+        }));
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+
+        assert_eq!(
+            values(&tokenizer.tokenize("a `b c` d").unwrap()),
+            [
+                ("Identifier", "a"),
+                ("Backtick", "`"),
+                ("Text", "b c"),
+                ("Backtick", "`"),
+                ("Identifier", "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_reports_position() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut tokenizer = Tokenizer::new();
+        let recorded = Arc::clone(&seen);
+        tokenizer.add_context_closure_scanner(Box::new(move |input, context| {
+            if !input.starts_with('@') {
+                return Ok(None);
+            }
+            recorded.lock().unwrap().push((context.offset(), context.line(), context.column()));
+            Ok(Some(token("At", "@")))
+        }));
+
+        tokenizer.tokenize("@\n  @").unwrap();
+        assert_eq!(*seen.lock().unwrap(), [(0, 1, 1), (4, 2, 3)]);
+    }
+
+    #[test]
+    fn test_declined_scan_keeps_mode() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_context_closure_scanner(Box::new(|_, context| {
+            // Pushes a mode but never matches
+            context.push_mode("noise");
+            Ok(None)
+        }));
+        tokenizer.add_context_closure_scanner(Box::new(|input, context| {
+            assert_eq!(context.mode(), ScanContext::<&str>::DEFAULT_MODE);
+            Ok(input.starts_with('x').then(|| token("X", "x")))
+        }));
+
+        assert_eq!(values(&tokenizer.tokenize("x x").unwrap()), [("X", "x"), ("X", "x")]);
+    }
+
+    #[test]
+    fn test_standalone_scan_has_empty_context() {
+        let scan: ContextScanFn = Box::new(|input, context| {
+            assert!(context.previous().is_none());
+            Ok(input.starts_with('/').then(|| token("Regex", "/")))
+        });
+        let scanner = ScannerType::Context(Box::new(scan));
+
+        assert!(scanner.scan("/a/").unwrap().is_some());
+        assert_eq!(scanner.kind(), "context");
+    }
+
+    #[test]
+    fn test_parallel_tokenization_with_context_scanners() {
+        let tokenizer = javascript_tokenizer();
+        // Each "/" starts a line, so it is a regex literal only at the start of a chunk
+        let input = "a\n/ b /\n".repeat(100);
+        let parallel = ParallelConfig {
+            threads: 4,
+            min_chunk_size: 16,
+            boundary: SplitBoundary::Newline,
+        };
+
+        assert_eq!(
+            tokenizer.tokenize_parallel(&input, &parallel).unwrap(),
+            tokenizer.tokenize(&input).unwrap()
+        );
+    }
+}
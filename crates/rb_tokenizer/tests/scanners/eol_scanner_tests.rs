@@ -1,5 +1,6 @@
 extern crate rb_tokenizer;
 
+use rb_tokenizer::scanners::{EolScanner, Scanner};
 use rb_tokenizer::{Tokenizer, TokenizerConfig};
 
 fn get_eol_scanner_tokenizer() -> Tokenizer {
@@ -8,6 +9,7 @@ fn get_eol_scanner_tokenizer() -> Tokenizer {
         continue_on_error: true,
         error_tolerance_limit: 5,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 
@@ -252,4 +254,89 @@ mod eol_scanner_tests {
         assert!(result[1].value.contains("\"quotes\""));
         assert!(result[1].value.contains("\\escapes"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_line_continuation() {
+        let mut tokenizer = Tokenizer::new();
+        let mut directive = EolScanner::new("#", "Preprocessor", None, true);
+        directive.set_line_continuation(Some("\\"));
+        tokenizer.register_scanner(None, 0, directive).unwrap();
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+
+        let tokens = tokenizer.tokenize("#define MAX \\\n  10 \\\r\n  + 1\nnext").unwrap();
+
+        assert_eq!(tokens[0].value, "#define MAX \\\n  10 \\\r\n  + 1\n");
+        assert_eq!((tokens[1].value.as_str(), tokens[1].line, tokens[1].column), ("next", 4, 1));
+    }
+
+    #[test]
+    fn test_continuation_only_counts_at_line_end() {
+        let mut scanner = EolScanner::new("#", "Preprocessor", None, true);
+        scanner.set_line_continuation(Some("\\"));
+
+        assert_eq!(scanner.scan("# a \\ b\nc").unwrap().unwrap().value, "# a \\ b\n");
+        assert_eq!(scanner.scan("#\\").unwrap().unwrap().value, "#\\");
+    }
+
+    #[test]
+    fn test_excluded_terminator_is_still_consumed() {
+        let mut tokenizer = Tokenizer::new();
+        let mut comment = EolScanner::new("//", "Comment", None, false);
+        comment.set_include_terminator(false);
+        tokenizer.register_scanner(None, 0, comment).unwrap();
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+
+        let tokens = tokenizer.tokenize("// note\r\nx").unwrap();
+
+        assert_eq!(tokens[0].value, " note");
+        assert_eq!((tokens[1].value.as_str(), tokens[1].line, tokens[1].column), ("x", 2, 1));
+    }
+
+    #[test]
+    fn test_terminator_ends_the_token() {
+        let mut tokenizer = Tokenizer::new();
+        let mut comment = EolScanner::new("<%#", "Comment", None, false);
+        comment.add_terminator("%>");
+        comment.add_terminator("-%>");
+        tokenizer.register_scanner(None, 0, comment).unwrap();
+        tokenizer.add_symbol_scanner("%>", "TagEnd", None);
+        tokenizer.add_symbol_scanner("-%>", "TagEnd", None);
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+
+        let tokens = tokenizer.tokenize("<%# note %> x <%# trim -%>\n<%# open\ny").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| (token.token_type, token.value.as_str())).collect();
+
+        assert_eq!(
+            values,
+            [
+                ("Comment", " note "),
+                ("TagEnd", "%>"),
+                ("Identifier", "x"),
+                ("Comment", " trim "),
+                ("TagEnd", "-%>"),
+                ("Comment", " open\n"),
+                ("Identifier", "y"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_eol_options() {
+        let tokenizer = Tokenizer::builder()
+            .eol("#").continuation("\\").exclude_terminator().terminator("?>").keep_delimiter().kind("Directive")
+            .regex(r"^[a-z]+").kind("Identifier")
+            .symbol("?>").kind("End")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("#a \\\nb\nc #d ?>").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| (token.token_type, token.value.as_str())).collect();
+        assert_eq!(
+            values,
+            [("Directive", "#a \\\nb"), ("Identifier", "c"), ("Directive", "#d "), ("End", "?>")]
+        );
+
+        let errors = Tokenizer::builder().eol("#").terminator("").kind("Directive").build().err().unwrap();
+        assert_eq!(errors[0].message, "EOL terminators must not be empty");
+    }
+}
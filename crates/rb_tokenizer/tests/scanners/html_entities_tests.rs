@@ -0,0 +1,125 @@
+use rb_tokenizer::scanners::block_scanner::BlockScanner;
+use rb_tokenizer::scanners::html_entities;
+use rb_tokenizer::scanners::scanner::Scanner;
+use rb_tokenizer::tokens::TokenizationError;
+use rb_tokenizer::Tokenizer;
+
+/// An HTML attribute value with entities resolved
+fn attribute_scanner() -> BlockScanner {
+    let mut scanner = BlockScanner::new("\"", "\"", "Attribute", None, false, false, false);
+    scanner.add_named_escape('&', ';', html_entities::MAX_NAME_LEN + 2);
+    scanner.set_transform_escapes(true);
+    scanner.set_html_entities(true);
+    scanner
+}
+
+fn value(scanner: &BlockScanner, input: &str) -> String {
+    scanner.scan(input).unwrap().unwrap().value
+}
+
+#[cfg(test)]
+mod html_entities_tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(html_entities::lookup("amp"), Some("&"));
+        assert_eq!(html_entities::lookup("hellip"), Some("\u{2026}"));
+        assert_eq!(html_entities::lookup("NotEqualTilde"), Some("\u{2242}\u{338}"));
+        assert_eq!(html_entities::lookup("CounterClockwiseContourIntegral"), Some("\u{2233}"));
+        assert_eq!(html_entities::lookup("AMP"), Some("&"));
+        assert_eq!(html_entities::lookup("bogus"), None);
+        assert_eq!(html_entities::lookup("amp;"), None);
+    }
+
+    #[test]
+    fn test_named_references() {
+        let scanner = attribute_scanner();
+
+        assert_eq!(value(&scanner, "\"a &lt; b&hellip;\""), "a < b\u{2026}");
+        // Two code points
+        assert_eq!(value(&scanner, "\"&NotEqualTilde;\""), "\u{2242}\u{338}");
+        assert_eq!(value(&scanner, "\"&CounterClockwiseContourIntegral;\""), "\u{2233}");
+        // Unknown names are kept as written
+        assert_eq!(value(&scanner, "\"&bogus; &amp\""), "&bogus; &amp");
+    }
+
+    #[test]
+    fn test_numeric_references() {
+        let scanner = attribute_scanner();
+
+        assert_eq!(value(&scanner, "\"&#123;&#x1F600;&#X41;\""), "{\u{1F600}A");
+        // NUL, surrogates and code points past U+10FFFF are replaced
+        assert_eq!(value(&scanner, "\"&#0;&#xD800;&#x110000;&#99999999999;\""), "\u{FFFD}".repeat(4));
+        // Malformed references are kept as written
+        assert_eq!(value(&scanner, "\"&#; &#x; &#12a;\""), "&#; &#x; &#12a;");
+    }
+
+    #[test]
+    fn test_c1_numeric_references_read_as_windows_1252() {
+        let scanner = attribute_scanner();
+
+        assert_eq!(value(&scanner, "\"&#128;&#150;&#x9F;\""), "\u{20AC}\u{2013}\u{178}");
+        // Undefined in windows-1252, so kept as the control character
+        assert_eq!(value(&scanner, "\"&#x81;&#x9D;\""), "\u{81}\u{9D}");
+        assert_eq!(html_entities::numeric(0x7F), '\u{7F}');
+        assert_eq!(html_entities::numeric(0xA0), '\u{A0}');
+    }
+
+    #[test]
+    fn test_escape_map_takes_precedence() {
+        let mut scanner = attribute_scanner();
+        scanner.add_escape_mapping("amp", "and");
+        scanner.add_escape_mapping("brand", "rb_tokenizer");
+
+        assert_eq!(value(&scanner, "\"&brand; &amp; &lt;\""), "rb_tokenizer and <");
+    }
+
+    #[test]
+    fn test_table_is_opt_in() {
+        let mut scanner = attribute_scanner();
+        scanner.set_html_entities(false);
+        scanner.add_escape_mapping("lt", '<');
+
+        assert_eq!(value(&scanner, "\"&lt; &gt; &#65;\""), "< &gt; &#65;");
+    }
+
+    #[test]
+    fn test_reject_unknown_entities() {
+        let mut scanner = attribute_scanner();
+        scanner.set_reject_unknown_entities(true);
+
+        assert_eq!(value(&scanner, "\"&amp;&#65;\""), "&A");
+        match scanner.scan("\"a &bogus; b\"") {
+            Err(TokenizationError::UnknownEntity(entity)) => assert_eq!(entity, "&bogus;"),
+            other => panic!("expected an unknown entity error, got {:?}", other),
+        }
+
+        // Checked even when the value is kept as written
+        scanner.set_transform_escapes(false);
+        assert_eq!(value(&scanner, "\"&amp;\""), "&amp;");
+        assert!(scanner.scan("\"&bogus;\"").is_err());
+        assert_eq!(
+            TokenizationError::UnknownEntity("&bogus;".to_string()).to_string(),
+            "Unknown entity: '&bogus;'"
+        );
+    }
+
+    #[test]
+    fn test_builder_options() {
+        let tokenizer = Tokenizer::builder()
+            .block("\"", "\"")
+            .named_escape('&', ';', 40)
+            .escape_mapping("dots", "...")
+            .transform_escapes()
+            .html_entities()
+            .reject_unknown_entities()
+            .kind("String")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("\"&dots;&copy;\"").unwrap();
+        assert_eq!(tokens[0].value, "...\u{A9}");
+        assert!(tokenizer.tokenize("\"&nope;\"").is_err());
+    }
+}
@@ -4,8 +4,12 @@ mod block_scanner_tests;
 #[cfg(test)]
 mod eol_scanner_tests;
 #[cfg(test)]
+mod html_entities_tests;
+#[cfg(test)]
 mod closure_scanner_tests;
 #[cfg(test)]
+mod context_scanner_tests;
+#[cfg(test)]
 mod regex_scanner_tests;
 #[cfg(test)]
 mod scanner_types_tests;
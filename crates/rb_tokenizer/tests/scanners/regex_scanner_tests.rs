@@ -1,6 +1,8 @@
 use rb_tokenizer::{
     scanners::regex_scanner::RegexScanner,
     scanners::scanner::Scanner,
+    tokens::TokenValue,
+    Tokenizer,
 };
 
 #[cfg(test)]
@@ -69,4 +71,144 @@ mod regex_scanner_tests {
         assert_eq!(token.token_type, "ASSIGNMENT");
         assert_eq!(token.value, "value=42"); // Should capture the entire match
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_regex_scanner_literal() {
+        let scanner = RegexScanner::new(r"^(0x[0-9a-fA-F]+|\d+)", "NUMBER", None).with_literal(|text| {
+            let value = match text.strip_prefix("0x") {
+                Some(hex) => i128::from_str_radix(hex, 16),
+                None => text.parse(),
+            };
+            value.ok().map(TokenValue::Int)
+        });
+
+        let token = scanner.scan("0xff + 1").unwrap().unwrap();
+        assert_eq!(token.value, "0xff");
+        assert_eq!(token.literal, Some(TokenValue::Int(255)));
+        assert_eq!(scanner.scan("12").unwrap().unwrap().literal, Some(TokenValue::Int(12)));
+
+        // A conversion that fails leaves the token without a literal
+        let token = scanner.scan("999999999999999999999999999999999999999999").unwrap().unwrap();
+        assert_eq!(token.literal, None);
+
+        // Without a conversion there is no literal
+        let plain = RegexScanner::new(r"^\d+", "NUMBER", None);
+        assert_eq!(plain.scan("12").unwrap().unwrap().literal, None);
+    }
+
+    #[test]
+    fn test_builder_regex_literal() {
+        let tokenizer = Tokenizer::builder()
+            .regex(r"^(true|false)\b")
+            .literal(|text| Some(TokenValue::Bool(text == "true")))
+            .kind("Bool")
+            .regex(r"^\d+\.\d+")
+            .literal(|text| text.parse().ok().map(TokenValue::Float))
+            .kind("Float")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("true 2.5 false").unwrap();
+        let literals: Vec<_> = tokens.iter().map(|token| token.literal.clone()).collect();
+        assert_eq!(
+            literals,
+            [Some(TokenValue::Bool(true)), Some(TokenValue::Float(2.5)), Some(TokenValue::Bool(false))]
+        );
+    }
+
+    #[test]
+    fn test_regex_scanner_value_group() {
+        let scanner = RegexScanner::new(r#"^"(?P<content>[^"]*)""#, "STRING", None).with_value_group("content");
+        let token = scanner.scan(r#""hello" world"#).unwrap().unwrap();
+        assert_eq!(token.value, "hello");
+        // The quotes are still consumed
+        assert_eq!(scanner.consumed_len(r#""hello" world"#, &token), 7);
+
+        let scanner = RegexScanner::new(r"^(\w+)=(\d+)", "ASSIGNMENT", None)
+            .with_value_group(2)
+            .with_literal(|text| text.parse().ok().map(TokenValue::Int));
+        let token = scanner.scan("value=42 next").unwrap().unwrap();
+        assert_eq!(token.value, "42");
+        assert_eq!(token.literal, Some(TokenValue::Int(42)));
+
+        // A group that does not take part in the match gives an empty value
+        let scanner = RegexScanner::new(r"^#(\d+)?", "HASH", None).with_value_group(1);
+        assert_eq!(scanner.scan("#x").unwrap().unwrap().value, "");
+    }
+
+    #[test]
+    fn test_regex_scanner_sub_type_groups() {
+        let scanner = RegexScanner::new(r"^(?:(?P<Hex>0x[0-9a-fA-F]+)|(?P<Bin>0b[01]+)|\d+)", "NUMBER", Some("Dec"))
+            .with_sub_type_group("Hex", "Hex")
+            .with_sub_type_group("Bin", "Binary");
+
+        let sub_type = |input: &str| scanner.scan(input).unwrap().unwrap().token_sub_type;
+        assert_eq!(sub_type("0x1F"), Some("Hex"));
+        assert_eq!(sub_type("0b101"), Some("Binary"));
+        // No group matched, so the scanner's sub-type is used
+        assert_eq!(sub_type("42"), Some("Dec"));
+        assert!(scanner.scan("0x1F").unwrap().unwrap().captures.is_empty());
+    }
+
+    #[test]
+    fn test_regex_scanner_capture_metadata() {
+        let scanner = RegexScanner::new(r"^(?P<number>\d+)(?:\.(\d+))?(?P<unit>px|em)?", "LENGTH", None)
+            .with_value_group("number")
+            .with_capture_metadata();
+
+        let token = scanner.scan("12.5em;").unwrap().unwrap();
+        assert_eq!(token.value, "12");
+        assert_eq!(
+            token.captures,
+            [("2".to_string(), "5".to_string()), ("unit".to_string(), "em".to_string())]
+        );
+        assert_eq!(token.capture("unit"), Some("em"));
+        assert_eq!(token.capture("number"), None);
+
+        // Groups that do not take part are left out
+        let token = scanner.scan("7;").unwrap().unwrap();
+        assert!(token.captures.is_empty());
+    }
+
+    #[test]
+    fn test_regex_scanner_check_groups() {
+        let scanner = RegexScanner::new(r"^(?P<a>x)(y)", "T", None);
+        assert_eq!(scanner.check_groups(), Ok(()));
+        assert_eq!(RegexScanner::new(r"^(?P<a>x)(y)", "T", None).with_value_group(2).check_groups(), Ok(()));
+        assert_eq!(
+            RegexScanner::new(r"^(?P<a>x)(y)", "T", None).with_value_group(3).check_groups(),
+            Err("capture group 3 is not in regex '^(?P<a>x)(y)'".to_string())
+        );
+        assert_eq!(
+            RegexScanner::new(r"^(?P<a>x)(y)", "T", None).with_sub_type_group("b", "B").check_groups(),
+            Err("capture group 'b' is not in regex '^(?P<a>x)(y)'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_capture_groups() {
+        let tokenizer = Tokenizer::builder()
+            .regex(r#"^(?:'(?P<single>[^']*)'|"(?P<double>[^"]*)")"#)
+            .value_group(0)
+            .sub_kind_group("single", "Single")
+            .sub_kind_group("double", "Double")
+            .capture_metadata()
+            .kind("String")
+            .regex(r"^\w+").kind("Word")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize(r#"'a b' x "c""#).unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| (token.value.as_str(), token.token_sub_type)).collect();
+        assert_eq!(values, [("'a b'", Some("Single")), ("x", None), ("\"c\"", Some("Double"))]);
+        assert_eq!(tokens[0].capture("single"), Some("a b"));
+        assert_eq!(tokens[2].column, 9);
+
+        let errors = Tokenizer::builder()
+            .regex(r"^(\d+)").value_group("digits").kind("Number")
+            .build()
+            .err()
+            .expect("an unknown group should be rejected");
+        assert_eq!(errors[0].message, r"capture group 'digits' is not in regex '^(\d+)'");
+    }
+}
@@ -47,13 +47,7 @@ mod scanner_types_tests {
         impl CallbackScanner for TestCallbackScanner {
             fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
                 if input.starts_with("test") {
-                    Ok(Some(Token {
-                        token_type: "TEST",
-                        token_sub_type: None,
-                        value: "test".to_string(),
-                        line: 0,
-                        column: 0,
-                    }))
+                    Ok(Some(Token::new("TEST", None, "test".to_string(), 0, 0)))
                 } else {
                     Ok(None)
                 }
@@ -1,8 +1,17 @@
 use rb_tokenizer::{
-    scanners::symbol_scanner::SymbolScanner,
+    scanners::symbol_scanner::{CaseMatching, SymbolOptions, SymbolScanner},
     scanners::scanner::Scanner,
+    Tokenizer,
 };
 
+fn scanner_with(symbol: &str, options: SymbolOptions) -> SymbolScanner {
+    SymbolScanner::with_options(symbol, "SYMBOL", None, options)
+}
+
+fn case(case: CaseMatching) -> SymbolOptions {
+    SymbolOptions { case, ..SymbolOptions::default() }
+}
+
 #[cfg(test)]
 mod symbol_scanner_tests {
     use super::*;
@@ -114,4 +123,101 @@ mod symbol_scanner_tests {
         let token_option = result.unwrap();
         assert!(token_option.is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ascii_case_insensitive_keeps_value_as_written() {
+        let scanner = scanner_with("select", case(CaseMatching::AsciiInsensitive));
+
+        assert_eq!(scanner.match_len("SELECT * FROM table"), Some(6));
+        let token = scanner.scan("SeLeCt * FROM table").unwrap().unwrap();
+        assert_eq!(token.value, "SeLeCt");
+        assert_eq!(scanner.match_len("selec"), None);
+
+        // Only ASCII letters are folded
+        let scanner = scanner_with("été", case(CaseMatching::AsciiInsensitive));
+        assert_eq!(scanner.match_len("ÉTÉ"), None);
+        assert_eq!(scanner.match_len("éTé"), Some("éTé".len()));
+    }
+
+    #[test]
+    fn test_unicode_case_insensitive() {
+        let scanner = scanner_with("été", case(CaseMatching::UnicodeInsensitive));
+        assert_eq!(scanner.match_len("ÉTÉ!"), Some("ÉTÉ".len()));
+
+        // The Kelvin sign lowercases to an ASCII `k`
+        let scanner = scanner_with("kind", case(CaseMatching::UnicodeInsensitive));
+        let token = scanner.scan("\u{212A}IND x").unwrap().unwrap();
+        assert_eq!(token.value, "\u{212A}IND");
+        assert_eq!(scanner.match_len("kin"), None);
+    }
+
+    #[test]
+    fn test_word_boundary() {
+        let options = SymbolOptions { word_boundary: true, ..SymbolOptions::default() };
+        let scanner = scanner_with("in", options);
+
+        assert_eq!(scanner.match_len("in x"), Some(2));
+        assert_eq!(scanner.match_len("in(x)"), Some(2));
+        assert_eq!(scanner.match_len("in"), Some(2));
+        assert_eq!(scanner.match_len("index"), None);
+        assert_eq!(scanner.match_len("in_range"), None);
+        assert_eq!(scanner.match_len("iné"), None);
+
+        // A symbol ending in punctuation can be followed by anything
+        let scanner = scanner_with("(", options);
+        assert_eq!(scanner.match_len("(x"), Some(1));
+    }
+
+    #[test]
+    fn test_keyword_options() {
+        let scanner = scanner_with("from", SymbolOptions::keyword());
+        assert_eq!(scanner.match_len("FROM t"), Some(4));
+        assert_eq!(scanner.match_len("FROMAGE"), None);
+        assert!(!SymbolOptions::keyword().is_exact());
+        assert!(SymbolOptions::default().is_exact());
+    }
+
+    #[test]
+    fn test_normalized_matching() {
+        let options = SymbolOptions { normalize: true, ..SymbolOptions::default() };
+
+        // A precomposed symbol matches the decomposed input and the other way around
+        let scanner = scanner_with("caf\u{e9}", options);
+        assert_eq!(scanner.match_len("cafe\u{301} au lait"), Some("cafe\u{301}".len()));
+        assert_eq!(scanner.match_len("caf\u{e9}"), Some("caf\u{e9}".len()));
+        let scanner = scanner_with("cafe\u{301}", options);
+        assert_eq!(scanner.match_len("caf\u{e9}!"), Some("caf\u{e9}".len()));
+
+        // A combining mark after the match would change its last character
+        let scanner = scanner_with("e", options);
+        assert_eq!(scanner.match_len("e\u{301}"), None);
+        assert_eq!(scanner.match_len("ex"), Some(1));
+
+        // Without normalization the forms differ
+        let scanner = scanner_with("caf\u{e9}", SymbolOptions::default());
+        assert_eq!(scanner.match_len("cafe\u{301}"), None);
+    }
+
+    #[test]
+    fn test_normalized_case_insensitive() {
+        let options = SymbolOptions {
+            case: CaseMatching::UnicodeInsensitive,
+            normalize: true,
+            word_boundary: true,
+        };
+        let scanner = scanner_with("caf\u{e9}", options);
+        assert_eq!(scanner.match_len("CAFE\u{301} noir"), Some("CAFE\u{301}".len()));
+        assert_eq!(scanner.match_len("CAFE\u{301}S"), None);
+    }
+
+    #[test]
+    fn test_tokenizer_symbol_scanner_with_options() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_symbol_scanner_with_options("in", "KEYWORD", None, SymbolOptions::keyword());
+        tokenizer.add_regex_scanner(r"^[a-zA-Z_]\w*", "IDENTIFIER", None);
+
+        let tokens = tokenizer.tokenize("IN index In").unwrap();
+        let values: Vec<_> = tokens.iter().map(|token| (token.token_type, token.value.as_str())).collect();
+        assert_eq!(values, [("KEYWORD", "IN"), ("IDENTIFIER", "index"), ("KEYWORD", "In")]);
+    }
+}
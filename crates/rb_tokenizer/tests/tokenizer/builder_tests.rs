@@ -0,0 +1,211 @@
+use rb_tokenizer::{LineEndings, Tokenizer, TokenizerBuilder};
+use crate::helpers::values;
+
+#[cfg(test)]
+mod builder_tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_matches_positional_api() {
+        let built = Tokenizer::builder()
+            .block("/*", "*/").nesting().raw().keep_delimiters().kind("Comment")
+            .eol("//").sub_kind("Line").kind("Comment")
+            .symbol("+").sub_kind("Plus").kind("Operator")
+            .regex(r"^\d+").kind("Number")
+            .build()
+            .unwrap();
+
+        let mut manual = Tokenizer::new();
+        manual.add_block_scanner("/*", "*/", "Comment", None, true, true, true);
+        manual.add_eol_scanner("//", "Comment", Some("Line"), false);
+        manual.add_symbol_scanner("+", "Operator", Some("Plus"));
+        manual.add_regex_scanner(r"^\d+", "Number", None);
+
+        let input = "1 + /* a /* b */ c */ 2 // sum\n3";
+        assert_eq!(built.tokenize(input).unwrap(), manual.tokenize(input).unwrap());
+        assert_eq!(
+            values(&built.tokenize(input).unwrap()),
+            [
+                ("Number", "1"),
+                ("Operator", "+"),
+                ("Comment", "/* a /* b */ c */"),
+                ("Number", "2"),
+                ("Comment", " sum\n"),
+                ("Number", "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delimiters_are_dropped_by_default() {
+        let tokenizer = TokenizerBuilder::new()
+            .block("<<", ">>").kind("Quote")
+            .eol("#").keep_delimiter().kind("Comment")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("<<hi>> # note").unwrap();
+        assert_eq!(values(&tokens), [("Quote", "hi"), ("Comment", "# note")]);
+    }
+
+    #[test]
+    fn test_block_escape_configuration() {
+        let tokenizer = Tokenizer::builder()
+            .block("<", ">")
+            .named_escape('&', ';', 6)
+            .escape_mapping("gt", '>')
+            .escape_mapping("amp", '&')
+            .transform_escapes()
+            .kind("Tag")
+            .build()
+            .unwrap();
+
+        let tokens = tokenizer.tokenize("<a &gt; b &amp; c>").unwrap();
+        assert_eq!(values(&tokens), [("Tag", "a > b & c")]);
+    }
+
+    #[test]
+    fn test_configuration_options() {
+        let tokenizer = Tokenizer::builder()
+            .tokenize_whitespace(true)
+            .continue_on_error(true)
+            .error_tolerance_limit(3)
+            .line_endings(LineEndings::Lf)
+            .eol("--").kind("Comment")
+            .build()
+            .unwrap();
+
+        let config = tokenizer.config();
+        assert!(config.tokenize_whitespace);
+        assert!(config.continue_on_error);
+        assert_eq!(config.error_tolerance_limit, 3);
+
+        // The EOL scanner follows the builder's line endings, so "\r" does not end the comment
+        let tokens = tokenizer.tokenize("--a\rb\nc").unwrap();
+        assert_eq!(values(&tokens), [("Comment", "a\rb\n")]);
+        assert_eq!(tokenizer.last_errors().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_build_reports_every_problem() {
+        let errors = Tokenizer::builder()
+            .symbol("=").kind("Assign")
+            .symbol("==").kind("Equal")
+            .symbol("").kind("Nothing")
+            .regex(r"^(unclosed").kind("Bad")
+            .regex(r"\d+").kind("Unanchored")
+            .regex(r"^\d*").kind("Empty")
+            .block("'", "'").raw().escape('\\').kind("String")
+            .block("\"", "\"").pattern_escape("(").kind("String")
+            .eol("").kind("Comment")
+            .build()
+            .err()
+            .expect("the definition should be rejected");
+
+        let scanners: Vec<usize> = errors.iter().map(|error| error.scanner).collect();
+        assert_eq!(scanners, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(errors[0].to_string(), "Scanner 1: symbol '==' is unreachable: scanner 0 matches '=' first");
+        assert!(errors[2].message.starts_with("invalid regex"));
+        assert!(errors[3].message.contains("anchored"));
+        assert!(errors[4].message.contains("empty string"));
+        assert!(errors[5].message.contains("cannot have escape rules"));
+        assert!(errors[6].message.starts_with("invalid escape pattern"));
+    }
+
+    #[test]
+    fn test_build_optimizes_without_changing_results() {
+        let built = Tokenizer::builder()
+            .continue_on_error(true)
+            .tokenize_whitespace(true)
+            .block("\"", "\"").keep_delimiters().kind("String")
+            .regex(r"^[a-zA-Zé_]\w*").kind("Identifier")
+            .regex(r"^(?i)select").kind("Keyword")
+            .regex(r"^-?\d+").kind("Number")
+            .symbol("-").kind("Minus")
+            .build()
+            .unwrap();
+        assert!(built.is_optimized());
+
+        let mut plain = Tokenizer::new();
+        plain.set_continue_on_error(true).set_tokenize_whitespace(true);
+        plain.add_block_scanner("\"", "\"", "String", None, false, false, true);
+        plain.add_regex_scanner(r"^[a-zA-Zé_]\w*", "Identifier", None);
+        plain.add_regex_scanner(r"^(?i)select", "Keyword", None);
+        plain.add_regex_scanner(r"^-?\d+", "Number", None);
+        plain.add_symbol_scanner("-", "Minus", None);
+        assert!(!plain.is_optimized());
+
+        for input in ["SELECT été - -12 \"x y\" ?", "-a", "Sélect 3-4"] {
+            assert_eq!(format!("{:?}", built.tokenize(input)), format!("{:?}", plain.tokenize(input)), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_anchor_after_inline_flags() {
+        let tokenizer = Tokenizer::builder()
+            .regex(r"(?i)^select\b").kind("Keyword")
+            .regex(r"^[a-z]+").kind("Word")
+            .build()
+            .unwrap();
+
+        assert_eq!(values(&tokenizer.tokenize("SELECT").unwrap()), [("Keyword", "SELECT")]);
+    }
+
+    #[test]
+    fn test_adding_scanners_drops_the_index() {
+        let mut tokenizer = Tokenizer::builder().symbol("+").kind("Plus").build().unwrap();
+        assert!(tokenizer.is_optimized());
+
+        tokenizer.add_symbol_scanner("-", "Minus", None);
+        assert!(!tokenizer.is_optimized());
+        assert_eq!(tokenizer.tokenize("+-").unwrap().len(), 2);
+
+        // Unanchored patterns can match past the first byte, so they are tried everywhere
+        tokenizer.add_regex_scanner(r"[a-z]+", "Word", None);
+        tokenizer.optimize();
+        assert_eq!(values(&tokenizer.tokenize("1abc").unwrap())[0], ("Word", "abc"));
+    }
+
+    #[test]
+    fn test_symbol_matching_options() {
+        let tokenizer = Tokenizer::builder()
+            .symbol("in").ignore_ascii_case().word_boundary().kind("In")
+            .symbol("été").ignore_case().kind("Summer")
+            .symbol("caf\u{e9}").normalize().kind("Cafe")
+            .regex(r"^\w+").kind("Word")
+            .build()
+            .unwrap();
+        assert!(tokenizer.is_optimized());
+
+        let tokens = tokenizer.tokenize("IN index ÉTÉ cafe\u{301} In").unwrap();
+        assert_eq!(
+            values(&tokens),
+            [("In", "IN"), ("Word", "index"), ("Summer", "ÉTÉ"), ("Cafe", "cafe\u{301}"), ("In", "In")]
+        );
+    }
+
+    #[test]
+    fn test_symbol_options_decide_reachability() {
+        // `int` cannot match where the word boundary lets `in` match
+        assert!(Tokenizer::builder()
+            .symbol("in").word_boundary().kind("In")
+            .symbol("int").kind("Int")
+            .build()
+            .is_ok());
+
+        // A case-sensitive symbol does not match other cases of a later one
+        assert!(Tokenizer::builder()
+            .symbol("in").kind("In")
+            .symbol("INT").ignore_ascii_case().kind("Int")
+            .build()
+            .is_ok());
+
+        let errors = Tokenizer::builder()
+            .symbol("in").ignore_ascii_case().kind("In")
+            .symbol("INT").kind("Int")
+            .build()
+            .err()
+            .expect("'INT' starts with a match of 'in'");
+        assert_eq!(errors[0].to_string(), "Scanner 1: symbol 'INT' is unreachable: scanner 0 matches 'in' first");
+    }
+}
@@ -0,0 +1,136 @@
+use rb_tokenizer::{
+    tokenizers::PositionTracker,
+    tokens::TokenizationError,
+    ColumnMode, Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod column_mode_tests {
+    use super::*;
+
+    fn create_test_tokenizer(column_mode: ColumnMode) -> Tokenizer {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            column_mode,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+        tokenizer.add_symbol_scanner("=", "Operator", Some("Assign"));
+        tokenizer
+    }
+
+    fn columns(tokenizer: &Tokenizer, input: &str) -> Vec<(usize, usize)> {
+        tokenizer
+            .tokenize(input)
+            .unwrap()
+            .iter()
+            .map(|t| (t.line, t.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_column_mode_is_chars() {
+        let tokenizer = Tokenizer::new();
+        assert_eq!(tokenizer.config().column_mode, ColumnMode::Chars);
+    }
+
+    #[test]
+    fn test_tracker_modes() {
+        // "a", CJK wide char, emoji (surrogate pair in UTF-16), "e" + combining acute
+        let text = "a日😀e\u{301}";
+
+        let advance = |mode| {
+            let mut tracker = PositionTracker::new(mode);
+            tracker.advance(text);
+            tracker.column
+        };
+
+        assert_eq!(advance(ColumnMode::Bytes), 1 + 1 + 3 + 4 + 1 + 2);
+        assert_eq!(advance(ColumnMode::Chars), 1 + 5);
+        assert_eq!(advance(ColumnMode::Utf16), 1 + 1 + 1 + 2 + 1 + 1);
+        assert_eq!(advance(ColumnMode::Graphemes), 1 + 4);
+        assert_eq!(advance(ColumnMode::DisplayWidth { tab_width: 4 }), 1 + 1 + 2 + 2 + 1);
+    }
+
+    #[test]
+    fn test_tracker_tab_stops() {
+        let mut tracker = PositionTracker::new(ColumnMode::DisplayWidth { tab_width: 4 });
+
+        tracker.advance("\t");
+        assert_eq!(tracker.column, 5);
+        tracker.advance("ab\t");
+        assert_eq!(tracker.column, 9);
+        tracker.advance("\nx\t");
+        assert_eq!((tracker.line, tracker.column), (2, 5));
+    }
+
+    #[test]
+    fn test_tracker_counts_split_clusters_once() {
+        let mut tracker = PositionTracker::new(ColumnMode::Graphemes);
+        tracker.advance("ae");
+        assert_eq!(tracker.column, 3);
+        // The combining mark extends the "e" cluster already counted
+        tracker.advance("\u{301}");
+        assert_eq!(tracker.column, 3);
+        tracker.advance("\u{301}b");
+        assert_eq!(tracker.column, 4);
+        tracker.advance("\n\u{301}");
+        assert_eq!((tracker.line, tracker.column), (2, 2));
+    }
+
+    #[test]
+    fn test_token_boundary_inside_grapheme_cluster() {
+        let mut tokenizer = create_test_tokenizer(ColumnMode::Graphemes);
+        tokenizer.add_regex_scanner(r"^\p{M}+", "Mark", None);
+
+        // The identifier ends between "e" and its combining accent
+        let tokens = tokenizer.tokenize("ae\u{301} = x").unwrap();
+        let positions: Vec<_> = tokens.iter().map(|t| (t.token_type, t.column)).collect();
+        assert_eq!(
+            positions,
+            [("Identifier", 1), ("Mark", 3), ("Whitespace", 3), ("Operator", 4), ("Whitespace", 5), ("Identifier", 6)]
+        );
+    }
+
+    #[test]
+    fn test_token_columns_follow_mode() {
+        // U+3000 IDEOGRAPHIC SPACE is whitespace: 3 bytes, 1 char, 1 UTF-16 unit, 2 cells wide
+        let input = "a\u{3000}=\tb";
+
+        let chars = create_test_tokenizer(ColumnMode::Chars);
+        assert_eq!(columns(&chars, input), vec![(1, 1), (1, 2), (1, 3), (1, 4), (1, 5)]);
+
+        let bytes = create_test_tokenizer(ColumnMode::Bytes);
+        assert_eq!(columns(&bytes, input), vec![(1, 1), (1, 2), (1, 5), (1, 6), (1, 7)]);
+
+        let display = create_test_tokenizer(ColumnMode::DisplayWidth { tab_width: 8 });
+        assert_eq!(columns(&display, input), vec![(1, 1), (1, 2), (1, 4), (1, 5), (1, 9)]);
+    }
+
+    #[test]
+    fn test_columns_reset_after_newline() {
+        let tokenizer = create_test_tokenizer(ColumnMode::DisplayWidth { tab_width: 4 });
+        let tokens = tokenizer.tokenize("a\n\tb").unwrap();
+
+        assert_eq!(tokens[2].value, "b");
+        assert_eq!((tokens[2].line, tokens[2].column), (2, 5));
+    }
+
+    #[test]
+    fn test_error_positions_follow_mode() {
+        let mut tokenizer = create_test_tokenizer(ColumnMode::Utf16);
+        tokenizer.set_continue_on_error(true);
+
+        // The emoji takes two UTF-16 units, so the '?' after it is at column 4
+        let tokens = tokenizer.tokenize("a😀?").unwrap();
+        assert_eq!(tokens.len(), 1);
+
+        let errors = tokenizer.last_errors().unwrap();
+        assert_eq!(errors.len(), 2);
+        match &errors[1] {
+            TokenizationError::UnrecognizedToken(msg) => assert!(msg.contains("line 1, column 4"), "{}", msg),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use rb_tokenizer::scanners::{BlockScanner, EolScanner, SymbolScanner};
+use rb_tokenizer::tokenizers::{ConflictResolution, MergeConflict};
+use rb_tokenizer::{LineEndings, Tokenizer};
+use crate::helpers::values;
+
+/// The scanners every dialect shares
+fn base_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.register_scanner(Some("comment"), 0, EolScanner::new("//", "Comment", None, true)).unwrap();
+    tokenizer.register_scanner(Some("string"), 0, BlockScanner::new("\"", "\"", "String", None, false, false, true)).unwrap();
+    tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+    tokenizer.add_symbol_scanner("+", "Operator", None);
+    tokenizer
+}
+
+#[cfg(test)]
+mod composition_tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_dialect_leaves_base_unchanged() {
+        let base = base_tokenizer();
+        let mut dialect = base.clone();
+        dialect.replace_scanner("comment", EolScanner::new("#", "Comment", None, true)).unwrap();
+        dialect.remove_scanner("string").unwrap();
+        dialect.add_symbol_scanner("-", "Operator", None);
+
+        assert_eq!(
+            values(&base.tokenize("a + \"b c\" // d").unwrap()),
+            [("Identifier", "a"), ("Operator", "+"), ("String", "\"b c\""), ("Comment", "// d")]
+        );
+        assert_eq!(
+            values(&dialect.tokenize("a - b # c").unwrap()),
+            [("Identifier", "a"), ("Operator", "-"), ("Identifier", "b"), ("Comment", "# c")]
+        );
+        assert!(base.tokenize("a - b").is_err());
+        assert_eq!(base.scanners().count(), 4);
+    }
+
+    #[test]
+    fn test_clone_keeps_optimization() {
+        let mut base = base_tokenizer();
+        base.optimize();
+        let dialect = base.clone();
+
+        assert!(dialect.is_optimized());
+        assert_eq!(dialect.tokenize("a + b").unwrap(), base.tokenize("a + b").unwrap());
+    }
+
+    #[test]
+    fn test_line_endings_on_a_clone_do_not_affect_the_base() {
+        let base = base_tokenizer();
+        let mut dialect = base.clone();
+        dialect.set_line_endings(LineEndings::Lf);
+
+        assert_eq!(values(&base.tokenize("// a\rb").unwrap()), [("Comment", "// a\r"), ("Identifier", "b")]);
+        assert_eq!(values(&dialect.tokenize("// a\rb").unwrap()), [("Comment", "// a\rb")]);
+    }
+
+    #[test]
+    fn test_merge_adds_scanners_by_priority() {
+        let mut tokenizer = base_tokenizer();
+        let mut operators = Tokenizer::new();
+        operators.add_symbol_scanner("-", "Operator", None);
+        operators.register_scanner(Some("arrow"), -1, SymbolScanner::new("->", "Arrow", None)).unwrap();
+
+        assert_eq!(tokenizer.merge(&operators, ConflictResolution::Fail), Ok(vec![]));
+        assert_eq!(
+            values(&tokenizer.tokenize("a -> b - c").unwrap()),
+            [("Identifier", "a"), ("Arrow", "->"), ("Identifier", "b"), ("Operator", "-"), ("Identifier", "c")]
+        );
+        assert_eq!(tokenizer.scanners().next().unwrap().name, Some("arrow"));
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let mut other = Tokenizer::new();
+        other.register_scanner(Some("comment"), 0, EolScanner::new("#", "Comment", None, true)).unwrap();
+        other.add_symbol_scanner("+", "Plus", None);
+        let expected = vec![MergeConflict::Name("comment".to_string()), MergeConflict::Literal("+".to_string())];
+
+        let mut failed = base_tokenizer();
+        assert_eq!(failed.merge(&other, ConflictResolution::Fail), Err(expected.clone()));
+        assert_eq!(values(&failed.tokenize("+").unwrap()), [("Operator", "+")]);
+        assert!(failed.tokenize("# a").is_err());
+
+        let mut kept = base_tokenizer();
+        assert_eq!(kept.merge(&other, ConflictResolution::KeepExisting), Ok(expected.clone()));
+        assert_eq!(values(&kept.tokenize("+ // a").unwrap()), [("Operator", "+"), ("Comment", "// a")]);
+
+        let mut replaced = base_tokenizer();
+        assert_eq!(replaced.merge(&other, ConflictResolution::Replace), Ok(expected));
+        assert_eq!(values(&replaced.tokenize("+ # a").unwrap()), [("Plus", "+"), ("Comment", "# a")]);
+        assert_eq!(replaced.scanners().count(), 4);
+    }
+
+    #[test]
+    fn test_merge_interned_tokenizers() {
+        let mut base = Tokenizer::interned();
+        base.add_named_symbol_scanner("+", "Operator", None);
+
+        // A dialect extends the base registry, so the two can be merged
+        let mut dialect = base.clone();
+        dialect.add_named_regex_scanner(r"^\d+", "Number", None);
+        base.merge(&dialect, ConflictResolution::KeepExisting).unwrap();
+        let tokens = base.tokenize("1 + 2").unwrap();
+        assert_eq!(base.type_name(tokens[0].token_type), "Number");
+        assert_eq!(base.type_name(tokens[1].token_type), "Operator");
+
+        let mut unrelated = Tokenizer::interned();
+        unrelated.add_named_symbol_scanner("-", "Minus", None);
+        assert_eq!(
+            base.merge(&unrelated, ConflictResolution::Replace),
+            Err(vec![MergeConflict::Registry])
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use rb_tokenizer::{Tokenizer, TokenizerConfig};
+use rb_tokenizer::{ColumnMode, Tokenizer, TokenizerConfig};
 
 #[cfg(test)]
 mod config_tests {
@@ -14,6 +14,7 @@ mod config_tests {
         assert!(!config.continue_on_error, "Default continue_on_error should be false");
         assert_eq!(config.error_tolerance_limit, 10, "Default error_tolerance_limit should be 10");
         assert!(config.track_token_positions, "Default track_token_positions should be true");
+        assert_eq!(config.column_mode, ColumnMode::Chars, "Default column_mode should be Chars");
     }
 
     #[test]
@@ -23,6 +24,7 @@ mod config_tests {
             continue_on_error: false,
             error_tolerance_limit: 5,
             track_token_positions: false,
+            ..TokenizerConfig::default()
         };
 
         let tokenizer = Tokenizer::with_config(custom_config);
@@ -80,6 +82,7 @@ mod config_tests {
             continue_on_error: false,
             error_tolerance_limit: 7,
             track_token_positions: false,
+            ..TokenizerConfig::default()
         };
 
         // Clone the config and verify it's equal
@@ -98,6 +101,7 @@ mod config_tests {
             continue_on_error: false,
             error_tolerance_limit: 3,
             track_token_positions: true,
+            ..TokenizerConfig::default()
         };
 
         let debug_output = format!("{:?}", config);
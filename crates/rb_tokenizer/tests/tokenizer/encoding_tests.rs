@@ -0,0 +1,175 @@
+use rb_tokenizer::{
+    tokenizers::{DecodedInput, Encoding},
+    tokens::{Token, TokenizationError},
+    Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::*;
+
+    fn create_test_tokenizer(continue_on_error: bool) -> Tokenizer {
+        let config = TokenizerConfig {
+            continue_on_error,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_regex_scanner(r"^\w+", "Word", None);
+        tokenizer.add_block_scanner("\"", "\"", "String", None, false, false, true);
+        tokenizer
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+        tokens.iter().map(|t| (t.value.as_str(), t.line, t.column)).collect()
+    }
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian { vec![0xFE, 0xFF] } else { vec![0xFF, 0xFE] };
+        for unit in text.encode_utf16() {
+            if big_endian {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn units(bytes: &[u8]) -> Vec<u16> {
+        bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect()
+    }
+
+    #[test]
+    fn test_plain_utf8_bytes() {
+        let tokenizer = create_test_tokenizer(false);
+        let tokens = tokenizer.tokenize_bytes("héllo wörld".as_bytes()).unwrap();
+
+        assert_eq!(summary(&tokens), vec![("héllo", 1, 1), ("wörld", 1, 7)]);
+    }
+
+    #[test]
+    fn test_utf8_bom_is_stripped() {
+        let tokenizer = create_test_tokenizer(false);
+        let tokens = tokenizer.tokenize_bytes(b"\xEF\xBB\xBFfirst second").unwrap();
+
+        assert_eq!(summary(&tokens), vec![("first", 1, 1), ("second", 1, 7)]);
+
+        let decoded = DecodedInput::decode(b"\xEF\xBB\xBFfirst");
+        assert_eq!(decoded.encoding, Encoding::Utf8);
+        assert_eq!(decoded.bom_len, 3);
+        assert_eq!(decoded.original_offset(0), 3);
+    }
+
+    #[test]
+    fn test_utf16_le_and_be() {
+        let tokenizer = create_test_tokenizer(false);
+        let text = "héllo \"日本\"\nnext";
+
+        for big_endian in [false, true] {
+            let tokens = tokenizer.tokenize_bytes(&utf16(text, big_endian)).unwrap();
+            assert_eq!(
+                summary(&tokens),
+                vec![("héllo", 1, 1), ("\"日本\"", 1, 7), ("next", 2, 1)]
+            );
+        }
+
+        let decoded = DecodedInput::decode(&utf16(text, true));
+        assert_eq!(decoded.encoding, Encoding::Utf16Be);
+        assert_eq!(decoded.text, text);
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_reported_with_position() {
+        let tokenizer = create_test_tokenizer(true);
+        let tokens = tokenizer.tokenize_bytes(b"good\nba\xFFd end").unwrap();
+
+        // The invalid byte splits the word it appears in
+        assert_eq!(summary(&tokens), vec![("good", 1, 1), ("ba", 2, 1), ("d", 2, 4), ("end", 2, 6)]);
+
+        let errors = tokenizer.last_errors().unwrap();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            TokenizationError::InvalidEncoding(msg) => {
+                assert_eq!(msg, "Invalid UTF-8 sequence [FF] at byte 7 (line 2, column 3)");
+            }
+            e => panic!("Expected InvalidEncoding error, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8_stops_strict_tokenization() {
+        let tokenizer = create_test_tokenizer(false);
+        let errors = tokenizer.tokenize_bytes(b"ok \xC3( rest").unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], TokenizationError::InvalidEncoding(_)));
+    }
+
+    #[test]
+    fn test_invalid_bytes_end_a_block() {
+        let tokenizer = create_test_tokenizer(true);
+        tokenizer.tokenize_bytes(b"\"abc\xFFdef\"").unwrap();
+
+        let errors = tokenizer.last_errors().unwrap();
+        assert!(matches!(errors[0], TokenizationError::UnmatchedBlockDelimiter(_, _)));
+        assert!(errors.iter().any(|e| matches!(e, TokenizationError::InvalidEncoding(_))));
+    }
+
+    #[test]
+    fn test_truncated_sequence_at_end_of_input() {
+        let decoded = DecodedInput::decode(b"abc\xE6\x97");
+
+        assert_eq!(decoded.text, "abc\u{FFFD}");
+        assert_eq!(decoded.invalid.len(), 1);
+        assert_eq!(decoded.invalid[0].original_offset, 3);
+        assert_eq!(decoded.invalid[0].bytes, vec![0xE6, 0x97]);
+    }
+
+    #[test]
+    fn test_unpaired_utf16_surrogate() {
+        // "a", lone high surrogate, "b"
+        let bytes = [0xFF, 0xFE, 0x61, 0x00, 0x00, 0xD8, 0x62, 0x00];
+        let decoded = DecodedInput::decode(&bytes);
+
+        assert_eq!(decoded.encoding, Encoding::Utf16Le);
+        assert_eq!(decoded.text, "a\u{FFFD}b");
+        assert_eq!(decoded.invalid[0].original_offset, 4);
+
+        let tokenizer = create_test_tokenizer(true);
+        let tokens = tokenizer.tokenize_bytes(&bytes).unwrap();
+        assert_eq!(summary(&tokens), vec![("a", 1, 1), ("b", 1, 3)]);
+        assert!(tokenizer.last_errors().unwrap()[0].to_string().contains("UTF-16LE"));
+    }
+
+    #[test]
+    fn test_original_offsets() {
+        let decoded = DecodedInput::decode(b"\xEF\xBB\xBFab\xFF\xFEcd");
+        // "ab" + U+FFFD + U+FFFD + "cd"
+        let c = decoded.text.find('c').unwrap();
+        assert_eq!(decoded.original_offset(c), 3 + 2 + 2);
+
+        let text = "a日b";
+        let decoded = DecodedInput::decode(&utf16(text, false));
+        assert_eq!(decoded.original_offset(text.find('b').unwrap()), 2 + 4);
+    }
+
+    #[test]
+    fn test_tokens_map_back_to_original_bytes() {
+        let mut tokenizer = create_test_tokenizer(true);
+        tokenizer.set_tokenize_whitespace(true);
+        let bytes = utf16("日本 \"x y\"\nword", false);
+        let decoded = DecodedInput::decode(&bytes);
+
+        let tokens = tokenizer.tokenize_decoded(&decoded).unwrap();
+        let words: Vec<_> = tokens.iter().filter(|(token, _)| token.token_type == "Word").collect();
+        let (word, range) = words[1];
+        assert_eq!(word.value, "word");
+        // BOM, then 9 UTF-16 code units before "word"
+        assert_eq!(*range, 2 + 2 * 9..2 + 2 * 13);
+        assert_eq!(String::from_utf16_lossy(&units(&bytes[range.clone()])), "word");
+
+        let (string, range) = tokens.iter().find(|(token, _)| token.token_type == "String").unwrap();
+        assert_eq!(String::from_utf16_lossy(&units(&bytes[range.clone()])), string.value);
+        assert_eq!(*range, 2 + 2 * 3..2 + 2 * 8);
+    }
+}
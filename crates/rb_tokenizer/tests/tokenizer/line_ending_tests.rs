@@ -0,0 +1,158 @@
+use rb_tokenizer::{
+    tokens::Token,
+    LineEndingStyle, LineEndings, ParallelConfig, SplitBoundary, Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod line_ending_tests {
+    use super::*;
+
+    fn create_test_tokenizer(line_endings: LineEndings) -> Tokenizer {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            line_endings,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_eol_scanner("//", "Comment", None, true);
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+        tokenizer
+    }
+
+    fn positions(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+        tokens
+            .iter()
+            .filter(|t| t.token_type != "Whitespace")
+            .map(|t| (t.value.as_str(), t.line, t.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_line_endings() {
+        let config = TokenizerConfig::default();
+        assert_eq!(config.line_endings, LineEndings::Any);
+        assert!(!config.report_line_ending_style);
+    }
+
+    #[test]
+    fn test_crlf_positions() {
+        let tokenizer = create_test_tokenizer(LineEndings::Any);
+        let tokens = tokenizer.tokenize("one\r\ntwo\r\n  three").unwrap();
+
+        assert_eq!(positions(&tokens), vec![("one", 1, 1), ("two", 2, 1), ("three", 3, 3)]);
+    }
+
+    #[test]
+    fn test_lone_cr_is_a_line_break() {
+        let tokenizer = create_test_tokenizer(LineEndings::Any);
+        let tokens = tokenizer.tokenize("one\rtwo\r\rthree").unwrap();
+
+        assert_eq!(positions(&tokens), vec![("one", 1, 1), ("two", 2, 1), ("three", 4, 1)]);
+    }
+
+    #[test]
+    fn test_lf_policy_treats_cr_as_a_column() {
+        let tokenizer = create_test_tokenizer(LineEndings::Lf);
+        let tokens = tokenizer.tokenize("one\rtwo\r\nthree").unwrap();
+
+        assert_eq!(positions(&tokens), vec![("one", 1, 1), ("two", 1, 5), ("three", 2, 1)]);
+        // A whitespace run holding only "\r" is not a newline under this policy
+        assert_eq!(tokens[1].token_sub_type, None);
+    }
+
+    #[test]
+    fn test_unicode_line_separators() {
+        let input = "one\u{2028}two\u{2029}three";
+
+        let unicode = create_test_tokenizer(LineEndings::Unicode);
+        let tokens = unicode.tokenize(input).unwrap();
+        assert_eq!(positions(&tokens), vec![("one", 1, 1), ("two", 2, 1), ("three", 3, 1)]);
+        assert_eq!(tokens[1].token_sub_type, Some("Newline"));
+
+        let any = create_test_tokenizer(LineEndings::Any);
+        let tokens = any.tokenize(input).unwrap();
+        assert_eq!(positions(&tokens), vec![("one", 1, 1), ("two", 1, 5), ("three", 1, 9)]);
+        assert_eq!(tokens[1].token_sub_type, None);
+    }
+
+    #[test]
+    fn test_whitespace_newline_sub_type_for_cr() {
+        let tokenizer = create_test_tokenizer(LineEndings::Any);
+        let tokens = tokenizer.tokenize("a\rb").unwrap();
+
+        assert_eq!(tokens[1].value, "\r");
+        assert_eq!(tokens[1].token_sub_type, Some("Newline"));
+    }
+
+    #[test]
+    fn test_eol_scanner_stops_at_policy_terminators() {
+        let tokenizer = create_test_tokenizer(LineEndings::Any);
+        let tokens = tokenizer.tokenize("// crlf\r\n// cr\rnext").unwrap();
+
+        assert_eq!(tokens[0].value, "// crlf\r\n");
+        assert_eq!(tokens[1].value, "// cr\r");
+        assert_eq!(positions(&tokens), vec![("// crlf\r\n", 1, 1), ("// cr\r", 2, 1), ("next", 3, 1)]);
+    }
+
+    #[test]
+    fn test_config_line_endings_reach_eol_scanners() {
+        let mut tokenizer = create_test_tokenizer(LineEndings::Any);
+        tokenizer.config_mut().line_endings = LineEndings::Lf;
+
+        // "\r" no longer ends the comment, and positions agree with the scanner
+        let tokens = tokenizer.tokenize("// a\rb\nnext").unwrap();
+        assert_eq!(positions(&tokens), vec![("// a\rb\n", 1, 1), ("next", 2, 1)]);
+    }
+
+    #[test]
+    fn test_set_line_endings_updates_eol_scanners() {
+        let mut tokenizer = create_test_tokenizer(LineEndings::Any);
+        tokenizer.set_line_endings(LineEndings::Unicode);
+
+        let tokens = tokenizer.tokenize("// comment\u{2028}next").unwrap();
+
+        assert_eq!(tokens[0].value, "// comment\u{2028}");
+        assert_eq!(positions(&tokens)[1], ("next", 2, 1));
+    }
+
+    #[test]
+    fn test_line_ending_style_detection() {
+        assert_eq!(LineEndingStyle::detect("a\nb\n", LineEndings::Any), Some(LineEndingStyle::Lf));
+        assert_eq!(LineEndingStyle::detect("a\r\nb\r\n", LineEndings::Any), Some(LineEndingStyle::CrLf));
+        assert_eq!(LineEndingStyle::detect("a\rb", LineEndings::Any), Some(LineEndingStyle::Cr));
+        assert_eq!(LineEndingStyle::detect("a\r\nb\n", LineEndings::Any), Some(LineEndingStyle::Mixed));
+        assert_eq!(LineEndingStyle::detect("a\u{2028}b", LineEndings::Unicode), Some(LineEndingStyle::LineSeparator));
+        assert_eq!(LineEndingStyle::detect("a\u{2028}b", LineEndings::Any), None);
+        assert_eq!(LineEndingStyle::detect("single line", LineEndings::Any), None);
+    }
+
+    #[test]
+    fn test_report_line_ending_style() {
+        let mut tokenizer = create_test_tokenizer(LineEndings::Any);
+        tokenizer.tokenize("a\r\nb").unwrap();
+        assert_eq!(tokenizer.last_line_ending_style(), None, "Not recorded unless enabled");
+
+        tokenizer.set_report_line_ending_style(true);
+        tokenizer.tokenize("a\r\nb\r\n").unwrap();
+        assert_eq!(tokenizer.last_line_ending_style(), Some(LineEndingStyle::CrLf));
+
+        tokenizer.tokenize("a\r\nb\n").unwrap();
+        assert_eq!(tokenizer.last_line_ending_style(), Some(LineEndingStyle::Mixed));
+    }
+
+    #[test]
+    fn test_parallel_with_crlf_input() {
+        let tokenizer = create_test_tokenizer(LineEndings::Any);
+        let input = (0..50).map(|i| format!("line // comment {}\r\n\r", i)).collect::<String>();
+        let parallel = ParallelConfig {
+            threads: 4,
+            min_chunk_size: 16,
+            boundary: SplitBoundary::Newline,
+        };
+
+        assert_eq!(
+            tokenizer.tokenize_parallel(&input, &parallel).unwrap(),
+            tokenizer.tokenize(&input).unwrap()
+        );
+    }
+}
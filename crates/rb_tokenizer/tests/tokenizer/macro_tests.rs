@@ -0,0 +1,128 @@
+use rb_tokenizer::{tokenizer, tokens::TokenKind, utils, ColumnMode, Tokenizer, TokenizerConfig};
+
+tokenizer! {
+    /// Token kinds of the JSON test grammar
+    pub enum JsonKind;
+    /// Same grammar as `languages::json_tests`, declared with the macro
+    pub fn json_tokenizer;
+
+    config {
+        continue_on_error: false,
+        error_tolerance_limit: 1,
+    }
+
+    symbol "{" => Brace(OpenBrace);
+    symbol "}" => Brace(CloseBrace);
+    symbol "[" => Bracket(OpenBracket);
+    symbol "]" => Bracket(CloseBracket);
+    symbol ":" => Colon;
+    symbol "," => Comma;
+    regex r#"^"([^"\\]|\\.)*""# => String;
+    regex r"^-?\d+(\.\d+)?([eE][-+]?\d+)?" => Number;
+    regex r"^(true|false|null)\b" => Literal;
+}
+
+tokenizer! {
+    enum ScriptKind;
+    fn script_tokenizer;
+
+    config {
+        tokenize_whitespace: true,
+        column_mode: ColumnMode::DisplayWidth { tab_width: 4 },
+    }
+
+    block "/*" .. "*/" => Comment(Block) { nesting, raw };
+    block "'" .. "'" => Text { exclude_delimiters };
+    eol "#" => Comment(Line) { exclude_delimiter };
+    regex r"^[a-z]+" => Identifier;
+}
+
+#[cfg(test)]
+mod macro_tests {
+    use super::*;
+
+    fn string_json_tokenizer() -> Tokenizer {
+        let config = TokenizerConfig {
+            error_tolerance_limit: 1,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_symbol_scanner("{", "Brace", Some("OpenBrace"));
+        tokenizer.add_symbol_scanner("}", "Brace", Some("CloseBrace"));
+        tokenizer.add_symbol_scanner("[", "Bracket", Some("OpenBracket"));
+        tokenizer.add_symbol_scanner("]", "Bracket", Some("CloseBracket"));
+        tokenizer.add_symbol_scanner(":", "Colon", None);
+        tokenizer.add_symbol_scanner(",", "Comma", None);
+        tokenizer.add_regex_scanner(r#"^"([^"\\]|\\.)*""#, "String", None);
+        tokenizer.add_regex_scanner(r"^-?\d+(\.\d+)?([eE][-+]?\d+)?", "Number", None);
+        tokenizer.add_regex_scanner(r"^(true|false|null)\b", "Literal", None);
+        tokenizer
+    }
+
+    #[test]
+    fn test_generated_kind_enum() {
+        assert_eq!(JsonKind::Brace.as_str(), "Brace");
+        assert_eq!(JsonKind::OpenBrace.name(), "OpenBrace");
+        assert_eq!(JsonKind::whitespace(), JsonKind::Whitespace);
+        assert_eq!(JsonKind::newline(), JsonKind::Newline);
+        assert_eq!(JsonKind::ALL.len(), 13);
+        assert_eq!(JsonKind::ALL[0], JsonKind::Brace);
+    }
+
+    #[test]
+    fn test_generated_config() {
+        let tokenizer = json_tokenizer();
+        assert!(!tokenizer.config().continue_on_error);
+        assert_eq!(tokenizer.config().error_tolerance_limit, 1);
+    }
+
+    #[test]
+    fn test_matches_hand_written_tokenizer() {
+        let input = r#"{
+            "key": "value",
+            "array": [true, -1.5e3, null]
+        }"#;
+
+        let typed = json_tokenizer().tokenize(input).unwrap();
+        let strings = string_json_tokenizer().tokenize(input).unwrap();
+
+        assert_eq!(typed.len(), 15);
+        assert_eq!(typed.len(), strings.len());
+        for (typed, string) in typed.iter().zip(&strings) {
+            assert_eq!(typed.token_type.as_str(), string.token_type);
+            assert_eq!(typed.token_sub_type.map(|kind| kind.as_str()), string.token_sub_type);
+            assert_eq!((&typed.value, typed.line, typed.column), (&string.value, string.line, string.column));
+        }
+        assert_eq!(
+            utils::pretty_print_tokens(&typed),
+            utils::pretty_print_tokens(&strings)
+        );
+    }
+
+    #[test]
+    fn test_typed_kinds_can_be_matched() {
+        let tokens = json_tokenizer().tokenize("[1, 2]").unwrap();
+        let numbers = tokens
+            .iter()
+            .filter(|t| matches!(t.token_type, JsonKind::Number))
+            .count();
+        assert_eq!(numbers, 2);
+    }
+
+    #[test]
+    fn test_block_and_eol_options() {
+        let tokens = script_tokenizer()
+            .tokenize("/* a /* b */ */\tx 'text' # note\n")
+            .unwrap();
+
+        assert_eq!(tokens[0].token_type, ScriptKind::Comment);
+        assert_eq!(tokens[0].token_sub_type, Some(ScriptKind::Block));
+        assert_eq!(tokens[0].value, "/* a /* b */ */");
+        assert_eq!(tokens[1].token_type, ScriptKind::Whitespace);
+        // The tab stop follows the configured display-width column mode
+        assert_eq!((tokens[2].value.as_str(), tokens[2].column), ("x", 17));
+        assert_eq!(tokens[4].value, "text");
+        assert_eq!(tokens[6].token_sub_type, Some(ScriptKind::Line));
+        assert_eq!(tokens[6].value, " note\n");
+    }
+}
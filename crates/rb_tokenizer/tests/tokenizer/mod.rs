@@ -6,4 +6,27 @@ mod string_whitespace_tests;
 #[cfg(test)]
 mod config_tests;
 #[cfg(test)]
-mod error_handling_tests;
\ No newline at end of file
+mod builder_tests;
+#[cfg(test)]
+mod scanner_management_tests;
+#[cfg(test)]
+mod composition_tests;
+#[cfg(test)]
+mod state_tests;
+#[cfg(test)]
+mod error_handling_tests;
+#[cfg(test)]
+mod parallel_tests;
+#[cfg(test)]
+mod pipeline_tests;
+
+#[cfg(test)]
+mod column_mode_tests;
+#[cfg(test)]
+mod unicode_tests;
+#[cfg(test)]
+mod line_ending_tests;
+#[cfg(test)]
+mod encoding_tests;
+#[cfg(all(test, feature = "macros"))]
+mod macro_tests;
@@ -0,0 +1,151 @@
+use rb_tokenizer::{ColumnMode, ParallelConfig, SplitBoundary, Tokenizer, TokenizerConfig};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod parallel_tests {
+    use super::*;
+
+    fn create_test_tokenizer(config: TokenizerConfig) -> Tokenizer {
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_block_scanner("/*", "*/", "Comment", Some("Block"), false, false, true);
+        tokenizer.add_eol_scanner("//", "Comment", Some("Line"), true);
+        tokenizer.add_regex_scanner(r#"^"([^"\\]|\\.)*""#, "String", None);
+        tokenizer.add_regex_scanner(r"^\d+", "Number", None);
+        tokenizer.add_regex_scanner(r"^[a-zA-Z_][a-zA-Z0-9_]*", "Identifier", None);
+        tokenizer.add_symbol_scanner("=", "Operator", Some("Assign"));
+        tokenizer.add_symbol_scanner(";", "Punctuation", Some("Semicolon"));
+        tokenizer
+    }
+
+    fn small_chunks() -> ParallelConfig {
+        ParallelConfig {
+            threads: 4,
+            min_chunk_size: 8,
+            boundary: SplitBoundary::Newline,
+        }
+    }
+
+    fn sample_input() -> String {
+        let mut input = String::new();
+        for i in 0..40 {
+            input.push_str(&format!("let_{} = {}; // line {}\n", i, i * 7, i));
+            if i % 9 == 0 {
+                // Block comments spanning lines force chunks to resynchronize
+                input.push_str("/* spans\n  several\n  lines */ ");
+            }
+            if i % 5 == 0 {
+                input.push_str("\n\n   ");
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let tokenizer = create_test_tokenizer(TokenizerConfig::default());
+        let input = sample_input();
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        let parallel = tokenizer.tokenize_parallel(&input, &small_chunks()).unwrap();
+
+        assert!(sequential.len() > 100);
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_with_whitespace_tokens() {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            ..TokenizerConfig::default()
+        };
+        let tokenizer = create_test_tokenizer(config);
+        let input = sample_input();
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        let parallel = tokenizer.tokenize_parallel(&input, &small_chunks()).unwrap();
+
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_parallel_merges_errors_in_order() {
+        let config = TokenizerConfig {
+            continue_on_error: true,
+            error_tolerance_limit: 100,
+            ..TokenizerConfig::default()
+        };
+        let tokenizer = create_test_tokenizer(config);
+        let input = sample_input().replace("= 14;", "= @14;").replace("= 210;", "= #210;");
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        let sequential_errors = tokenizer.last_errors().unwrap();
+        let parallel = tokenizer.tokenize_parallel(&input, &small_chunks()).unwrap();
+        let parallel_errors = tokenizer.last_errors().unwrap();
+
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel_errors.len(), 2);
+        // Error messages carry positions relative to the whole input
+        assert_eq!(
+            format!("{:?}", parallel_errors),
+            format!("{:?}", sequential_errors)
+        );
+    }
+
+    #[test]
+    fn test_parallel_stops_at_first_error_in_strict_mode() {
+        let tokenizer = create_test_tokenizer(TokenizerConfig::default());
+        let input = sample_input().replace("= 210", "= #210");
+
+        let sequential = tokenizer.tokenize(&input).unwrap_err();
+        let parallel = tokenizer.tokenize_parallel(&input, &small_chunks()).unwrap_err();
+
+        assert_eq!(format!("{:?}", parallel), format!("{:?}", sequential));
+    }
+
+    #[test]
+    fn test_parallel_with_split_predicate() {
+        let tokenizer = create_test_tokenizer(TokenizerConfig::default());
+        let input = sample_input();
+        // Split right before a semicolon, which is always a token boundary here
+        let parallel_config = ParallelConfig {
+            boundary: SplitBoundary::Predicate(Arc::new(|input: &str, at: usize| input[at..].starts_with(';'))),
+            ..small_chunks()
+        };
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        let parallel = tokenizer.tokenize_parallel(&input, &parallel_config).unwrap();
+
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_parallel_mid_line_split_with_tab_stops() {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            column_mode: ColumnMode::DisplayWidth { tab_width: 4 },
+            ..TokenizerConfig::default()
+        };
+        let tokenizer = create_test_tokenizer(config);
+        let input = sample_input().replace(' ', "\t");
+        let parallel_config = ParallelConfig {
+            boundary: SplitBoundary::Predicate(Arc::new(|input: &str, at: usize| input[at..].starts_with(';'))),
+            ..small_chunks()
+        };
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        let parallel = tokenizer.tokenize_parallel(&input, &parallel_config).unwrap();
+
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_small_input_falls_back_to_sequential() {
+        let tokenizer = create_test_tokenizer(TokenizerConfig::default());
+        let input = "a = 1;\nb = 2;\n";
+
+        let tokens = tokenizer.tokenize_parallel(input, &ParallelConfig::default()).unwrap();
+
+        assert_eq!(tokens, tokenizer.tokenize(input).unwrap());
+        assert_eq!(tokens[4].line, 2);
+    }
+}
@@ -0,0 +1,226 @@
+use rb_tokenizer::tokenizers::TokenPipeline;
+use rb_tokenizer::tokens::{Token, TokenFlags, TokenizationError};
+use rb_tokenizer::Tokenizer;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use crate::helpers::values;
+
+/// Identifiers, strings, comments and the text between `<`...`>` tags
+fn template_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.add_block_scanner("<", ">", "Tag", None, false, false, true);
+    tokenizer.add_block_scanner("\"", "\"", "String", None, false, false, true);
+    tokenizer.add_eol_scanner("#", "Comment", None, true);
+    tokenizer.add_regex_scanner(r"^[a-zA-Z_.]+", "Identifier", None);
+    tokenizer.add_regex_scanner(r"^[0-9]+", "Number", None);
+    tokenizer
+}
+
+fn messages(errors: impl IntoIterator<Item = TokenizationError>) -> Vec<String> {
+    errors.into_iter().map(|error| error.to_string()).collect()
+}
+
+fn positions(tokens: &[Token]) -> Vec<(&str, usize, usize)> {
+    tokens.iter().map(|token| (token.value.as_str(), token.line, token.column)).collect()
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_map_and_reclassify() {
+        let tokenizer = template_tokenizer();
+        let tokens = tokenizer.tokenize("if x # check\n\"a b\" else").unwrap();
+
+        let pipeline = TokenPipeline::new()
+            .filter(|token| token.token_type != "Comment")
+            .reclassify(|token| {
+                let keyword = token.token_type == "Identifier" && ["if", "else"].contains(&token.value.as_str());
+                keyword.then_some(("Keyword", Some("Control")))
+            })
+            .map(|token| match token.token_type {
+                "String" => Token {
+                    value: token.value[1..token.value.len() - 1].to_string(),
+                    ..token
+                },
+                _ => token,
+            });
+
+        let tokens: Vec<_> = pipeline.apply(tokens).collect();
+        assert_eq!(values(&tokens), [("Keyword", "if"), ("Identifier", "x"), ("String", "a b"), ("Keyword", "else")]);
+        assert_eq!(tokens[0].token_sub_type, Some("Control"));
+        assert_eq!((tokens[2].line, tokens[2].column), (2, 1));
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.set_tokenize_whitespace(true);
+        tokenizer.add_block_scanner("<", ">", "Tag", None, false, false, true);
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Text", None);
+        tokenizer.add_regex_scanner(r"^[!,.]", "Text", None);
+
+        let text = |token: &Token| token.token_type == "Text" || token.token_type == "Whitespace";
+        let pipeline = TokenPipeline::new().merge_adjacent(move |merged, next| text(merged) && text(next));
+
+        let tokens = tokenizer.tokenize("<p>hello, big\nworld!</p> <br>").unwrap();
+        let tokens: Vec<_> = pipeline.apply(tokens).collect();
+        assert_eq!(
+            positions(&tokens),
+            [("<p>", 1, 1), ("hello, big\nworld!", 1, 4), ("</p>", 2, 7), (" ", 2, 11), ("<br>", 2, 12)]
+        );
+        assert_eq!(tokens[1].token_type, "Text");
+    }
+
+    #[test]
+    fn test_split_positions_parts() {
+        let tokenizer = template_tokenizer();
+        let tokens = tokenizer.tokenize("x \"ab\ncd\" a.b.c").unwrap();
+        let pipeline = TokenPipeline::new()
+            .split(|token| match token.token_type {
+                "String" => token.value.find('\n').map(|i| vec![i + 1]).unwrap_or_default(),
+                "Identifier" => token.value.match_indices('.').flat_map(|(i, _)| [i, i + 1]).collect(),
+                _ => Vec::new(),
+            })
+            .reclassify(|token| (token.value == ".").then_some(("Dot", None)));
+
+        let tokens: Vec<_> = pipeline.apply(tokens).collect();
+        assert_eq!(
+            positions(&tokens),
+            [
+                ("x", 1, 1),
+                ("\"ab\n", 1, 3),
+                ("cd\"", 2, 1),
+                ("a", 2, 5),
+                (".", 2, 6),
+                ("b", 2, 7),
+                (".", 2, 8),
+                ("c", 2, 9)
+            ]
+        );
+        assert_eq!(tokens[4].token_type, "Dot");
+
+        // Out of range offsets and offsets inside a character are ignored
+        let tokens = tokenizer.tokenize("\"é\"").unwrap();
+        let pipeline = TokenPipeline::new().split(|_| vec![0, 2, 99]);
+        assert_eq!(pipeline.apply(tokens.clone()).collect::<Vec<_>>(), tokens);
+    }
+
+    #[test]
+    fn test_split_parts_point_into_the_source() {
+        let tokenizer = template_tokenizer();
+        let input = "<a\nbé\nc> x.y \"d\ne\"";
+        let pipeline = TokenPipeline::new().split(|token| token.value.char_indices().skip(1).map(|(i, _)| i).collect());
+
+        // Every token keeps its source text as its value, so each part is found where it is positioned
+        let lines: Vec<&str> = input.split_inclusive('\n').collect();
+        for part in pipeline.apply(tokenizer.tokenize(input).unwrap()) {
+            let line = lines[part.line - 1];
+            let at = line.char_indices().nth(part.column - 1).map_or(line.len(), |(i, _)| i);
+            assert!(line[at..].starts_with(&part.value), "{:?} at {}:{}", part.value, part.line, part.column);
+        }
+    }
+
+    #[test]
+    fn test_attached_pipeline() {
+        let mut tokenizer = template_tokenizer();
+        tokenizer.set_pipeline(TokenPipeline::new().filter(|token| token.token_type != "Comment"));
+
+        let tokens = tokenizer.tokenize("a # note\nb").unwrap();
+        assert_eq!(values(&tokens), [("Identifier", "a"), ("Identifier", "b")]);
+        let trees = tokenizer.tokenize_trees("a # note", &Default::default()).unwrap();
+        assert_eq!(trees.len(), 1);
+
+        // Clones keep the pipeline
+        let mut clone = tokenizer.clone();
+        assert!(clone.pipeline().is_some());
+        assert!(clone.take_pipeline().is_some());
+        assert_eq!(clone.tokenize("a # note").unwrap().len(), 2);
+        assert_eq!(tokenizer.tokenize("a # note").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tokens_are_produced_lazily() {
+        let scanned = Arc::new(AtomicUsize::new(0));
+        let counter = scanned.clone();
+        let mut tokenizer = template_tokenizer();
+        tokenizer.set_pipeline(
+            TokenPipeline::new()
+                .map(move |token| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    token
+                })
+                .filter(|token| token.token_type == "Number"),
+        );
+
+        let input = "a 1 b 2 c 3 d 4 e 5";
+        let first_two: Vec<_> = tokenizer.tokens(input).take(2).map(Result::unwrap).collect();
+        assert_eq!(values(&first_two), [("Number", "1"), ("Number", "2")]);
+        assert_eq!(scanned.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_tokens_yield_errors_in_place() {
+        let mut tokenizer = template_tokenizer();
+        tokenizer.set_pipeline(TokenPipeline::new().merge_adjacent(|_, _| true));
+
+        // Without continue_on_error the first error ends the iteration
+        let items: Vec<_> = tokenizer.tokens("a b = c").collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap().value, "ab");
+        assert!(matches!(items[1], Err(TokenizationError::UnrecognizedToken(_))));
+
+        // Merging stops at an error and starts again after it
+        tokenizer.set_continue_on_error(true);
+        let items: Vec<_> = tokenizer.tokens("a b = c d").collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2].as_ref().unwrap().value, "cd");
+        let tokens = tokenizer.tokenize("a b = c d").unwrap();
+        assert_eq!(values(&tokens), [("Identifier", "abcd")]);
+    }
+
+    #[test]
+    fn test_tokens_stop_where_tokenize_stops() {
+        let mut tokenizer = template_tokenizer();
+        tokenizer.set_continue_on_error(true).set_error_tolerance_limit(3);
+
+        for input in ["= = \"a b", "= = = = a", "\"a = \"b \"c d", "a = b"] {
+            let (tokens, errors): (Vec<_>, Vec<_>) = tokenizer.tokens(input).partition(Result::is_ok);
+            let tokens: Vec<Token> = tokens.into_iter().map(Result::unwrap).collect();
+            let errors = messages(errors.into_iter().map(Result::unwrap_err));
+
+            match tokenizer.tokenize(input) {
+                Ok(expected) => {
+                    assert_eq!(tokens, expected, "{}", input);
+                    assert_eq!(errors, messages(tokenizer.last_errors().unwrap_or_default()), "{}", input);
+                }
+                Err(expected) => assert_eq!(errors, messages(expected), "{}", input),
+            }
+        }
+
+        // Unrecognized characters count, so the unterminated string is the third error
+        let items: Vec<_> = tokenizer.tokens("= = \"a b").collect();
+        assert_eq!(items.len(), 3);
+        assert!(tokenizer.tokenize("= = \"a b").is_err());
+    }
+
+    #[test]
+    fn test_merged_and_split_tokens_keep_flags() {
+        let tokens = vec![
+            Token::new("Text", None, "a".to_string(), 1, 1).with_literal('a'),
+            Token::new("Text", None, "\\n".to_string(), 1, 2).with_flag(TokenFlags::HAD_ESCAPES),
+            Token::new("Text", None, "b".to_string(), 1, 4).with_flag("last"),
+        ];
+
+        let merged: Vec<_> = TokenPipeline::new().merge_adjacent(|_, _| true).apply(tokens).collect();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].value, "a\\nb");
+        assert_eq!(merged[0].literal, None);
+        assert_eq!(merged[0].flags.iter().collect::<Vec<_>>(), [TokenFlags::HAD_ESCAPES, "last"]);
+
+        let parts: Vec<_> = TokenPipeline::new().split(|_| vec![1, 3]).apply(merged).collect();
+        assert_eq!(positions(&parts), [("a", 1, 1), ("\\n", 1, 2), ("b", 1, 4)]);
+        assert!(parts.iter().all(|part| part.has_flag("last") && part.literal.is_none()));
+    }
+}
@@ -0,0 +1,147 @@
+use rb_tokenizer::scanners::{BlockScanner, EolScanner, RegexScanner, ScannerType, SymbolScanner};
+use rb_tokenizer::tokenizers::ScannerError;
+use rb_tokenizer::Tokenizer;
+use crate::helpers::values;
+
+/// A small base language whose scanners are all named
+fn base_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer
+        .register_scanner(Some("identifier"), 10, RegexScanner::new(r"^[a-z]+", "Identifier", None))
+        .unwrap();
+    tokenizer
+        .register_scanner(Some("keyword"), 0, RegexScanner::new(r"^(if|else)\b", "Keyword", None))
+        .unwrap();
+    tokenizer
+        .register_scanner(Some("string"), 0, BlockScanner::new("\"", "\"", "String", None, false, false, true))
+        .unwrap();
+    tokenizer
+        .register_scanner(Some("comment"), 0, EolScanner::new("#", "Comment", None, true))
+        .unwrap();
+    tokenizer
+}
+
+#[cfg(test)]
+mod scanner_management_tests {
+    use super::*;
+
+    #[test]
+    fn test_lower_priority_is_tried_first() {
+        let tokenizer = base_tokenizer();
+
+        // The keyword scanner was registered later but has the lower priority
+        assert_eq!(
+            values(&tokenizer.tokenize("if x").unwrap()),
+            [("Keyword", "if"), ("Identifier", "x")]
+        );
+        let order: Vec<_> = tokenizer.scanners().map(|info| (info.name.unwrap(), info.priority)).collect();
+        assert_eq!(order, [("keyword", 0), ("string", 0), ("comment", 0), ("identifier", 10)]);
+    }
+
+    #[test]
+    fn test_priority_does_not_shift_when_scanners_are_added() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_scanner_with_priority(Box::new(SymbolScanner::new("=", "Assign", None)), 0);
+        tokenizer.add_scanner_with_priority(Box::new(SymbolScanner::new("==", "Equals", None)), -1);
+        tokenizer.add_symbol_scanner("=", "Other", None);
+        tokenizer.add_symbol_scanner("==", "Other", None);
+
+        assert_eq!(values(&tokenizer.tokenize("== =").unwrap()), [("Equals", "=="), ("Assign", "=")]);
+        let kinds: Vec<_> = tokenizer.scanners().map(|info| (info.scanner.kind(), info.priority)).collect();
+        assert_eq!(kinds, [("custom", -1), ("custom", 0), ("symbol", 0), ("symbol", 0)]);
+    }
+
+    #[test]
+    fn test_remove_and_replace_derive_a_dialect() {
+        let mut dialect = base_tokenizer();
+        assert!(matches!(dialect.remove_scanner("comment").as_deref(), Ok(ScannerType::Eol(_))));
+        let old = dialect
+            .replace_scanner("string", BlockScanner::new("'", "'", "String", None, false, false, true))
+            .unwrap();
+        assert_eq!(old.kind(), "block");
+
+        assert!(dialect.tokenize("'a b' # c").is_err());
+        dialect.register_scanner(Some("comment"), 0, EolScanner::new("--", "Comment", None, true)).unwrap();
+        assert_eq!(
+            values(&dialect.tokenize("'a b' -- c").unwrap()),
+            [("String", "'a b'"), ("Comment", "-- c")]
+        );
+
+        // The replacement keeps the scanner's place
+        let names: Vec<_> = dialect.scanners().map(|info| info.name.unwrap()).collect();
+        assert_eq!(names, ["keyword", "string", "comment", "identifier"]);
+    }
+
+    #[test]
+    fn test_disabled_scanners_are_skipped() {
+        let mut tokenizer = base_tokenizer();
+        tokenizer.disable_scanner("keyword").unwrap();
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Identifier", "if")]);
+        assert!(!tokenizer.scanners().find(|info| info.name == Some("keyword")).unwrap().enabled);
+
+        tokenizer.optimize();
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Identifier", "if")]);
+
+        tokenizer.enable_scanner("keyword").unwrap();
+        assert!(!tokenizer.is_optimized());
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Keyword", "if")]);
+    }
+
+    #[test]
+    fn test_set_scanner_priority_reorders() {
+        let mut tokenizer = base_tokenizer();
+        tokenizer.set_scanner_priority("identifier", -5).unwrap();
+
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Identifier", "if")]);
+        assert_eq!(tokenizer.scanners().next().unwrap().name, Some("identifier"));
+    }
+
+    #[test]
+    fn test_unknown_and_duplicate_names() {
+        let mut tokenizer = base_tokenizer();
+
+        assert_eq!(
+            tokenizer.remove_scanner("missing").err(),
+            Some(ScannerError::UnknownScanner("missing".to_string()))
+        );
+        assert_eq!(tokenizer.disable_scanner("missing"), Err(ScannerError::UnknownScanner("missing".to_string())));
+        assert_eq!(
+            tokenizer.register_scanner(Some("string"), 0, SymbolScanner::new("'", "Quote", None)),
+            Err(ScannerError::DuplicateName("string".to_string()))
+        );
+        assert_eq!(tokenizer.scanners().count(), 4);
+    }
+
+    #[test]
+    fn test_builder_names_and_priorities() {
+        let mut tokenizer = Tokenizer::builder()
+            .regex(r"^[a-z]+").name("identifier").kind("Identifier")
+            .symbol("if").name("if").priority(-1).kind("Keyword")
+            .build()
+            .unwrap();
+
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Keyword", "if")]);
+        tokenizer.remove_scanner("if").unwrap();
+        assert_eq!(values(&tokenizer.tokenize("if").unwrap()), [("Identifier", "if")]);
+
+        let errors = Tokenizer::builder()
+            .symbol("+").name("op").kind("Plus")
+            .symbol("-").name("op").kind("Minus")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "Scanner 1: A scanner named 'op' already exists");
+    }
+
+    #[test]
+    fn test_builder_checks_symbol_reachability_in_priority_order() {
+        // "==" is tried first, so the later "=" does not shadow it
+        let tokenizer = Tokenizer::builder()
+            .symbol("=").kind("Assign")
+            .symbol("==").priority(-1).kind("Equals")
+            .build()
+            .unwrap();
+        assert_eq!(values(&tokenizer.tokenize("= ==").unwrap()), [("Assign", "="), ("Equals", "==")]);
+    }
+}
@@ -0,0 +1,157 @@
+use rb_tokenizer::scanners::ScanContext;
+use rb_tokenizer::tokens::{Token, TokenizationError};
+use rb_tokenizer::Tokenizer;
+use crate::helpers::{token, values};
+
+/// Brace depth of the open `${` substitutions in template literals
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TemplateState {
+    depth: usize,
+}
+
+/// Template literals with `${ ... }` substitutions: a `}` closing a
+/// substitution goes back to the template text.
+fn template(input: &str, context: &mut ScanContext) -> Result<Option<Token>, TokenizationError> {
+    let in_template = context.mode() == "template";
+    if input.starts_with('`') {
+        if in_template {
+            context.pop_mode();
+        } else {
+            context.push_mode("template");
+        }
+        return Ok(Some(token("Backtick", "`")));
+    }
+    if in_template && input.starts_with("${") {
+        context.state_mut::<TemplateState>().unwrap().depth += 1;
+        context.push_mode("code");
+        return Ok(Some(token("SubstitutionStart", "${")));
+    }
+    if in_template {
+        let end = input.find(['`', '$']).unwrap_or(input.len()).max(1);
+        return Ok(Some(token("Text", &input[..end])));
+    }
+    let closes = context.mode() == "code" && context.state::<TemplateState>().is_some_and(|state| state.depth > 0);
+    if closes && input.starts_with('}') {
+        context.state_mut::<TemplateState>().unwrap().depth -= 1;
+        context.pop_mode();
+        return Ok(Some(token("SubstitutionEnd", "}")));
+    }
+    Ok(None)
+}
+
+fn template_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.add_context_closure_scanner(Box::new(template));
+    tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+    tokenizer.add_symbol_scanner("{", "Brace", None);
+    tokenizer.add_symbol_scanner("}", "Brace", None);
+    tokenizer
+}
+
+#[cfg(test)]
+mod state_tests {
+    use super::*;
+
+    #[test]
+    fn test_state_is_threaded_through_scanners() {
+        let tokenizer = template_tokenizer();
+        let mut state = TemplateState::default();
+
+        let tokens = tokenizer.tokenize_with_state("`a ${ b } c` { }", &mut state).unwrap();
+        assert_eq!(
+            values(&tokens),
+            [
+                ("Backtick", "`"),
+                ("Text", "a "),
+                ("SubstitutionStart", "${"),
+                ("Identifier", "b"),
+                ("SubstitutionEnd", "}"),
+                ("Text", " c"),
+                ("Backtick", "`"),
+                ("Brace", "{"),
+                ("Brace", "}"),
+            ]
+        );
+        assert_eq!(state, TemplateState { depth: 0 });
+
+        let mut state = TemplateState::default();
+        tokenizer.tokenize_with_state("`${ a", &mut state).unwrap();
+        assert_eq!(state.depth, 1);
+    }
+
+    #[test]
+    fn test_state_of_another_type_is_not_visible() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_context_closure_scanner(Box::new(|input, context| {
+            assert!(context.state::<TemplateState>().is_none());
+            let count = context.state_mut::<usize>();
+            Ok(match count {
+                Some(count) if input.starts_with('x') => {
+                    *count += 1;
+                    Some(token("X", "x"))
+                }
+                _ => None,
+            })
+        }));
+
+        let mut count = 0usize;
+        tokenizer.tokenize_with_state("x x x", &mut count).unwrap();
+        assert_eq!(count, 3);
+        // Without a state the scanner finds nothing
+        assert!(tokenizer.tokenize("x").is_err());
+    }
+
+    #[test]
+    fn test_snapshots_are_taken_per_line() {
+        let tokenizer = template_tokenizer();
+        let mut state = TemplateState::default();
+
+        let (_, snapshots) = tokenizer.tokenize_with_snapshots("a\n`${\nb\n} c`\n", &mut state).unwrap();
+
+        let lines: Vec<_> = snapshots.iter().map(|snapshot| (snapshot.line(), snapshot.offset())).collect();
+        assert_eq!(lines, [(1, 0), (2, 2), (3, 6), (4, 8), (5, 13)]);
+        assert_eq!(snapshots[2].state().depth, 1);
+        assert_eq!(snapshots[2].modes(), ["template", "code"]);
+        assert_eq!(snapshots[3].state().depth, 1);
+        assert!(snapshots[4].modes().is_empty());
+    }
+
+    #[test]
+    fn test_resume_after_edit_matches_full_tokenization() {
+        let tokenizer = template_tokenizer();
+        let original = "a\n`x ${\nb\n} y`\nc";
+        let mut state = TemplateState::default();
+        let (tokens, snapshots) = tokenizer.tokenize_with_snapshots(original, &mut state).unwrap();
+
+        // Edit line 3, inside the substitution
+        let edited = "a\n`x ${\nb { d }\n} y`\nc";
+        let snapshot = snapshots.iter().rev().find(|snapshot| snapshot.line() <= 3).unwrap();
+        let mut state = TemplateState { depth: 99 };
+        let resumed = tokenizer.resume_from_snapshot(edited, snapshot, &mut state).unwrap();
+
+        let kept = tokens.iter().take_while(|token| token.line < snapshot.line()).cloned();
+        let combined: Vec<_> = kept.chain(resumed).collect();
+        let mut fresh_state = TemplateState::default();
+        assert_eq!(combined, tokenizer.tokenize_with_state(edited, &mut fresh_state).unwrap());
+        assert_eq!(state, fresh_state);
+    }
+
+    #[test]
+    fn test_resume_keeps_previous_token_for_context_scanners() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_context_closure_scanner(Box::new(|input, context| {
+            let after_name = context.previous().is_some_and(|previous| previous.token_type == "Identifier");
+            Ok((!after_name && input.starts_with('/')).then(|| token("Regex", "/")))
+        }));
+        tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+        tokenizer.add_symbol_scanner("/", "Divide", None);
+
+        let input = "a\n/\n";
+        let (tokens, snapshots) = tokenizer.tokenize_with_snapshots(input, &mut ()).unwrap();
+        let resumed = tokenizer.resume_from_snapshot(input, &snapshots[1], &mut ()).unwrap();
+
+        assert_eq!(values(&tokens), [("Identifier", "a"), ("Divide", "/")]);
+        assert_eq!(resumed, tokens[1..]);
+        assert_eq!((resumed[0].line, resumed[0].column), (2, 1));
+    }
+}
@@ -0,0 +1,224 @@
+use rb_tokenizer::{
+    scanners::{BlockScanner, CallbackScanner, Scanner},
+    tokens::{Token, TokenizationError},
+    Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod unicode_tests {
+    use super::*;
+
+    fn create_tokenizer() -> Tokenizer {
+        let config = TokenizerConfig {
+            continue_on_error: true,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::with_config(config);
+        tokenizer.add_regex_scanner(r"^\p{L}[\p{L}\p{N}_]*", "Identifier", None);
+        tokenizer.add_regex_scanner(r"^\d+", "Number", None);
+        tokenizer
+    }
+
+    fn summary(tokens: &[Token]) -> Vec<(&str, &str, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.token_type, t.value.as_str(), t.line, t.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_regex_tokens_with_multibyte_characters() {
+        let tokenizer = create_tokenizer();
+        let tokens = tokenizer.tokenize("héllo wörld 42").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![
+                ("Identifier", "héllo", 1, 1),
+                ("Identifier", "wörld", 1, 7),
+                ("Number", "42", 1, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbol_scanner_with_multibyte_symbols() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.add_symbol_scanner("→", "Operator", Some("Arrow"));
+        tokenizer.add_symbol_scanner("≠", "Operator", Some("NotEqual"));
+
+        let tokens = tokenizer.tokenize("α→β≠γ").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![
+                ("Identifier", "α", 1, 1),
+                ("Operator", "→", 1, 2),
+                ("Identifier", "β", 1, 3),
+                ("Operator", "≠", 1, 4),
+                ("Identifier", "γ", 1, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_scanner_with_multibyte_content() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.add_block_scanner("\"", "\"", "String", None, false, false, true);
+        tokenizer.add_block_scanner("«", "»", "Quote", None, false, false, false);
+
+        let tokens = tokenizer.tokenize("\"héllo\" «日本語» x").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![
+                ("String", "\"héllo\"", 1, 1),
+                ("Quote", "日本語", 1, 9),
+                ("Identifier", "x", 1, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_scanner_with_transformed_escapes() {
+        let mut tokenizer = create_tokenizer();
+        let mut scanner = BlockScanner::new("'", "'", "String", None, false, false, true);
+        scanner.add_named_escape('&', ';', 8);
+        scanner.add_escape_mapping("amp", '&');
+        scanner.set_transform_escapes(true);
+        tokenizer.add_scanner_with_priority(Box::new(scanner), 0);
+
+        // The value is shorter than the consumed text once escapes are transformed
+        let tokens = tokenizer.tokenize("'ça&amp;va' après").unwrap();
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].value, "'ça&va'");
+        assert_eq!(summary(&tokens[1..]), vec![("Identifier", "après", 1, 13)]);
+    }
+
+    #[test]
+    fn test_multiline_block_with_multibyte_content() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.add_block_scanner("/*", "*/", "Comment", None, false, true, true);
+
+        let tokens = tokenizer.tokenize("/* 日本\n語 */ ünd").unwrap();
+
+        assert_eq!(summary(&tokens[1..]), vec![("Identifier", "ünd", 2, 6)]);
+    }
+
+    #[test]
+    fn test_eol_scanner_with_multibyte_content() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.add_eol_scanner("#", "Comment", None, false);
+        tokenizer.add_eol_scanner("—", "Dash", None, true);
+
+        let tokens = tokenizer.tokenize("# café\nnaïve — ok\nüber").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![
+                ("Comment", " café\n", 1, 1),
+                ("Identifier", "naïve", 2, 1),
+                ("Dash", "— ok\n", 2, 7),
+                ("Identifier", "über", 3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closure_and_callback_scanners_with_multibyte_tokens() {
+        struct EmojiScanner;
+
+        impl CallbackScanner for EmojiScanner {
+            fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+                Ok(input.strip_prefix("🎉").map(|_| Token::new("Emoji", None, "🎉".to_string(), 0, 0)))
+            }
+        }
+
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_callback_scanner(Box::new(EmojiScanner));
+        tokenizer.add_closure_scanner(Box::new(|input: &str| {
+            Ok(input.starts_with("ß").then(|| Token::new("Eszett", None, "ß".to_string(), 0, 0)))
+        }));
+
+        let tokens = tokenizer.tokenize("🎉ß 🎉").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![("Emoji", "🎉", 1, 1), ("Eszett", "ß", 1, 2), ("Emoji", "🎉", 1, 4)]
+        );
+    }
+
+    #[test]
+    fn test_custom_scanner_with_multibyte_tokens() {
+        struct WordScanner;
+
+        impl Scanner for WordScanner {
+            fn scan(&self, input: &str) -> Result<Option<Token>, TokenizationError> {
+                let end = input.find(' ').unwrap_or(input.len());
+                Ok((end > 0).then(|| Token::new("Word", None, input[..end].to_string(), 0, 0)))
+            }
+        }
+
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_scanner(Box::new(WordScanner));
+
+        let tokens = tokenizer.tokenize("Ελληνικά русский 中文").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![("Word", "Ελληνικά", 1, 1), ("Word", "русский", 1, 10), ("Word", "中文", 1, 18)]
+        );
+    }
+
+    #[test]
+    fn test_multibyte_whitespace_tokens() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.set_tokenize_whitespace(true);
+
+        // NO-BREAK SPACE and IDEOGRAPHIC SPACE are multibyte whitespace
+        let tokens = tokenizer.tokenize("a\u{a0}\u{3000}b\n\u{3000}c").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![
+                ("Identifier", "a", 1, 1),
+                ("Whitespace", "\u{a0}\u{3000}", 1, 2),
+                ("Identifier", "b", 1, 4),
+                ("Whitespace", "\n\u{3000}", 1, 5),
+                ("Identifier", "c", 2, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_multibyte_characters() {
+        let tokenizer = create_tokenizer();
+        let tokens = tokenizer.tokenize("a ☃ b 😀 c").unwrap();
+
+        assert_eq!(
+            summary(&tokens),
+            vec![("Identifier", "a", 1, 1), ("Identifier", "b", 1, 5), ("Identifier", "c", 1, 9)]
+        );
+
+        let errors = tokenizer.last_errors().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().contains("line 1, column 3: '☃'"));
+        assert!(errors[1].to_string().contains("line 1, column 7: '😀'"));
+    }
+
+    #[test]
+    fn test_scanner_error_skips_whole_multibyte_character() {
+        let mut tokenizer = create_tokenizer();
+        tokenizer.add_block_scanner("「", "」", "Quote", None, false, false, true);
+
+        let tokens = tokenizer.tokenize("「unclosed").unwrap();
+
+        // The rest of the input is still tokenized after skipping the 3-byte opener
+        assert_eq!(summary(&tokens), vec![("Identifier", "unclosed", 1, 2)]);
+        assert!(matches!(
+            tokenizer.last_errors().unwrap()[0],
+            TokenizationError::UnmatchedBlockDelimiter(_, _)
+        ));
+    }
+}
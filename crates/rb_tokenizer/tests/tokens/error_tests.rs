@@ -71,6 +71,13 @@ mod error_tests {
         }
     }
 
+    #[test]
+    fn test_invalid_encoding_display_output() {
+        let error = TokenizationError::InvalidEncoding("Invalid UTF-8 sequence [FF] at byte 3".to_string());
+
+        assert_eq!(format!("{}", error), "Invalid encoding: Invalid UTF-8 sequence [FF] at byte 3");
+    }
+
     #[test]
     fn test_error_clone() {
         let original = TokenizationError::UnrecognizedToken("Original error".to_string());
@@ -0,0 +1,131 @@
+use rb_tokenizer::{
+    scanners::{Scanner, SymbolScanner},
+    tokens::{Token, TokenKind, TokenizationError},
+    utils, ParallelConfig, SplitBoundary, Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod kind_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Kind {
+        Identifier,
+        Number,
+        Operator,
+        Plus,
+        Comment,
+        String,
+        Whitespace,
+        Newline,
+    }
+
+    impl TokenKind for Kind {
+        fn whitespace() -> Self {
+            Kind::Whitespace
+        }
+
+        fn newline() -> Self {
+            Kind::Newline
+        }
+    }
+
+    fn create_typed_tokenizer() -> Tokenizer<Kind> {
+        let mut tokenizer = Tokenizer::<Kind>::new_typed();
+        tokenizer.add_eol_scanner("//", Kind::Comment, None, true);
+        tokenizer.add_block_scanner("\"", "\"", Kind::String, None, false, false, true);
+        tokenizer.add_regex_scanner(r"^[a-z]+", Kind::Identifier, None);
+        tokenizer.add_regex_scanner(r"^\d+", Kind::Number, None);
+        tokenizer.add_symbol_scanner("+", Kind::Operator, Some(Kind::Plus));
+        tokenizer
+    }
+
+    #[test]
+    fn test_str_is_the_default_kind() {
+        assert_eq!(<&str as TokenKind>::whitespace(), "Whitespace");
+        assert_eq!(<&str as TokenKind>::newline(), "Newline");
+        assert_eq!("Identifier".name(), "Identifier");
+    }
+
+    #[test]
+    fn test_typed_tokens() {
+        let tokenizer = create_typed_tokenizer();
+        let tokens = tokenizer.tokenize("a + 42 \"s\" // done").unwrap();
+
+        let kinds: Vec<Kind> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(
+            kinds,
+            vec![Kind::Identifier, Kind::Operator, Kind::Number, Kind::String, Kind::Comment]
+        );
+        assert_eq!(tokens[1].token_sub_type, Some(Kind::Plus));
+
+        // Kinds can be matched exhaustively instead of compared as strings
+        let numbers = tokens
+            .iter()
+            .filter(|t| matches!(t.token_type, Kind::Number))
+            .count();
+        assert_eq!(numbers, 1);
+    }
+
+    #[test]
+    fn test_typed_whitespace_tokens() {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::<Kind>::with_typed_config(config);
+        tokenizer.add_regex_scanner(r"^[a-z]+", Kind::Identifier, None);
+
+        let tokens = tokenizer.tokenize("a b\nc").unwrap();
+
+        assert_eq!(tokens[1].token_type, Kind::Whitespace);
+        assert_eq!(tokens[1].token_sub_type, None);
+        assert_eq!(tokens[3].token_type, Kind::Whitespace);
+        assert_eq!(tokens[3].token_sub_type, Some(Kind::Newline));
+    }
+
+    #[test]
+    fn test_typed_custom_scanner() {
+        struct HashScanner;
+
+        impl Scanner<Kind> for HashScanner {
+            fn scan(&self, input: &str) -> Result<Option<Token<Kind>>, TokenizationError> {
+                Ok(input.starts_with('#').then(|| Token::new(Kind::Operator, None, "#".to_string(), 0, 0)))
+            }
+        }
+
+        let mut tokenizer = create_typed_tokenizer();
+        tokenizer.add_scanner(Box::new(HashScanner));
+        tokenizer.add_scanner(Box::new(SymbolScanner::new("-", Kind::Operator, None)));
+
+        let tokens = tokenizer.tokenize("#a-1").unwrap();
+        let kinds: Vec<Kind> = tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(kinds, vec![Kind::Operator, Kind::Identifier, Kind::Operator, Kind::Number]);
+    }
+
+    #[test]
+    fn test_typed_parallel_and_bytes() {
+        let tokenizer = create_typed_tokenizer();
+        let input = (0..100).map(|i| format!("x + {} // c\n", i)).collect::<String>();
+        let parallel = ParallelConfig {
+            threads: 4,
+            min_chunk_size: 32,
+            boundary: SplitBoundary::Newline,
+        };
+
+        let sequential = tokenizer.tokenize(&input).unwrap();
+        assert_eq!(tokenizer.tokenize_parallel(&input, &parallel).unwrap(), sequential);
+        assert_eq!(tokenizer.tokenize_bytes(input.as_bytes()).unwrap(), sequential);
+    }
+
+    #[test]
+    fn test_utils_use_kind_names() {
+        let tokenizer = create_typed_tokenizer();
+        let tokens = tokenizer.tokenize("a + 1").unwrap();
+
+        assert_eq!(utils::token_summary(&tokens[1]), "Operator:Plus '+'");
+        let pretty = utils::pretty_print_tokens(&tokens);
+        assert!(pretty.contains("Identifier"));
+        assert!(utils::analyze_tokens(&tokens).contains("Number"));
+    }
+}
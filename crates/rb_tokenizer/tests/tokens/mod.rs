@@ -2,4 +2,9 @@
 #[cfg(test)]
 mod token_tests;
 #[cfg(test)]
-mod error_tests;
\ No newline at end of file
+mod error_tests;#[cfg(test)]
+mod kind_tests;
+#[cfg(test)]
+mod registry_tests;
+#[cfg(test)]
+mod tree_tests;
@@ -0,0 +1,136 @@
+use rb_tokenizer::{
+    tokens::{TokenKind, TokenTypeId, TokenTypeRegistry},
+    Tokenizer, TokenizerConfig,
+};
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+
+    // Names as they would arrive from a grammar file, i.e. not `'static`
+    fn grammar_names() -> Vec<String> {
+        ["Identifier", "Number", "Operator", "Plus", "Comment"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn create_interned_tokenizer() -> Tokenizer<TokenTypeId> {
+        let names = grammar_names();
+        let mut tokenizer = Tokenizer::interned();
+        tokenizer.add_named_eol_scanner("#", &names[4], None, true);
+        tokenizer.add_named_regex_scanner(r"^[a-z]+", &names[0], None);
+        tokenizer.add_named_regex_scanner(r"^\d+", &names[1], None);
+        tokenizer.add_named_symbol_scanner("+", &names[2], Some(&names[3]));
+        tokenizer
+    }
+
+    #[test]
+    fn test_intern_returns_stable_ids() {
+        let mut registry = TokenTypeRegistry::new();
+        let a = registry.intern("Identifier");
+        let b = registry.intern("Number");
+
+        assert_ne!(a, b);
+        assert_eq!(registry.intern("Identifier"), a);
+        assert_eq!(registry.get("Number"), Some(b));
+        assert_eq!(registry.get("Missing"), None);
+        assert_eq!(registry.name(a), Some("Identifier"));
+    }
+
+    #[test]
+    fn test_reserved_whitespace_ids() {
+        let mut registry = TokenTypeRegistry::new();
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.intern("Whitespace"), TokenTypeId::WHITESPACE);
+        assert_eq!(registry.get("Newline"), Some(TokenTypeId::NEWLINE));
+        assert_eq!(TokenTypeId::whitespace().name(), "Whitespace");
+    }
+
+    #[test]
+    fn test_unknown_ids_resolve_to_placeholder() {
+        let registry = TokenTypeRegistry::new();
+        let mut other = TokenTypeRegistry::new();
+        let foreign = other.intern("Foreign");
+
+        assert_eq!(registry.name(foreign), None);
+        assert_eq!(registry.resolve(foreign), format!("#{}", foreign.index()));
+    }
+
+    #[test]
+    fn test_iter_in_id_order() {
+        let mut registry = TokenTypeRegistry::new();
+        registry.intern("B");
+        registry.intern("A");
+
+        let names: Vec<&str> = registry.iter().map(|(_, name)| name).collect();
+        assert_eq!(names, vec!["Whitespace", "Newline", "B", "A"]);
+    }
+
+    #[test]
+    fn test_tokens_carry_ids() {
+        let tokenizer = create_interned_tokenizer();
+        let tokens = tokenizer.tokenize("x + 1 # note").unwrap();
+
+        let registry = tokenizer.registry();
+        let types: Vec<&str> = tokens
+            .iter()
+            .map(|t| registry.name(t.token_type).unwrap())
+            .collect();
+        assert_eq!(types, vec!["Identifier", "Operator", "Number", "Comment"]);
+        assert_eq!(tokens[1].token_sub_type, registry.get("Plus"));
+        assert_eq!(tokenizer.type_name(tokens[2].token_type), "Number");
+    }
+
+    #[test]
+    fn test_whitespace_tokens_use_reserved_ids() {
+        let config = TokenizerConfig {
+            tokenize_whitespace: true,
+            ..TokenizerConfig::default()
+        };
+        let mut tokenizer = Tokenizer::interned_with_config(config);
+        tokenizer.add_named_regex_scanner(r"^[a-z]+", "Word", None);
+
+        let tokens = tokenizer.tokenize("a\nb").unwrap();
+
+        assert_eq!(tokens[1].token_type, TokenTypeId::WHITESPACE);
+        assert_eq!(tokens[1].token_sub_type, Some(TokenTypeId::NEWLINE));
+        assert_eq!(tokenizer.type_name(tokens[1].token_type), "Whitespace");
+    }
+
+    #[test]
+    fn test_pretty_print_resolves_names() {
+        let tokenizer = create_interned_tokenizer();
+        let tokens = tokenizer.tokenize("x + 1").unwrap();
+
+        let output = tokenizer.pretty_print_tokens(&tokens);
+        assert!(output.contains("Identifier"));
+        assert!(output.contains("Operator"));
+        assert!(output.contains("Plus"));
+        assert!(!output.contains('#'));
+    }
+
+    #[test]
+    fn test_describe_token_for_errors() {
+        let tokenizer = create_interned_tokenizer();
+        let tokens = tokenizer.tokenize("x\n  + 1").unwrap();
+
+        assert_eq!(
+            tokenizer.describe_token(&tokens[1]),
+            "Operator:Plus '+' at line 2, column 3"
+        );
+    }
+
+    #[test]
+    fn test_mixing_interned_and_id_scanners() {
+        let mut tokenizer = create_interned_tokenizer();
+        let minus = tokenizer.intern("Minus");
+        let operator = tokenizer.intern("Operator");
+        tokenizer.add_symbol_scanner("-", operator, Some(minus));
+
+        let tokens = tokenizer.tokenize("1 - 2 + 3").unwrap();
+        assert_eq!(tokens[1].token_type, tokens[3].token_type);
+        assert_eq!(tokenizer.type_name(tokens[1].token_sub_type.unwrap()), "Minus");
+    }
+}
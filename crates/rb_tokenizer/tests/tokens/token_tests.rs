@@ -1,4 +1,4 @@
-use rb_tokenizer::tokens::Token;
+use rb_tokenizer::tokens::{Token, TokenFlags, TokenValue};
 
 #[cfg(test)]
 mod token_tests {
@@ -6,13 +6,7 @@ mod token_tests {
 
     #[test]
     fn test_token_creation_and_accessors() {
-        let token = Token {
-            token_type: "IDENTIFIER",
-            token_sub_type: Some("VARIABLE"),
-            value: "myVariable".to_string(),
-            line: 42,
-            column: 10,
-        };
+        let token = Token::new("IDENTIFIER", Some("VARIABLE"), "myVariable".to_string(), 42, 10);
 
         // Test basic properties
         assert_eq!(token.token_type, "IDENTIFIER");
@@ -24,13 +18,7 @@ mod token_tests {
 
     #[test]
     fn test_token_with_no_subtype() {
-        let token = Token {
-            token_type: "NUMBER",
-            token_sub_type: None,
-            value: "123.45".to_string(),
-            line: 5,
-            column: 20,
-        };
+        let token = Token::new("NUMBER", None, "123.45".to_string(), 5, 20);
 
         assert_eq!(token.token_type, "NUMBER");
         assert_eq!(token.token_sub_type, None);
@@ -39,29 +27,11 @@ mod token_tests {
 
     #[test]
     fn test_token_equality() {
-        let token1 = Token {
-            token_type: "KEYWORD",
-            token_sub_type: Some("CONTROL"),
-            value: "if".to_string(),
-            line: 10,
-            column: 5,
-        };
+        let token1 = Token::new("KEYWORD", Some("CONTROL"), "if".to_string(), 10, 5);
 
-        let token2 = Token {
-            token_type: "KEYWORD",
-            token_sub_type: Some("CONTROL"),
-            value: "if".to_string(),
-            line: 10,
-            column: 5,
-        };
+        let token2 = Token::new("KEYWORD", Some("CONTROL"), "if".to_string(), 10, 5);
 
-        let different_token = Token {
-            token_type: "KEYWORD",
-            token_sub_type: Some("CONTROL"),
-            value: "else".to_string(),
-            line: 10,
-            column: 15,
-        };
+        let different_token = Token::new("KEYWORD", Some("CONTROL"), "else".to_string(), 10, 15);
 
         // Test equality
         assert_eq!(token1, token2);
@@ -76,6 +46,9 @@ mod token_tests {
             value: "Hello, world!".to_string(),
             line: 7,
             column: 12,
+            literal: Some(TokenValue::Str("Hello, world!".to_string())),
+            flags: TokenFlags::default(),
+            captures: vec![("quote".to_string(), "\"".to_string())],
         };
 
         let cloned = original.clone();
@@ -87,17 +60,12 @@ mod token_tests {
         assert_eq!(cloned.value, "Hello, world!");
         assert_eq!(cloned.line, 7);
         assert_eq!(cloned.column, 12);
+        assert_eq!(cloned.capture("quote"), Some("\""));
     }
 
     #[test]
     fn test_token_debug_output() {
-        let token = Token {
-            token_type: "OPERATOR",
-            token_sub_type: Some("ARITHMETIC"),
-            value: "+".to_string(),
-            line: 15,
-            column: 8,
-        };
+        let token = Token::new("OPERATOR", Some("ARITHMETIC"), "+".to_string(), 15, 8);
 
         // Test Debug implementation
         let debug_output = format!("{:?}", token);
@@ -112,13 +80,7 @@ mod token_tests {
 
     #[test]
     fn test_token_with_multiline_content() {
-        let token = Token {
-            token_type: "COMMENT",
-            token_sub_type: Some("BLOCK"),
-            value: "/* This is\na multiline\ncomment */".to_string(),
-            line: 20,
-            column: 0,
-        };
+        let token = Token::new("COMMENT", Some("BLOCK"), "/* This is\na multiline\ncomment */".to_string(), 20, 0);
 
         assert_eq!(token.token_type, "COMMENT");
         assert_eq!(token.value, "/* This is\na multiline\ncomment */");
@@ -127,4 +89,46 @@ mod token_tests {
         let newline_count = token.value.chars().filter(|&c| c == '\n').count();
         assert_eq!(newline_count, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_token_literal_values() {
+        let token = Token::new("NUMBER", None, "0x2A".to_string(), 1, 1).with_literal(42i128);
+        assert_eq!(token.literal, Some(TokenValue::Int(42)));
+        assert_eq!(token.literal.as_ref().and_then(TokenValue::as_int), Some(42));
+        assert_eq!(token.literal.as_ref().and_then(TokenValue::as_str), None);
+
+        assert_eq!(TokenValue::from(1.5).as_float(), Some(1.5));
+        assert_eq!(TokenValue::from("a\nb").as_str(), Some("a\nb"));
+        assert_eq!(TokenValue::from(true).as_bool(), Some(true));
+        assert_eq!(TokenValue::from('x').as_char(), Some('x'));
+        assert_ne!(TokenValue::Int(1), TokenValue::Float(1.0));
+
+        // Custom values are shared by clones and compared by identity
+        #[derive(Debug, PartialEq)]
+        struct Date(u16, u8, u8);
+        let custom = TokenValue::custom(Date(2024, 2, 29));
+        assert_eq!(custom.downcast_ref::<Date>(), Some(&Date(2024, 2, 29)));
+        assert_eq!(custom.downcast_ref::<String>(), None);
+        assert_eq!(custom, custom.clone());
+        assert_ne!(custom, TokenValue::custom(Date(2024, 2, 29)));
+        assert_eq!(format!("{:?}", custom), "Custom(..)");
+    }
+
+    #[test]
+    fn test_token_flags() {
+        let mut token = Token::new("STRING", None, "\"abc".to_string(), 1, 1)
+            .with_flag(TokenFlags::UNTERMINATED)
+            .with_flag("interpolated")
+            .with_flag(TokenFlags::UNTERMINATED);
+        assert!(token.has_flag("unterminated"));
+        assert!(!token.has_flag(TokenFlags::HAD_ESCAPES));
+        assert_eq!(token.flags.iter().collect::<Vec<_>>(), ["unterminated", "interpolated"]);
+
+        // Flags are part of equality
+        assert_ne!(token, Token::new("STRING", None, "\"abc".to_string(), 1, 1));
+        assert!(token.flags.remove("interpolated"));
+        assert!(!token.flags.remove("interpolated"));
+        token.flags.extend([String::from("owned")]);
+        assert_eq!(token.flags.iter().collect::<Vec<_>>(), ["unterminated", "owned"]);
+    }
+}
@@ -0,0 +1,130 @@
+use rb_tokenizer::tokens::{DelimiterTable, Token, TokenTree, TreeCursor};
+use rb_tokenizer::Tokenizer;
+
+fn expression_tokenizer() -> Tokenizer {
+    let mut tokenizer = Tokenizer::new();
+    tokenizer.add_regex_scanner(r"^[a-z]+", "Identifier", None);
+    for symbol in ["(", ")", "[", "]", "{", "}", ",", "<", ">"] {
+        tokenizer.add_symbol_scanner(symbol, "Punctuation", None);
+    }
+    tokenizer
+}
+
+/// Writes trees back as text, marking groups and error nodes
+fn render(trees: &[TokenTree]) -> String {
+    trees
+        .iter()
+        .map(|tree| match tree {
+            TokenTree::Token(token) => token.value.clone(),
+            TokenTree::Group { open, close, children } => format!("{}{}{}", open.value, render(children), close.value),
+            TokenTree::Unclosed { open, children } => format!("!{}{}", open.value, render(children)),
+            TokenTree::Unexpected(token) => format!("?{}", token.value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn values<'t>(tokens: impl Iterator<Item = &'t Token>) -> Vec<&'t str> {
+    tokens.map(|token| token.value.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tree_tests {
+    use super::*;
+
+    #[test]
+    fn test_groups() {
+        let tokenizer = expression_tokenizer();
+        let trees = tokenizer.tokenize_trees("f(a, [b]) {c}", &DelimiterTable::brackets()).unwrap();
+
+        assert_eq!(trees.len(), 3);
+        assert_eq!(render(&trees), "f (a , [b]) {c}");
+        match &trees[1] {
+            TokenTree::Group { open, close, children } => {
+                assert_eq!((open.value.as_str(), close.value.as_str()), ("(", ")"));
+                assert_eq!(children.len(), 3);
+                assert_eq!((close.line, close.column), (1, 9));
+            }
+            other => panic!("expected a group, got {:?}", other),
+        }
+        assert!(!trees.iter().any(TokenTree::has_errors));
+    }
+
+    #[test]
+    fn test_unexpected_closer() {
+        let tokenizer = expression_tokenizer();
+        let trees = tokenizer.tokenize_trees("a ] (b})", &DelimiterTable::brackets()).unwrap();
+
+        assert_eq!(render(&trees), "a ?] (b ?})");
+        assert!(trees[1].has_errors());
+        assert!(trees[2].has_errors());
+    }
+
+    #[test]
+    fn test_closer_of_outer_group_leaves_inner_groups_unclosed() {
+        let tokenizer = expression_tokenizer();
+        let table = DelimiterTable::brackets();
+
+        assert_eq!(render(&tokenizer.tokenize_trees("(a [b) c", &table).unwrap()), "(a ![b) c");
+        assert_eq!(render(&tokenizer.tokenize_trees("a (b {c", &table).unwrap()), "a !(b !{c");
+    }
+
+    #[test]
+    fn test_typed_pairs() {
+        let mut tokenizer = expression_tokenizer();
+        tokenizer.add_block_scanner("\"", "\"", "String", None, false, false, true);
+        let table = DelimiterTable::new().typed_pair("Punctuation", "(", ")");
+
+        // The parenthesis inside the string is not a delimiter
+        let trees = tokenizer.tokenize_trees("(\"(\") [a]", &table).unwrap();
+        assert_eq!(render(&trees), "(\"(\") [ a ]");
+    }
+
+    #[test]
+    fn test_pairs_with_the_same_open_and_close() {
+        let mut tokenizer = expression_tokenizer();
+        tokenizer.add_symbol_scanner("|", "Bar", None);
+        let table = DelimiterTable::brackets().pair("|", "|");
+
+        assert_eq!(render(&tokenizer.tokenize_trees("|a| (|b|)", &table).unwrap()), "|a| (|b|)");
+        assert_eq!(render(&tokenizer.tokenize_trees("|a (b| c|", &table).unwrap()), "|a !(b| c !|");
+        assert_eq!(render(&tokenizer.tokenize_trees("|a||b|", &table).unwrap()), "|a| |b|");
+    }
+
+    #[test]
+    fn test_tokens_iterator_restores_input_order() {
+        let tokenizer = expression_tokenizer();
+        let tokens = tokenizer.tokenize("a (b [c) } d").unwrap();
+        let trees = DelimiterTable::brackets().group(tokens.clone());
+
+        let flattened: Vec<&Token> = trees.iter().flat_map(TokenTree::tokens).collect();
+        assert_eq!(flattened, tokens.iter().collect::<Vec<_>>());
+        assert_eq!(values(trees[1].tokens()), ["(", "b", "[", "c", ")"]);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let tokenizer = expression_tokenizer();
+        let trees = tokenizer.tokenize_trees("f(a, b) g", &DelimiterTable::brackets()).unwrap();
+
+        let mut cursor = TreeCursor::new(&trees);
+        assert_eq!(cursor.bump().unwrap().first_token().value, "f");
+        assert_eq!(cursor.peek(1).unwrap().first_token().value, "g");
+
+        let saved = cursor;
+        let mut arguments = cursor.enter().expect("the call has arguments");
+        let names: Vec<_> = std::iter::from_fn(|| arguments.bump())
+            .map(|tree| tree.first_token().value.as_str())
+            .collect();
+        assert_eq!(names, ["a", ",", "b"]);
+        assert!(arguments.is_at_end());
+
+        cursor.bump();
+        assert!(cursor.enter().is_none());
+        assert_eq!(cursor.remaining().len(), 1);
+        cursor.bump();
+        assert!(cursor.is_at_end() && cursor.current().is_none());
+        // The saved copy is still at the group
+        assert_eq!(saved.current().unwrap().children().len(), 3);
+    }
+}
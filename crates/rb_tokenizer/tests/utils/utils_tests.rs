@@ -6,41 +6,11 @@ mod utils_tests {
 
     fn create_test_tokens() -> Vec<Token> {
         vec![
-            Token {
-                token_type: "IDENTIFIER",
-                token_sub_type: Some("VARIABLE"),
-                value: "myVar".to_string(),
-                line: 1,
-                column: 5,
-            },
-            Token {
-                token_type: "OPERATOR",
-                token_sub_type: Some("ASSIGNMENT"),
-                value: "=".to_string(),
-                line: 1,
-                column: 11,
-            },
-            Token {
-                token_type: "NUMBER",
-                token_sub_type: None,
-                value: "42".to_string(),
-                line: 1,
-                column: 13,
-            },
-            Token {
-                token_type: "PUNCTUATION",
-                token_sub_type: Some("SEMICOLON"),
-                value: ";".to_string(),
-                line: 1,
-                column: 15,
-            },
-            Token {
-                token_type: "WHITESPACE",
-                token_sub_type: Some("NEWLINE"),
-                value: "\n".to_string(),
-                line: 1,
-                column: 16,
-            },
+            Token::new("IDENTIFIER", Some("VARIABLE"), "myVar".to_string(), 1, 5),
+            Token::new("OPERATOR", Some("ASSIGNMENT"), "=".to_string(), 1, 11),
+            Token::new("NUMBER", None, "42".to_string(), 1, 13),
+            Token::new("PUNCTUATION", Some("SEMICOLON"), ";".to_string(), 1, 15),
+            Token::new("WHITESPACE", Some("NEWLINE"), "\n".to_string(), 1, 16),
         ]
     }
 
@@ -65,13 +35,7 @@ mod utils_tests {
 
     #[test]
     fn test_token_summary() {
-        let token = Token {
-            token_type: "STRING",
-            token_sub_type: Some("DOUBLE_QUOTED"),
-            value: "Hello\nWorld".to_string(),
-            line: 2,
-            column: 3,
-        };
+        let token = Token::new("STRING", Some("DOUBLE_QUOTED"), "Hello\nWorld".to_string(), 2, 3);
 
         let summary = utils::token_summary(&token);
 
@@ -0,0 +1,228 @@
+use crate::parse::{Rule, ScannerRule, TokenizerDef};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::collections::HashMap;
+use syn::{Ident, LitStr};
+
+const CONFIG_FIELDS: &[&str] = &[
+    "tokenize_whitespace",
+    "continue_on_error",
+    "error_tolerance_limit",
+    "track_token_positions",
+    "column_mode",
+    "line_endings",
+    "report_line_ending_style",
+];
+
+/// Collects every problem in the definition so they are all reported at once
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, error: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
+}
+
+pub(crate) fn expand(def: TokenizerDef) -> syn::Result<TokenStream> {
+    let mut errors = Errors::default();
+    validate(&def, &mut errors);
+    errors.finish()?;
+
+    let kind = def.kind.as_ref().unwrap();
+    let constructor = def.constructor.as_ref().unwrap();
+    let kind_name = &kind.name;
+    let variants = variants(&def.rules);
+
+    let kind_enum = {
+        let attrs = &kind.attrs;
+        let vis = &kind.vis;
+        let names = variants.iter().map(|variant| variant.to_string());
+        let all = variants.iter().map(|variant| quote!(#kind_name::#variant));
+        quote! {
+            #(#attrs)*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            #vis enum #kind_name {
+                #(#variants,)*
+            }
+
+            impl #kind_name {
+                /// Every kind, in declaration order
+                pub const ALL: &'static [#kind_name] = &[#(#all),*];
+
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        #(#kind_name::#variants => #names,)*
+                    }
+                }
+            }
+
+            impl ::rb_tokenizer::tokens::TokenKind for #kind_name {
+                fn whitespace() -> Self {
+                    #kind_name::Whitespace
+                }
+
+                fn newline() -> Self {
+                    #kind_name::Newline
+                }
+
+                fn name(&self) -> ::std::borrow::Cow<'static, str> {
+                    ::std::borrow::Cow::Borrowed(self.as_str())
+                }
+            }
+        }
+    };
+
+    let config = def.config.iter().map(|(field, value)| quote!(config.#field = #value;));
+    let scanners = def.rules.iter().map(|rule| add_scanner(kind_name, rule));
+    let attrs = &constructor.attrs;
+    let vis = &constructor.vis;
+    let fn_name = &constructor.name;
+
+    Ok(quote! {
+        #kind_enum
+
+        #(#attrs)*
+        #vis fn #fn_name() -> ::rb_tokenizer::Tokenizer<#kind_name> {
+            #[allow(unused_mut)]
+            let mut config = ::rb_tokenizer::TokenizerConfig::default();
+            #(#config)*
+            let mut tokenizer = ::rb_tokenizer::Tokenizer::<#kind_name>::with_typed_config(config);
+            #(#scanners)*
+            tokenizer
+        }
+    })
+}
+
+/// Kind variants in order of first use, plus the whitespace kinds
+fn variants(rules: &[Rule]) -> Vec<Ident> {
+    let mut variants: Vec<Ident> = Vec::new();
+    let used = rules
+        .iter()
+        .flat_map(|rule| std::iter::once(&rule.token_type).chain(&rule.token_sub_type))
+        .cloned()
+        .chain(["Whitespace", "Newline"].map(|name| Ident::new(name, Span::call_site())));
+    for ident in used {
+        if !variants.contains(&ident) {
+            variants.push(ident);
+        }
+    }
+    variants
+}
+
+fn add_scanner(kind: &Ident, rule: &Rule) -> TokenStream {
+    let token_type = &rule.token_type;
+    let token_sub_type = match &rule.token_sub_type {
+        Some(sub_type) => quote!(Some(#kind::#sub_type)),
+        None => quote!(None),
+    };
+    let has = |option: &str| rule.options.iter().any(|ident| ident == option);
+
+    match &rule.scanner {
+        ScannerRule::Symbol(symbol) => quote! {
+            tokenizer.add_symbol_scanner(#symbol, #kind::#token_type, #token_sub_type);
+        },
+        ScannerRule::Regex(pattern) => quote! {
+            tokenizer.add_regex_scanner(#pattern, #kind::#token_type, #token_sub_type);
+        },
+        ScannerRule::Block(start, end) => {
+            let nesting = has("nesting");
+            let raw = has("raw");
+            let include_delimiters = !has("exclude_delimiters");
+            quote! {
+                tokenizer.add_block_scanner(#start, #end, #kind::#token_type, #token_sub_type, #nesting, #raw, #include_delimiters);
+            }
+        }
+        ScannerRule::Eol(delimiter) => {
+            let include_delimiter = !has("exclude_delimiter");
+            quote! {
+                tokenizer.add_eol_scanner(#delimiter, #kind::#token_type, #token_sub_type, #include_delimiter);
+            }
+        }
+    }
+}
+
+fn validate(def: &TokenizerDef, errors: &mut Errors) {
+    if def.kind.is_none() {
+        errors.push(syn::Error::new(Span::call_site(), "missing the token kind declaration, e.g. `pub enum MyKind;`"));
+    }
+    if def.constructor.is_none() {
+        errors.push(syn::Error::new(Span::call_site(), "missing the constructor declaration, e.g. `pub fn my_tokenizer;`"));
+    }
+
+    for (field, _) in &def.config {
+        if !CONFIG_FIELDS.contains(&field.to_string().as_str()) {
+            errors.push(syn::Error::new(
+                field.span(),
+                format!("unknown config option `{}`; expected one of {}", field, CONFIG_FIELDS.join(", ")),
+            ));
+        }
+    }
+
+    let mut symbols: HashMap<String, &LitStr> = HashMap::new();
+    for rule in &def.rules {
+        let allowed: &[&str] = match &rule.scanner {
+            ScannerRule::Symbol(symbol) => {
+                non_empty(symbol, "symbol", errors);
+                if symbols.insert(symbol.value(), symbol).is_some() {
+                    errors.push(syn::Error::new(symbol.span(), format!("duplicate symbol {:?}", symbol.value())));
+                }
+                &[]
+            }
+            ScannerRule::Regex(pattern) => {
+                validate_regex(pattern, errors);
+                &[]
+            }
+            ScannerRule::Block(start, end) => {
+                non_empty(start, "block start delimiter", errors);
+                non_empty(end, "block end delimiter", errors);
+                &["nesting", "raw", "exclude_delimiters"]
+            }
+            ScannerRule::Eol(delimiter) => {
+                non_empty(delimiter, "EOL delimiter", errors);
+                &["exclude_delimiter"]
+            }
+        };
+
+        for option in &rule.options {
+            if !allowed.contains(&option.to_string().as_str()) {
+                let message = match allowed {
+                    [] => format!("unknown option `{}`; this scanner takes no options", option),
+                    _ => format!("unknown option `{}`; expected one of {}", option, allowed.join(", ")),
+                };
+                errors.push(syn::Error::new(option.span(), message));
+            }
+        }
+    }
+}
+
+fn non_empty(literal: &LitStr, what: &str, errors: &mut Errors) {
+    if literal.value().is_empty() {
+        errors.push(syn::Error::new(literal.span(), format!("{} must not be empty", what)));
+    }
+}
+
+/// Compiles the pattern now, so `RegexScanner::new` can never panic on it
+fn validate_regex(pattern: &LitStr, errors: &mut Errors) {
+    let source = pattern.value();
+    match regex::Regex::new(&source) {
+        Err(e) => errors.push(syn::Error::new(pattern.span(), format!("invalid regex: {}", e))),
+        Ok(_) if !source.starts_with('^') && !source.starts_with("\\A") => {
+            errors.push(syn::Error::new(pattern.span(), "regex must be anchored with `^`"));
+        }
+        Ok(regex) if regex.is_match("") => {
+            errors.push(syn::Error::new(
+                pattern.span(),
+                "regex can match an empty string, which would never advance the tokenizer",
+            ));
+        }
+        Ok(_) => {}
+    }
+}
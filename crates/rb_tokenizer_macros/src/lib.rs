@@ -0,0 +1,40 @@
+//! Procedural macros for `rb_tokenizer`. Use them through the re-exports in
+//! `rb_tokenizer` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use syn::parse_macro_input;
+
+mod expand;
+mod parse;
+
+/// Defines a tokenizer and its token kind enum at compile time.
+///
+/// ```ignore
+/// rb_tokenizer::tokenizer! {
+///     pub enum JsonKind;
+///     pub fn json_tokenizer;
+///
+///     config { continue_on_error: false, error_tolerance_limit: 1 }
+///
+///     symbol "{" => Brace(OpenBrace);
+///     symbol "}" => Brace(CloseBrace);
+///     regex r"^-?\d+(\.\d+)?" => Number;
+///     block "/*" .. "*/" => Comment(Block) { nesting, raw };
+///     eol "//" => Comment(Line) { exclude_delimiter };
+/// }
+/// ```
+///
+/// Rows are added in order, so earlier rows take priority. The enum gets a
+/// variant for every type and sub-type named in the rows, plus `Whitespace`
+/// and `Newline`. Regexes are compiled during expansion: invalid, unanchored
+/// or empty-matching patterns are compile errors.
+#[proc_macro]
+pub fn tokenizer(input: TokenStream) -> TokenStream {
+    let def = parse_macro_input!(input as parse::TokenizerDef);
+    expand::expand(def)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+#[cfg(test)]
+mod tests;
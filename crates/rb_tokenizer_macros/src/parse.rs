@@ -0,0 +1,145 @@
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, parenthesized, Attribute, Expr, Ident, LitStr, Result, Token, Visibility};
+
+/// A whole `tokenizer! { ... }` invocation
+pub(crate) struct TokenizerDef {
+    pub(crate) kind: Option<Item>,
+    pub(crate) constructor: Option<Item>,
+    pub(crate) config: Vec<(Ident, Expr)>,
+    pub(crate) rules: Vec<Rule>,
+}
+
+/// The `enum Kind;` or `fn name;` declaration, with its attributes and visibility
+pub(crate) struct Item {
+    pub(crate) attrs: Vec<Attribute>,
+    pub(crate) vis: Visibility,
+    pub(crate) name: Ident,
+}
+
+/// One scanner row, e.g. `block "/*" .. "*/" => Comment(Block) { nesting };`
+pub(crate) struct Rule {
+    pub(crate) scanner: ScannerRule,
+    pub(crate) token_type: Ident,
+    pub(crate) token_sub_type: Option<Ident>,
+    pub(crate) options: Vec<Ident>,
+}
+
+pub(crate) enum ScannerRule {
+    Symbol(LitStr),
+    Regex(LitStr),
+    Block(LitStr, LitStr),
+    Eol(LitStr),
+}
+
+impl Parse for TokenizerDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut def = TokenizerDef {
+            kind: None,
+            constructor: None,
+            config: Vec::new(),
+            rules: Vec::new(),
+        };
+
+        while !input.is_empty() {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let vis: Visibility = input.parse()?;
+
+            if input.peek(Token![enum]) {
+                let keyword = input.parse::<Token![enum]>()?;
+                let name = input.parse()?;
+                input.parse::<Token![;]>()?;
+                if def.kind.is_some() {
+                    return Err(syn::Error::new(keyword.span, "the token kind enum is already declared"));
+                }
+                def.kind = Some(Item { attrs, vis, name });
+                continue;
+            }
+
+            if input.peek(Token![fn]) {
+                let keyword = input.parse::<Token![fn]>()?;
+                let name = input.parse()?;
+                input.parse::<Token![;]>()?;
+                if def.constructor.is_some() {
+                    return Err(syn::Error::new(keyword.span, "the constructor function is already declared"));
+                }
+                def.constructor = Some(Item { attrs, vis, name });
+                continue;
+            }
+
+            if let Some(attr) = attrs.first() {
+                return Err(syn::Error::new_spanned(attr, "attributes are only allowed on `enum` and `fn` declarations"));
+            }
+            if !matches!(vis, Visibility::Inherited) {
+                return Err(syn::Error::new_spanned(vis, "expected `enum` or `fn` after a visibility"));
+            }
+
+            let keyword: Ident = input.parse()?;
+            match keyword.to_string().as_str() {
+                "config" => {
+                    let content;
+                    braced!(content in input);
+                    while !content.is_empty() {
+                        let field: Ident = content.parse()?;
+                        content.parse::<Token![:]>()?;
+                        let value: Expr = content.parse()?;
+                        def.config.push((field, value));
+                        if !content.is_empty() {
+                            content.parse::<Token![,]>()?;
+                        }
+                    }
+                }
+                "symbol" => def.rules.push(Rule::parse_rest(input, ScannerRule::Symbol(input.parse()?))?),
+                "regex" => def.rules.push(Rule::parse_rest(input, ScannerRule::Regex(input.parse()?))?),
+                "eol" => def.rules.push(Rule::parse_rest(input, ScannerRule::Eol(input.parse()?))?),
+                "block" => {
+                    let start = input.parse()?;
+                    input.parse::<Token![..]>()?;
+                    let end = input.parse()?;
+                    def.rules.push(Rule::parse_rest(input, ScannerRule::Block(start, end))?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        keyword.span(),
+                        "expected `enum`, `fn`, `config`, `symbol`, `regex`, `block` or `eol`",
+                    ))
+                }
+            }
+        }
+
+        Ok(def)
+    }
+}
+
+impl Rule {
+    /// Parses `=> Type(SubType) { options };` after the scanner's delimiters
+    fn parse_rest(input: ParseStream, scanner: ScannerRule) -> Result<Self> {
+        input.parse::<Token![=>]>()?;
+        let token_type = input.parse()?;
+
+        let token_sub_type = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+
+        let mut options = Vec::new();
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            options = content
+                .parse_terminated(Ident::parse, Token![,])?
+                .into_iter()
+                .collect();
+        }
+        input.parse::<Token![;]>()?;
+
+        Ok(Rule {
+            scanner,
+            token_type,
+            token_sub_type,
+            options,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use crate::expand::expand;
+use crate::parse::TokenizerDef;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+fn expand_tokens(input: TokenStream) -> Result<TokenStream, Vec<String>> {
+    let def: TokenizerDef = syn::parse2(input).map_err(|e| vec![e.to_string()])?;
+    expand(def).map_err(|e| e.into_iter().map(|e| e.to_string()).collect())
+}
+
+fn errors(input: TokenStream) -> Vec<String> {
+    expand_tokens(input).expect_err("expansion should fail")
+}
+
+#[test]
+fn test_expands_valid_definition() {
+    let output = expand_tokens(quote! {
+        pub enum Kind;
+        pub fn make;
+        config { tokenize_whitespace: true }
+        symbol "+" => Operator(Plus);
+        regex r"^\d+" => Number;
+        block "/*" .. "*/" => Comment { nesting };
+        eol "//" => Comment(Line) { exclude_delimiter };
+    })
+    .unwrap()
+    .to_string();
+
+    assert!(output.contains("enum Kind"));
+    assert!(output.contains("Operator , Plus , Number , Comment , Line , Whitespace , Newline"));
+    assert!(output.contains("config . tokenize_whitespace = true"));
+    assert!(output.contains("add_block_scanner (\"/*\" , \"*/\" , Kind :: Comment , None , true , false , true)"));
+    assert!(output.contains("add_eol_scanner (\"//\" , Kind :: Comment , Some (Kind :: Line) , false)"));
+}
+
+#[test]
+fn test_invalid_regex_is_rejected() {
+    let errors = errors(quote! {
+        enum Kind;
+        fn make;
+        regex r"^(unclosed" => Bad;
+    });
+
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("invalid regex"), "{}", errors[0]);
+}
+
+#[test]
+fn test_unanchored_and_empty_matching_regexes_are_rejected() {
+    let errors = errors(quote! {
+        enum Kind;
+        fn make;
+        regex r"[a-z]+" => Word;
+        regex r"^\d*" => Number;
+    });
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("anchored"));
+    assert!(errors[1].contains("empty string"));
+}
+
+#[test]
+fn test_missing_declarations() {
+    let errors = errors(quote! {
+        symbol "+" => Plus;
+    });
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("token kind declaration"));
+    assert!(errors[1].contains("constructor declaration"));
+}
+
+#[test]
+fn test_unknown_options_and_config() {
+    let errors = errors(quote! {
+        enum Kind;
+        fn make;
+        config { tokenise_whitespace: true }
+        block "\"" .. "\"" => String { raw, nested };
+        symbol "+" => Plus { raw };
+    });
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("unknown config option `tokenise_whitespace`"));
+    assert!(errors[1].contains("unknown option `nested`"));
+    assert!(errors[2].contains("takes no options"));
+}
+
+#[test]
+fn test_duplicate_and_empty_symbols() {
+    let errors = errors(quote! {
+        enum Kind;
+        fn make;
+        symbol "+" => Plus;
+        symbol "+" => Add;
+        symbol "" => Nothing;
+        eol "" => Comment;
+    });
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("duplicate symbol"));
+    assert!(errors[1].contains("symbol must not be empty"));
+    assert!(errors[2].contains("EOL delimiter must not be empty"));
+}
+
+#[test]
+fn test_syntax_errors() {
+    assert!(errors(quote! { enum Kind; fn make; lexeme "x" => X; })[0].contains("expected `enum`"));
+    assert!(!errors(quote! { enum Kind; fn make; symbol "x" Plus; }).is_empty());
+    assert!(errors(quote! { enum Kind; enum Other; })[0].contains("already declared"));
+}
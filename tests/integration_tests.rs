@@ -10,6 +10,7 @@ mod integration_tests {
             continue_on_error: false,
             error_tolerance_limit: 1,
             track_token_positions: true,
+            ..TokenizerConfig::default()
         };
         let mut tokenizer = Tokenizer::with_config(config);
 
@@ -32,6 +33,6 @@ mod integration_tests {
         let json_input = r#"{"key": "value"}"#;
         let result = tokenizer.tokenize(json_input).expect("Tokenization failed");
 
-        assert_eq!(result.len(), 6, "Unexpected number of tokens");
+        assert_eq!(result.len(), 5, "Unexpected number of tokens");
     }
 }
\ No newline at end of file
@@ -6,6 +6,7 @@ fn get_json_tokenizer() -> Tokenizer {
         continue_on_error: false,
         error_tolerance_limit: 1,
         track_token_positions: true,
+        ..TokenizerConfig::default()
     };
     let mut tokenizer = Tokenizer::with_config(config);
 